@@ -0,0 +1,345 @@
+//! Typed async client for the bunctl daemon.
+//!
+//! This is the same IPC handling the CLI uses, packaged so other Rust
+//! programs can embed bunctl control without shelling out:
+//!
+//! ```no_run
+//! # async fn demo() -> Result<(), bunctl_client::ClientError> {
+//! use bunctl_client::BunctlClient;
+//!
+//! let mut client = BunctlClient::connect_default().await?;
+//! let status = client.status("my-api").await?;
+//! println!("{} is {}", status.name, status.state);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::path::Path;
+
+use bunctl_core::metrics::MetricSample;
+use bunctl_core::snapshot::DaemonSnapshot;
+use bunctl_core::{AppConfig, AppStatus, DaemonEvent};
+use bunctl_ipc::message::{
+    ClientInfo, ErrorCode, EventFilter, IpcRequest, IpcResponse, SubscriptionType,
+};
+use bunctl_ipc::{IpcClient, IpcError};
+use futures::Stream;
+use thiserror::Error;
+
+/// Errors surfaced by [`BunctlClient`].
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error(transparent)]
+    Ipc(#[from] IpcError),
+
+    /// The daemon answered with an error response.
+    #[error("daemon error ({code:?}): {message}")]
+    Daemon { code: ErrorCode, message: String },
+
+    /// The daemon answered with a response type the method did not expect.
+    #[error("unexpected response to {request}")]
+    UnexpectedResponse { request: &'static str },
+}
+
+/// An event pushed by the daemon to a subscribed client.
+#[derive(Debug, Clone)]
+pub struct Event {
+    /// Position in the daemon's emission order; gaps mean missed events.
+    pub seq: u64,
+    /// When the daemon emitted the event, RFC 3339.
+    pub ts: String,
+    /// App the event concerns, when app-scoped.
+    pub app: Option<String>,
+    pub event: DaemonEvent,
+}
+
+/// Typed handle to a daemon connection.
+pub struct BunctlClient {
+    inner: IpcClient,
+}
+
+impl BunctlClient {
+    /// Connect to the local daemon at the platform default socket path.
+    #[cfg(unix)]
+    pub async fn connect_default() -> Result<Self, ClientError> {
+        Self::connect(&bunctl_ipc::socket_path::default_socket_path()).await
+    }
+
+    /// Connect to the local daemon at `path`.
+    #[cfg(unix)]
+    pub async fn connect(path: &Path) -> Result<Self, ClientError> {
+        let mut client = Self { inner: IpcClient::connect(path).await? };
+        client.hello().await?;
+        Ok(client)
+    }
+
+    /// Connect to a remote daemon over TCP.
+    pub async fn connect_tcp(addr: &str, token: Option<&str>) -> Result<Self, ClientError> {
+        let mut client = Self { inner: IpcClient::connect_tcp(addr, token).await? };
+        client.hello().await?;
+        Ok(client)
+    }
+
+    /// Introduce this client (PID, username, version) so audit logs and
+    /// `status --clients` can attribute its commands.
+    async fn hello(&mut self) -> Result<(), ClientError> {
+        let user = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "unknown".into());
+        let req = IpcRequest::Hello {
+            pid: std::process::id(),
+            user,
+            version: env!("CARGO_PKG_VERSION").into(),
+        };
+        self.expect_success("hello", &req).await
+    }
+
+    /// Wrap an already-established IPC connection.
+    pub fn from_ipc(inner: IpcClient) -> Self {
+        Self { inner }
+    }
+
+    /// Use one fixed timeout for every request instead of the per-class
+    /// defaults (fast for queries, generous for lifecycle operations).
+    pub fn set_timeout(&mut self, timeout: std::time::Duration) {
+        self.inner.set_timeout(timeout);
+    }
+
+    /// Send a raw request. Prefer the typed methods; this exists for tools
+    /// (like the CLI) that build requests generically.
+    pub async fn request(&mut self, req: &IpcRequest) -> Result<IpcResponse, ClientError> {
+        Ok(self.inner.request(req).await?)
+    }
+
+    /// Register and start an app. Fails when it is already running.
+    pub async fn start(&mut self, config: AppConfig) -> Result<(), ClientError> {
+        let req = IpcRequest::Start { config: Box::new(config), force: false };
+        self.expect_success("start", &req).await
+    }
+
+    /// Blue/green swap: start a candidate with this config and promote it
+    /// once healthy; the old instance lingers briefly for rollback.
+    pub async fn blue_green(&mut self, config: AppConfig) -> Result<(), ClientError> {
+        self.expect_success("blue_green", &IpcRequest::BlueGreen { config: Box::new(config) })
+            .await
+    }
+
+    /// Bring an externally started process under management.
+    pub async fn adopt(&mut self, name: &str, pid: u32) -> Result<(), ClientError> {
+        self.expect_success("adopt", &IpcRequest::Adopt { name: name.into(), pid })
+            .await
+    }
+
+    /// Stop a running app.
+    pub async fn stop(&mut self, name: &str) -> Result<(), ClientError> {
+        self.expect_success("stop", &IpcRequest::Stop { name: name.into() }).await
+    }
+
+    /// Restart an app.
+    pub async fn restart(&mut self, name: &str) -> Result<(), ClientError> {
+        self.expect_success("restart", &IpcRequest::Restart { name: name.into(), config: None })
+            .await
+    }
+
+    /// Remove an app from the daemon, stopping it first. Whether its logs
+    /// are purged follows the app's configured default.
+    pub async fn delete(&mut self, name: &str) -> Result<(), ClientError> {
+        let req = IpcRequest::Delete { name: name.into(), purge_logs: None };
+        self.expect_success("delete", &req).await
+    }
+
+    /// Rename a stopped app, moving its logs and metrics with it.
+    pub async fn rename(&mut self, old: &str, new: &str) -> Result<(), ClientError> {
+        let req = IpcRequest::Rename { old: old.into(), new: new.into() };
+        self.expect_success("rename", &req).await
+    }
+
+    /// Status of a single app.
+    pub async fn status(&mut self, name: &str) -> Result<AppStatus, ClientError> {
+        match self.checked(&IpcRequest::Status { name: Some(name.into()) }).await? {
+            IpcResponse::Status(status) => Ok(*status),
+            _ => Err(ClientError::UnexpectedResponse { request: "status" }),
+        }
+    }
+
+    /// Status of all registered apps.
+    pub async fn status_all(&mut self) -> Result<Vec<AppStatus>, ClientError> {
+        match self.checked(&IpcRequest::Status { name: None }).await? {
+            IpcResponse::StatusList { statuses } => Ok(statuses),
+            _ => Err(ClientError::UnexpectedResponse { request: "status" }),
+        }
+    }
+
+    /// Status of one app plus its startup banner: the first lines of output
+    /// captured at the most recent start attempt.
+    pub async fn describe(
+        &mut self,
+        name: &str,
+    ) -> Result<(AppStatus, Vec<String>), ClientError> {
+        match self.checked(&IpcRequest::Describe { name: name.into() }).await? {
+            IpcResponse::Describe { status, banner } => Ok((*status, banner)),
+            _ => Err(ClientError::UnexpectedResponse { request: "describe" }),
+        }
+    }
+
+    /// Status of all registered apps; `all` includes orphan log entries.
+    pub async fn list(&mut self, all: bool) -> Result<Vec<AppStatus>, ClientError> {
+        match self.checked(&IpcRequest::List { all }).await? {
+            IpcResponse::StatusList { statuses } => Ok(statuses),
+            _ => Err(ClientError::UnexpectedResponse { request: "list" }),
+        }
+    }
+
+    /// Recent log lines for an app. `include_stopped` also reads logs of
+    /// apps that are no longer managed.
+    pub async fn logs(
+        &mut self,
+        name: &str,
+        lines: usize,
+        include_stopped: bool,
+    ) -> Result<Vec<String>, ClientError> {
+        let req = IpcRequest::Logs { name: name.into(), lines, include_stopped, grep: None };
+        match self.checked(&req).await? {
+            IpcResponse::Logs { lines } => Ok(lines),
+            _ => Err(ClientError::UnexpectedResponse { request: "logs" }),
+        }
+    }
+
+    /// Clients currently connected to the daemon.
+    pub async fn clients(&mut self) -> Result<Vec<ClientInfo>, ClientError> {
+        match self.checked(&IpcRequest::Clients).await? {
+            IpcResponse::Clients { clients } => Ok(clients),
+            _ => Err(ClientError::UnexpectedResponse { request: "clients" }),
+        }
+    }
+
+    /// Recorded health check results for an app, oldest first.
+    pub async fn health(
+        &mut self,
+        name: &str,
+    ) -> Result<Vec<bunctl_core::HealthRecord>, ClientError> {
+        match self.checked(&IpcRequest::Health { name: name.into() }).await? {
+            IpcResponse::Health { records } => Ok(records),
+            _ => Err(ClientError::UnexpectedResponse { request: "health" }),
+        }
+    }
+
+    /// The app's live process tree plus the daemon machine's clock rate
+    /// for converting `cpu_ticks` deltas to seconds.
+    pub async fn process_tree(
+        &mut self,
+        name: &str,
+    ) -> Result<(Vec<bunctl_core::ProcessNode>, u64), ClientError> {
+        match self.checked(&IpcRequest::ProcessTree { name: name.into() }).await? {
+            IpcResponse::ProcessTree { procs, clock_hz } => Ok((procs, clock_hz)),
+            _ => Err(ClientError::UnexpectedResponse { request: "process_tree" }),
+        }
+    }
+
+    /// The daemon's in-memory config snapshot of an app.
+    pub async fn config(&mut self, name: &str) -> Result<AppConfig, ClientError> {
+        match self.checked(&IpcRequest::GetConfig { name: name.into() }).await? {
+            IpcResponse::Config { config } => Ok(*config),
+            _ => Err(ClientError::UnexpectedResponse { request: "config" }),
+        }
+    }
+
+    /// Persisted resource samples for an app from the last `since_secs`
+    /// seconds.
+    pub async fn metrics(
+        &mut self,
+        name: &str,
+        since_secs: u64,
+    ) -> Result<Vec<MetricSample>, ClientError> {
+        let req = IpcRequest::Metrics { name: name.into(), since_secs };
+        match self.checked(&req).await? {
+            IpcResponse::Metrics { samples } => Ok(samples),
+            _ => Err(ClientError::UnexpectedResponse { request: "metrics" }),
+        }
+    }
+
+    /// Snapshot the daemon's full app registry.
+    pub async fn export(&mut self) -> Result<DaemonSnapshot, ClientError> {
+        match self.checked(&IpcRequest::Export).await? {
+            IpcResponse::Snapshot { snapshot } => Ok(*snapshot),
+            _ => Err(ClientError::UnexpectedResponse { request: "export" }),
+        }
+    }
+
+    /// Replay an exported snapshot into this daemon.
+    pub async fn import(&mut self, snapshot: DaemonSnapshot) -> Result<(), ClientError> {
+        self.expect_success("import", &IpcRequest::Import { snapshot: Box::new(snapshot) })
+            .await
+    }
+
+    /// Send several requests in one round-trip; responses come back in the
+    /// same order. Individual failures are plain `Error` entries, not an
+    /// `Err` of the whole batch.
+    pub async fn batch(
+        &mut self,
+        requests: Vec<IpcRequest>,
+    ) -> Result<Vec<IpcResponse>, ClientError> {
+        match self.checked(&IpcRequest::Batch { requests }).await? {
+            IpcResponse::Batch { responses } => Ok(responses),
+            _ => Err(ClientError::UnexpectedResponse { request: "batch" }),
+        }
+    }
+
+    /// Check the daemon is alive.
+    pub async fn ping(&mut self) -> Result<(), ClientError> {
+        self.expect_success("ping", &IpcRequest::Ping).await
+    }
+
+    /// Subscribe to daemon events. Consumes the client: the connection is
+    /// dedicated to the event stream from this point on.
+    pub async fn subscribe(
+        self,
+        subscription: SubscriptionType,
+        app: Option<String>,
+    ) -> Result<impl Stream<Item = Result<Event, ClientError>>, ClientError> {
+        self.subscribe_filtered(subscription, app, None).await
+    }
+
+    /// Like [`subscribe`](Self::subscribe) with a server-side
+    /// [`EventFilter`], so unwanted events never cross the wire.
+    pub async fn subscribe_filtered(
+        mut self,
+        subscription: SubscriptionType,
+        app: Option<String>,
+        filter: Option<EventFilter>,
+    ) -> Result<impl Stream<Item = Result<Event, ClientError>>, ClientError> {
+        self.expect_success("subscribe", &IpcRequest::Subscribe { subscription, app, filter })
+            .await?;
+        Ok(futures::stream::unfold(self.inner, |mut conn| async move {
+            loop {
+                match conn.read_response().await {
+                    Ok(IpcResponse::Event { seq, ts, app, event }) => {
+                        return Some((Ok(Event { seq, ts, app, event }), conn));
+                    }
+                    // Ignore any non-event frame interleaved on the stream.
+                    Ok(_) => continue,
+                    Err(IpcError::ConnectionClosed) => return None,
+                    Err(err) => return Some((Err(err.into()), conn)),
+                }
+            }
+        }))
+    }
+
+    async fn checked(&mut self, req: &IpcRequest) -> Result<IpcResponse, ClientError> {
+        match self.inner.request(req).await? {
+            IpcResponse::Error { code, message } => Err(ClientError::Daemon { code, message }),
+            resp => Ok(resp),
+        }
+    }
+
+    async fn expect_success(
+        &mut self,
+        request: &'static str,
+        req: &IpcRequest,
+    ) -> Result<(), ClientError> {
+        match self.checked(req).await? {
+            IpcResponse::Success { .. } => Ok(()),
+            _ => Err(ClientError::UnexpectedResponse { request }),
+        }
+    }
+}
@@ -0,0 +1,364 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Identifier of a managed application.
+///
+/// An `AppId` is always in sanitized form: lowercase ASCII alphanumerics and
+/// dashes. User-supplied names are normalized through [`AppId::new`] so the
+/// same id is derived regardless of where the name came from (CLI argument,
+/// config file, IPC message).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AppId(String);
+
+impl AppId {
+    /// Create an id from a user-supplied name, sanitizing it.
+    pub fn new(name: &str) -> Self {
+        let mut id = String::with_capacity(name.len());
+        for c in name.chars() {
+            match c {
+                'a'..='z' | '0'..='9' | '-' => id.push(c),
+                'A'..='Z' => id.push(c.to_ascii_lowercase()),
+                ' ' | '_' | '.' | '/' => id.push('-'),
+                _ => {}
+            }
+        }
+        // Collapse runs of dashes and trim them from the ends.
+        let mut collapsed = String::with_capacity(id.len());
+        let mut prev_dash = true;
+        for c in id.chars() {
+            if c == '-' {
+                if !prev_dash {
+                    collapsed.push('-');
+                }
+                prev_dash = true;
+            } else {
+                collapsed.push(c);
+                prev_dash = false;
+            }
+        }
+        while collapsed.ends_with('-') {
+            collapsed.pop();
+        }
+        AppId(collapsed)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether `name` is already in sanitized form, i.e. [`AppId::new`]
+    /// would keep it as-is. Distinct raw names can sanitize to the same id
+    /// (`"Test App"` and `"test-app"`); callers that must not silently
+    /// merge such apps use this to reject names needing normalization.
+    pub fn is_sanitized(name: &str) -> bool {
+        AppId::new(name).as_str() == name
+    }
+}
+
+impl fmt::Display for AppId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Lifecycle state of a managed application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppState {
+    /// Registered but not running.
+    Stopped,
+    /// Spawn requested; process not yet confirmed up.
+    Starting,
+    /// Process is up.
+    Running,
+    /// Stop requested; waiting for the process to exit.
+    Stopping,
+    /// Exited abnormally and is not being restarted.
+    Errored,
+    /// Restart budget exhausted; supervision is on hold until the rolling
+    /// window clears or an operator starts the app again.
+    Paused,
+}
+
+impl AppState {
+    /// Whether the lifecycle allows moving from `self` to `next`.
+    ///
+    /// The happy path is `Stopped → Starting → Running → Stopping →
+    /// Stopped`. `Running` can also fall to `Stopped`/`Errored` directly
+    /// (the process exited on its own) or back to `Starting` (supervised
+    /// restart), `Starting` can fail to `Errored` or be stopped before the
+    /// process is confirmed up, and `Errored` is left via `Starting`. A
+    /// crash loop that exhausts its restart budget moves to `Paused`, which
+    /// is left via `Starting` (window cleared or operator start) or
+    /// `Stopped` (operator stop). Staying in the same state is always
+    /// allowed. Callers that set state — the daemon funnels every change
+    /// through one place — use this to reject races like a monitor restart
+    /// flipping `Stopped` back to `Running` after an explicit stop.
+    pub fn can_transition_to(self, next: AppState) -> bool {
+        if self == next {
+            return true;
+        }
+        use AppState::{Errored, Paused, Running, Starting, Stopped, Stopping};
+        matches!(
+            (self, next),
+            (Starting, Running | Errored | Stopping | Stopped | Paused)
+                | (Running, Starting | Stopping | Stopped | Errored | Paused)
+                | (Stopping, Stopped | Errored)
+                | (Stopped | Errored, Starting)
+                | (Paused, Starting | Stopped)
+        )
+    }
+}
+
+impl fmt::Display for AppState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            AppState::Stopped => "stopped",
+            AppState::Starting => "starting",
+            AppState::Running => "running",
+            AppState::Stopping => "stopping",
+            AppState::Errored => "errored",
+            AppState::Paused => "paused",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Why a process exited, classified beyond the raw status code: a normal
+/// exit, a terminating signal, or an OOM kill (detected via the app's
+/// cgroup `memory.events` on Linux).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExitReason {
+    /// Exited on its own with this status code.
+    Code { code: i32 },
+    /// Killed by a signal; `name` is its symbolic form (`SIGKILL`) where
+    /// known.
+    Signal {
+        signal: i32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+    },
+    /// Killed by the kernel's out-of-memory killer.
+    OomKilled,
+    /// The exit status could not be observed (e.g. an adopted process that
+    /// was never our child).
+    Unknown,
+}
+
+impl fmt::Display for ExitReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExitReason::Code { code } => write!(f, "exit code {code}"),
+            ExitReason::Signal { signal, name: Some(name) } => {
+                write!(f, "signal {signal} ({name})")
+            }
+            ExitReason::Signal { signal, name: None } => write!(f, "signal {signal}"),
+            ExitReason::OomKilled => f.write_str("oom-killed"),
+            ExitReason::Unknown => f.write_str("unknown"),
+        }
+    }
+}
+
+/// Outcome of one health check attempt, as kept in the daemon's per-app
+/// history and returned from the `Health` IPC query.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthRecord {
+    /// Unix timestamp of the attempt.
+    pub ts: u64,
+    pub ok: bool,
+    /// How long the probe took (including a failed one).
+    pub latency_ms: u64,
+    /// What went wrong, when it did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// What one process generation was actually started with, captured at
+/// spawn time and shown by `bunctl describe`. Config edits after the spawn
+/// do not touch it, so it reflects the running process, not the registry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpawnInfo {
+    /// Unix timestamp of the spawn.
+    pub spawned_at: u64,
+    /// Which generation this is: 1 for the first spawn under this daemon,
+    /// plus one per restart.
+    pub generation: u64,
+    /// Fully resolved argv: the program (a pinned runtime where
+    /// applicable), interpreter flags, command and args.
+    pub argv: Vec<String>,
+    /// Effective extra environment of the child, with sensitive values
+    /// redacted and secret-sourced keys masked entirely.
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub env: std::collections::BTreeMap<String, String>,
+    /// First line of `<program> --version` at spawn time, when it ran.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interpreter_version: Option<String>,
+}
+
+/// One process in an app's tree — the main process or a descendant — as
+/// answered by the `ProcessTree` query and rendered by `bunctl top`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessNode {
+    pub pid: u32,
+    /// Parent pid; the root's parent lies outside the tree.
+    pub ppid: u32,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_bytes: Option<u64>,
+    /// Cumulative CPU time in clock ticks; the delta between two
+    /// snapshots divided by the clock rate gives a usage percentage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_ticks: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<u32>,
+}
+
+/// One member of an `instances` cluster, as carried in the aggregated
+/// status answering a query for the cluster's base name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceStatus {
+    /// 1-based member number within the cluster.
+    pub instance: u32,
+    pub state: AppState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_bytes: Option<u64>,
+}
+
+/// Point-in-time status snapshot of a managed application, as reported over
+/// IPC.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppStatus {
+    pub name: AppId,
+    /// The configured name before sanitization, when it differs from the
+    /// id (`"Test App"` for the id `test-app`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_name: Option<String>,
+    /// Which member of an `instances` cluster this app is (1-based), when
+    /// it came out of an expansion.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance_id: Option<u32>,
+    /// How many members the cluster has, when part of one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instances_total: Option<u32>,
+    /// Per-member detail when this status aggregates a whole cluster
+    /// (querying the base name of an `instances` app), sorted by member
+    /// number; empty for a single app or an individual member.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub instances: Vec<InstanceStatus>,
+    /// What the current process generation was started with; only filled
+    /// in `describe` responses, and absent until the first spawn.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spawn_info: Option<SpawnInfo>,
+    /// The app's configured description, when set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The app's configured tags.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    pub state: AppState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_percent: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uptime_secs: Option<u64>,
+    /// Combined readiness verdict, when readiness health checks are
+    /// configured: `false` means up but not fit for traffic.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ready: Option<bool>,
+    /// On-demand apps: what the daemon is doing with the public address,
+    /// e.g. holding it waiting for a connection, or proxying to the app.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_demand: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_files: Option<u32>,
+    /// Bun heap usage in bytes, when the app opts into runtime stats.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heap_bytes: Option<u64>,
+    /// Bun event loop lag in milliseconds, when runtime stats are enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_loop_lag_ms: Option<f64>,
+    /// Absolute working directory of the process, when one is configured
+    /// (relative `cwd` values are resolved against the config file at load
+    /// time).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<std::path::PathBuf>,
+    /// Selected env vars of the app. The daemon chooses which keys to
+    /// expose and redacts sensitive values before they travel over IPC.
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub env: std::collections::BTreeMap<String, String>,
+    pub restarts: u64,
+    /// Why the process last exited, once it has exited at least once under
+    /// this daemon.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_exit_reason: Option<ExitReason>,
+    /// Capture-filter counters (dropped/sampled lines), when the app has
+    /// `log_filters` configured.
+    #[serde(default, skip_serializing_if = "crate::metrics::LogMetrics::is_empty")]
+    pub log_metrics: crate::metrics::LogMetrics,
+    /// Not currently managed: only a leftover log file exists for this name.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub orphan: bool,
+    /// Recent CPU-percent samples, oldest first (a few seconds apart).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cpu_history: Vec<f32>,
+    /// Recent memory samples in bytes, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub memory_history: Vec<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn app_id_sanitizes_names() {
+        assert_eq!(AppId::new("my-api").as_str(), "my-api");
+        assert_eq!(AppId::new("My API").as_str(), "my-api");
+        assert_eq!(AppId::new("sites/acme/web").as_str(), "sites-acme-web");
+        assert_eq!(AppId::new("--weird--").as_str(), "weird");
+    }
+
+    #[test]
+    fn is_sanitized_spots_names_that_would_change() {
+        assert!(AppId::is_sanitized("my-api"));
+        assert!(!AppId::is_sanitized("My API"));
+        assert!(!AppId::is_sanitized("sites/acme/web"));
+        // Distinct raw names can collide on the same id.
+        assert_eq!(AppId::new("Test App"), AppId::new("test-app"));
+    }
+
+    #[test]
+    fn state_transitions_follow_the_lifecycle() {
+        use AppState::*;
+        assert!(Stopped.can_transition_to(Starting));
+        assert!(Starting.can_transition_to(Running));
+        assert!(Running.can_transition_to(Stopping));
+        assert!(Stopping.can_transition_to(Stopped));
+        // Crash, supervised restart, and a stop racing a slow start.
+        assert!(Running.can_transition_to(Errored));
+        assert!(Errored.can_transition_to(Starting));
+        assert!(Starting.can_transition_to(Stopped));
+        // Restart budget exhausted, then resumed or stopped by hand.
+        assert!(Running.can_transition_to(Paused));
+        assert!(Paused.can_transition_to(Starting));
+        assert!(Paused.can_transition_to(Stopped));
+        // A stopped app cannot be flipped straight to running.
+        assert!(!Stopped.can_transition_to(Running));
+        assert!(!Stopped.can_transition_to(Stopping));
+        assert!(!Errored.can_transition_to(Running));
+        assert!(!Stopping.can_transition_to(Running));
+        assert!(!Paused.can_transition_to(Running));
+    }
+}
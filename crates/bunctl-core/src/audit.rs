@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// One state-changing management action, as recorded in the daemon's
+/// append-only audit log (who/what/when/result).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Seconds since the Unix epoch.
+    pub ts: u64,
+    /// Where the command came from: `uid:<n>` for local socket clients,
+    /// the remote address for TCP clients.
+    pub peer: String,
+    /// The IPC command, e.g. `start`, `stop`, `delete`.
+    pub action: String,
+    /// The app the command targeted, when it targets one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app: Option<String>,
+    /// Whether the daemon executed it successfully.
+    pub success: bool,
+    /// Error message on failure.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
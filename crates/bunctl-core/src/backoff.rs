@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// How restart delays grow between consecutive failed attempts, selectable
+/// per app (`"backoff": {"strategy": "fibonacci"}`).
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum BackoffKind {
+    /// Doubling delays: base, 2×base, 4×base, … capped at the maximum.
+    #[default]
+    Exponential,
+    /// Delays grow by one base per attempt: base, 2×base, 3×base, …
+    Linear,
+    /// Fibonacci growth — gentler than doubling early on, still reaching
+    /// the cap: base, base, 2×base, 3×base, 5×base, …
+    Fibonacci,
+    /// An explicit per-attempt schedule in seconds, for services that need
+    /// long cool-downs (`{"strategy": "schedule", "delays": [1, 5, 30,
+    /// 300]}`). The last entry repeats once the schedule is exhausted, and
+    /// the cap does not apply: the delays mean exactly what they say.
+    Schedule { delays: Vec<u64> },
+}
+
+impl BackoffKind {
+    pub fn is_exponential(&self) -> bool {
+        *self == Self::Exponential
+    }
+}
+
+/// Backoff between restart attempts of a crashing app; the growth curve is
+/// chosen by a [`BackoffKind`].
+#[derive(Debug, Clone)]
+pub struct BackoffStrategy {
+    kind: BackoffKind,
+    base: Duration,
+    max: Duration,
+    attempt: u32,
+}
+
+impl BackoffStrategy {
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self { kind: BackoffKind::Exponential, base, max, attempt: 0 }
+    }
+
+    /// The default base and cap with the given growth curve.
+    pub fn with_kind(kind: BackoffKind) -> Self {
+        Self { kind, ..Self::default() }
+    }
+
+    /// Delay before the next restart attempt, per the strategy's curve.
+    pub fn next_delay(&mut self) -> Duration {
+        let attempt = self.attempt;
+        self.attempt = self.attempt.saturating_add(1);
+        match &self.kind {
+            BackoffKind::Exponential => {
+                self.base.saturating_mul(1u32 << attempt.min(16)).min(self.max)
+            }
+            BackoffKind::Linear => {
+                self.base.saturating_mul(attempt.saturating_add(1)).min(self.max)
+            }
+            BackoffKind::Fibonacci => self.base.saturating_mul(fibonacci(attempt)).min(self.max),
+            BackoffKind::Schedule { delays } => delays
+                .get(attempt as usize)
+                .or_else(|| delays.last())
+                .map_or(self.base, |&secs| Duration::from_secs(secs)),
+        }
+    }
+
+    /// Number of consecutive failed attempts so far.
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// Call after the app has been up long enough to count as stable.
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
+
+impl Default for BackoffStrategy {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(500), Duration::from_secs(30))
+    }
+}
+
+/// `1, 1, 2, 3, 5, …`, saturating instead of overflowing.
+fn fibonacci(n: u32) -> u32 {
+    let (mut a, mut b) = (1u32, 1u32);
+    for _ in 0..n {
+        (a, b) = (b, a.saturating_add(b));
+    }
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn doubles_up_to_max() {
+        let mut b = BackoffStrategy::new(Duration::from_secs(1), Duration::from_secs(8));
+        assert_eq!(b.next_delay(), Duration::from_secs(1));
+        assert_eq!(b.next_delay(), Duration::from_secs(2));
+        assert_eq!(b.next_delay(), Duration::from_secs(4));
+        assert_eq!(b.next_delay(), Duration::from_secs(8));
+        assert_eq!(b.next_delay(), Duration::from_secs(8));
+        b.reset();
+        assert_eq!(b.next_delay(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn linear_grows_by_base() {
+        let mut b = BackoffStrategy::with_kind(BackoffKind::Linear);
+        assert_eq!(b.next_delay(), Duration::from_millis(500));
+        assert_eq!(b.next_delay(), Duration::from_secs(1));
+        assert_eq!(b.next_delay(), Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn fibonacci_grows_gently_then_caps() {
+        let mut b = BackoffStrategy::with_kind(BackoffKind::Fibonacci);
+        let delays: Vec<u64> = (0..6).map(|_| b.next_delay().as_millis() as u64).collect();
+        assert_eq!(delays, [500, 500, 1000, 1500, 2500, 4000]);
+        for _ in 0..20 {
+            b.next_delay();
+        }
+        assert_eq!(b.next_delay(), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn schedule_repeats_its_last_entry_uncapped() {
+        let mut b = BackoffStrategy::with_kind(BackoffKind::Schedule {
+            delays: vec![1, 5, 300],
+        });
+        assert_eq!(b.next_delay(), Duration::from_secs(1));
+        assert_eq!(b.next_delay(), Duration::from_secs(5));
+        assert_eq!(b.next_delay(), Duration::from_secs(300));
+        assert_eq!(b.next_delay(), Duration::from_secs(300));
+    }
+}
@@ -0,0 +1,1160 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::backoff::BackoffKind;
+use crate::Error;
+
+/// Default config file name, looked up in the working directory.
+pub const CONFIG_FILE: &str = "bunctl.json";
+
+/// Top-level `bunctl.json` contents.
+///
+/// Two conveniences are resolved at parse time and never reach
+/// [`AppConfig`]: a `"defaults"` object whose keys fill in fields an app
+/// entry leaves out (`env` merges key-by-key, the app's entries winning),
+/// and an `"instances": N` key on an app entry that expands it into N
+/// copies with `{i}` replaced by the instance number (`1..=N`) in every
+/// string field; a name without `{i}` gets `-{i}` appended.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BunctlConfig {
+    #[serde(default)]
+    pub apps: Vec<AppConfig>,
+}
+
+impl BunctlConfig {
+    /// Load and parse a config file, resolving `extends` and `include`
+    /// references relative to the file's directory.
+    ///
+    /// `"extends": "./base.bunctl.json"` lays this file's keys over the
+    /// base's (`apps` concatenate, `defaults` merge key-by-key).
+    /// `"include": ["apps/*.json"]` appends the apps of each matched file;
+    /// only the file name part of a pattern may contain `*`. Reference
+    /// cycles are an error.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let mut visited = Vec::new();
+        let mut root = load_document(path, &mut visited)?;
+        expand(&mut root);
+        let mut config: Self = serde_json::from_value(root)?;
+        // Relative paths in a config file mean "next to this file", not
+        // "wherever the daemon happens to run"; pin them down here so the
+        // daemon only ever sees absolute paths.
+        let dir = path
+            .canonicalize()
+            .unwrap_or_else(|_| path.to_path_buf())
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        for app in &mut config.apps {
+            app.resolve_paths(&dir);
+        }
+        Ok(config)
+    }
+
+    /// Parse config text, applying the `defaults` section and expanding
+    /// `instances` templates. File references (`extends`, `include`) are
+    /// only resolved by [`load`](Self::load).
+    pub fn parse(data: &str) -> Result<Self, Error> {
+        let mut root: serde_json::Value = serde_json::from_str(data)?;
+        expand(&mut root);
+        Ok(serde_json::from_value(root)?)
+    }
+
+    /// Find an app entry by (unsanitized) name.
+    pub fn app(&self, name: &str) -> Option<&AppConfig> {
+        let id = crate::AppId::new(name);
+        self.apps.iter().find(|a| crate::AppId::new(&a.name) == id)
+    }
+}
+
+/// Declarative configuration of a single managed application.
+///
+/// This is what lives in `bunctl.json` under `"apps"` and what the CLI sends
+/// to the daemon on `start`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppConfig {
+    pub name: String,
+    /// Free-form one-liner shown in `bunctl status`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Labels for grouping and filtering (`bunctl list --tag worker`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Which member of an `instances` cluster this entry is (1-based).
+    /// Filled in by the `instances` expansion, not written by hand.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<u32>,
+    /// How many members the cluster was expanded into. Together with
+    /// `instance` this lets status output group the members back together.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instances_total: Option<u32>,
+    /// How the app is executed: a raw process (default) or a container
+    /// driven through a runtime CLI like docker/podman.
+    #[serde(default, skip_serializing_if = "ExecKind::is_process")]
+    pub exec_kind: ExecKind,
+    /// Container runtime binary for `exec_kind: container` (`docker` when
+    /// omitted; set to `podman` for Podman).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<String>,
+    /// Executable to run (e.g. `bun`), or the image for
+    /// `exec_kind: container`.
+    pub command: String,
+    /// Pin a specific Bun release for this app (e.g. `"1.1.8"`). The daemon
+    /// resolves it from the per-user versions directory, downloading it on
+    /// demand, so different apps can run different runtimes. Only applies
+    /// when `command` is `bun`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bun_version: Option<String>,
+    /// Interpreter to launch `command` with (e.g. `node`, `deno run`-less
+    /// `deno`); when set, `command` is the script and the process spawned is
+    /// `interpreter [interpreter_args...] command [args...]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interpreter: Option<String>,
+    /// Flags for the interpreter itself, placed before the script (e.g.
+    /// `["--max-old-space-size=2048"]`). No shell parsing is involved.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interpreter_args: Vec<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Working directory for the process.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    /// Create `cwd` (and missing parents) at start instead of failing when
+    /// it does not exist yet.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub create_cwd: bool,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Env values the daemon resolves at spawn time (from a file or a
+    /// command) and injects into the child only; they are never persisted
+    /// or echoed in status output or logs.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env_secrets: BTreeMap<String, SecretSource>,
+    /// Which env keys appear in status output (`["PORT", "FEATURE_*"]`; a
+    /// trailing `*` matches a prefix). When empty, a built-in set of common
+    /// operational prefixes is shown. The daemon's redaction deny-list
+    /// still applies to whatever is selected.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub status_env: Vec<String>,
+    /// Restart the process when it exits.
+    #[serde(default = "default_true")]
+    pub autorestart: bool,
+    /// Give up after this many consecutive failed restarts. `None` means
+    /// unlimited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_restarts: Option<u32>,
+    /// Restart budget: at most this many restarts within `window_secs` of
+    /// each other. Exhausting the budget pauses the app until the oldest
+    /// restart falls out of the window or an operator starts it again.
+    /// Unlike `max_restarts` this survives stable runs: a service that
+    /// crashes once an hour never trips `max_restarts` (backoff resets)
+    /// but does burn its budget. Off when omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_restarts_per_window: Option<u32>,
+    /// Length of the `max_restarts_per_window` rolling window; one hour
+    /// when omitted.
+    #[serde(default = "default_window_secs")]
+    pub window_secs: u64,
+    /// How restart delays grow between failed attempts: exponential (the
+    /// default), `linear`, `fibonacci`, or an explicit `schedule` of
+    /// delays in seconds.
+    #[serde(default, skip_serializing_if = "BackoffKind::is_exponential")]
+    pub backoff: BackoffKind,
+    /// Recycle on a schedule: a five-field cron expression in UTC, e.g.
+    /// `"0 4 * * *"` for 4am daily, for apps that need a periodic restart
+    /// anyway (third-party code that leaks). The daemon restarts matching
+    /// apps gracefully, staggered a few seconds apart so a shared schedule
+    /// does not take everything down at once.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart_cron: Option<String>,
+    /// Gracefully recycle the app once it has run this long, for processes
+    /// that degrade with age. Checked against the sampler's clock, so it
+    /// fires within a few seconds of the limit.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "duration_secs_opt")]
+    pub max_uptime: Option<Duration>,
+    /// Gracefully recycle once memory has grown this many percent above
+    /// its steady baseline — the first sample taken after the run counts
+    /// as stable — catching slow leaks long before `max_memory` would kill
+    /// the process.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recycle_on_memory_growth_percent: Option<f64>,
+    /// On-demand mode for dev environments: the daemon holds the app's
+    /// public address, starts the app at the first incoming connection,
+    /// proxies traffic to the app's own port and stops it again after an
+    /// idle timeout — from the outside the port is simply always there.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_demand: Option<OnDemand>,
+    /// Fail the app if it has not reached a stable run within this long of
+    /// the start request (spawn retries included). Unlimited when omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "duration_secs_opt")]
+    pub start_timeout: Option<Duration>,
+    /// Signal that starts the stop sequence (e.g. `TERM`, `INT`, `USR2`);
+    /// SIGTERM when omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_signal: Option<String>,
+    /// Grace period between the stop signal and escalation.
+    #[serde(default = "default_stop_timeout", with = "duration_secs")]
+    pub stop_timeout: Duration,
+    /// Optional intermediate signal sent after `stop_timeout`, for apps
+    /// with a separate drain phase; the hard kill follows it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub escalation_signal: Option<String>,
+    /// Grace period after `escalation_signal` before the hard kill
+    /// (defaults to `stop_timeout`).
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "duration_secs_opt")]
+    pub escalation_timeout: Option<Duration>,
+    /// How long to wait after the hard kill before giving up on the process.
+    #[serde(default = "default_kill_timeout", with = "duration_secs")]
+    pub kill_timeout: Duration,
+    /// Memory limit in bytes; exceeding it restarts the app. Containers
+    /// get it as the runtime's `--memory`; processes are restarted by the
+    /// daemon when a resource sample exceeds it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_memory: Option<u64>,
+    /// CPU usage limit in percent of one core.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cpu_percent: Option<f64>,
+    /// How `max_cpu_percent` is applied: `hard` enforces it (cgroup
+    /// `cpu.max` on Linux, `--cpus` for containers), `soft` only emits
+    /// `ResourceLimitExceeded` events while usage stays above the limit.
+    #[serde(default, skip_serializing_if = "CpuLimitMode::is_hard")]
+    pub cpu_limit_mode: CpuLimitMode,
+    /// CPU cores the app is pinned to, e.g. `[0, 1]` — for latency-sensitive
+    /// services that must not share cores with batch workloads. Empty means
+    /// no pinning.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cpu_affinity: Vec<u32>,
+    /// GPU indices the app may use, e.g. `[0]` to pin an inference service
+    /// to one card. Plain processes get `CUDA_VISIBLE_DEVICES` and
+    /// `NVIDIA_VISIBLE_DEVICES` exported (the app's own env wins);
+    /// containers get `--gpus device=…` for runtime-level isolation.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub gpus: Vec<u32>,
+    /// Run the app in a private network namespace with only loopback up
+    /// (Linux, needs a root daemon): whatever the app binds is invisible
+    /// outside the namespace, so internal tools cannot end up exposed on
+    /// all interfaces by accident.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub private_network: bool,
+    /// Ports the daemon forwards from the host's `127.0.0.1` into the
+    /// private namespace, making those — and only those — reachable from
+    /// the local machine. Only meaningful with `private_network`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub forward_ports: Vec<u16>,
+    /// Run the app under a different Windows account instead of the
+    /// daemon's own (often elevated) identity. Ignored with a warning on
+    /// other platforms.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_as: Option<RunAs>,
+    /// Put the app's process in a Windows job object with these flags.
+    /// Ignored with a warning on other platforms.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_object: Option<JobObjectConfig>,
+    /// Collect Bun runtime stats: the daemon exports `BUNCTL_STATS_FILE`
+    /// and samples the JSON the app periodically writes there (keys
+    /// `heap_used` and `event_loop_lag_ms`, e.g. from a small preload that
+    /// polls `bun:jsc` / `performance`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bun_stats: bool,
+    /// Collapse consecutive identical log lines into a single
+    /// `last message repeated N times` entry instead of writing each one.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub log_dedup: bool,
+    /// Strip ANSI escape sequences (colors, cursor movement) from captured
+    /// output before it reaches the log file. Live subscribers still get
+    /// the raw line.
+    #[serde(default = "default_true")]
+    pub strip_ansi: bool,
+    /// Attach indented continuation lines (stack traces) to the previous
+    /// log entry instead of starting a new timestamped one.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub log_multiline: bool,
+    /// Extra `*`-wildcard pattern for continuation lines that are not
+    /// indented, e.g. `"Caused by:*"`. Implies the `log_multiline` check.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_continuation: Option<String>,
+    /// Periodic health checks run while the app is `Running`; results are
+    /// kept per app and shown by `bunctl health <app>`. With several checks
+    /// the combined verdict follows `health_policy`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub health_checks: Vec<HealthCheck>,
+    /// How several `health_checks` combine into one verdict.
+    #[serde(default, skip_serializing_if = "HealthPolicy::is_all")]
+    pub health_policy: HealthPolicy,
+    /// What a failing liveness verdict does once `health_failures`
+    /// consecutive rounds have failed.
+    #[serde(default, skip_serializing_if = "HealthAction::is_restart")]
+    pub health_on_failure: HealthAction,
+    /// Consecutive failing liveness rounds before `health_on_failure`
+    /// fires (and before it fires again while still failing).
+    #[serde(default = "default_health_failures")]
+    pub health_failures: u32,
+    /// Capture-time filters applied to each line, in order; the first
+    /// matching filter decides. Counters of what they removed show up in
+    /// status as `log_metrics`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub log_filters: Vec<LogFilter>,
+    /// On-disk log format: prefixed text (default) or one JSON object per
+    /// line. In JSON mode a captured line that is itself a JSON object is
+    /// embedded as a nested `payload` instead of re-wrapped as a string.
+    #[serde(default, skip_serializing_if = "LogFormat::is_text")]
+    pub log_format: LogFormat,
+    /// Rotate the log file once it grows past this size, e.g. `10M`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_max_size: Option<String>,
+    /// Record size, SHA-256 and time range of every rotated log file in a
+    /// tamper-evident manifest (`bunctl logs <app> --verify` checks it).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub log_manifest: bool,
+    /// Remove the app's log files and metrics when it is deleted; the
+    /// `--purge-logs` / `--keep-logs` flags of `bunctl delete` override
+    /// this default per invocation.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub purge_logs: bool,
+    /// Allow the process to write core dumps on a crash (`setrlimit CORE`
+    /// at spawn). A dump left behind is moved into the log directory as
+    /// `<app>.core.<timestamp>`; its path is carried in the exit event.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub core_dumps: bool,
+    /// How many collected core dumps to keep per app; older ones are
+    /// deleted when a new dump comes in.
+    #[serde(default = "default_keep_core_dumps")]
+    pub keep_core_dumps: u32,
+    /// Warn when the process holds more than this many open descriptors.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_open_files: Option<u32>,
+    /// Deploy workflow for this app (`bunctl deploy`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deploy: Option<DeployConfig>,
+}
+
+impl AppConfig {
+    /// Resolve this app's relative paths (`cwd`, `env_secrets` files, the
+    /// deploy working copy) against `dir`, the directory of the config file
+    /// they were written in. Absolute paths are left alone.
+    pub fn resolve_paths(&mut self, dir: &Path) {
+        if let Some(cwd) = &mut self.cwd {
+            if cwd.is_relative() {
+                *cwd = dir.join(cwd.as_path());
+            }
+        }
+        for source in self.env_secrets.values_mut() {
+            if let SecretSource::File { path } = source {
+                if path.is_relative() {
+                    *path = dir.join(path.as_path());
+                }
+            }
+        }
+        if let Some(RunAs { password: Some(SecretSource::File { path }), .. }) = &mut self.run_as {
+            if path.is_relative() {
+                *path = dir.join(path.as_path());
+            }
+        }
+        if let Some(deploy) = &mut self.deploy {
+            if deploy.path.is_relative() {
+                deploy.path = dir.join(&deploy.path);
+            }
+        }
+        for check in &mut self.health_checks {
+            if let HealthCheckType::Http { ca_file: Some(path), .. } = &mut check.check {
+                if path.is_relative() {
+                    *path = dir.join(path.as_path());
+                }
+            }
+        }
+    }
+}
+
+impl Default for AppConfig {
+    /// An empty config with the same defaults serde applies to omitted
+    /// fields; `name` and `command` must be filled in by the caller.
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            description: None,
+            tags: Vec::new(),
+            instance: None,
+            instances_total: None,
+            exec_kind: ExecKind::Process,
+            runtime: None,
+            command: String::new(),
+            bun_version: None,
+            interpreter: None,
+            interpreter_args: Vec::new(),
+            args: Vec::new(),
+            cwd: None,
+            create_cwd: false,
+            env: BTreeMap::new(),
+            env_secrets: BTreeMap::new(),
+            status_env: Vec::new(),
+            autorestart: true,
+            max_restarts: None,
+            max_restarts_per_window: None,
+            window_secs: default_window_secs(),
+            backoff: BackoffKind::Exponential,
+            restart_cron: None,
+            max_uptime: None,
+            recycle_on_memory_growth_percent: None,
+            on_demand: None,
+            start_timeout: None,
+            stop_signal: None,
+            stop_timeout: default_stop_timeout(),
+            escalation_signal: None,
+            escalation_timeout: None,
+            kill_timeout: default_kill_timeout(),
+            max_memory: None,
+            max_cpu_percent: None,
+            cpu_limit_mode: CpuLimitMode::Hard,
+            cpu_affinity: Vec::new(),
+            gpus: Vec::new(),
+            private_network: false,
+            forward_ports: Vec::new(),
+            run_as: None,
+            job_object: None,
+            bun_stats: false,
+            log_dedup: false,
+            strip_ansi: true,
+            log_multiline: false,
+            log_continuation: None,
+            health_checks: Vec::new(),
+            health_policy: HealthPolicy::All,
+            health_on_failure: HealthAction::Restart,
+            health_failures: default_health_failures(),
+            log_filters: Vec::new(),
+            log_format: LogFormat::Text,
+            log_max_size: None,
+            log_manifest: false,
+            purge_logs: false,
+            core_dumps: false,
+            keep_core_dumps: default_keep_core_dumps(),
+            max_open_files: None,
+            deploy: None,
+        }
+    }
+}
+
+/// On-demand (socket-activation style) settings (`"on_demand": {"listen":
+/// "127.0.0.1:3000", "forward": "127.0.0.1:3001"}`). The app must listen
+/// on `forward`, not `listen` — the daemon keeps the latter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnDemand {
+    /// Address clients connect to, held by the daemon.
+    pub listen: String,
+    /// Address the app itself listens on once up; `listen` connections
+    /// are proxied here.
+    pub forward: String,
+    /// Stop the app once no connection has been open for this long; five
+    /// minutes when omitted.
+    #[serde(default = "default_idle_timeout", with = "duration_secs")]
+    pub idle_timeout: Duration,
+}
+
+/// Whether `max_cpu_percent` throttles the app or only raises alerts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CpuLimitMode {
+    /// Enforced by the platform; usage cannot exceed the limit.
+    #[default]
+    Hard,
+    /// Alert-only: exceeding the limit emits `ResourceLimitExceeded`
+    /// events but never throttles or restarts the app.
+    Soft,
+}
+
+impl CpuLimitMode {
+    fn is_hard(&self) -> bool {
+        *self == Self::Hard
+    }
+}
+
+/// Where a secret env value is read from at spawn time
+/// (`"env_secrets": {"DB_PASSWORD": {"from": "file", "path": "..."}}`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "from", rename_all = "snake_case")]
+pub enum SecretSource {
+    /// Contents of a file, e.g. a systemd/docker secret mount; a trailing
+    /// newline is trimmed.
+    File { path: PathBuf },
+    /// Trimmed stdout of a command, e.g. a cloud secret-manager CLI. An
+    /// explicit argv array; nothing is passed through a shell.
+    Command { argv: Vec<String> },
+}
+
+/// A different account to run an app under (`run_as`, Windows).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunAs {
+    /// Account name, optionally qualified as `DOMAIN\user`.
+    pub user: String,
+    /// Where the account password comes from — a reference like
+    /// `env_secrets` values (file or command), never an inline value.
+    /// Omitted for accounts that log on without one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<SecretSource>,
+    /// Filled from `password` at spawn time, on the throwaway spawn config
+    /// only; never serialized.
+    #[serde(skip)]
+    pub resolved_password: Option<String>,
+}
+
+/// Flags of the Windows job object an app runs in (`job_object`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobObjectConfig {
+    /// Children may leave the job with `CREATE_BREAKAWAY_FROM_JOB`
+    /// (`JOB_OBJECT_LIMIT_BREAKAWAY_OK`) — for tools like MSBuild that
+    /// spawn long-lived build daemons meant to outlive the app.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub breakaway_ok: bool,
+    /// Children leave the job automatically, without asking for it
+    /// (`JOB_OBJECT_LIMIT_SILENT_BREAKAWAY_OK`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub silent_breakaway: bool,
+    /// Kill every process still in the job when the daemon's handle
+    /// closes (`JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE`), so nothing escapes
+    /// supervision even if the daemon dies.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub kill_on_close: bool,
+}
+
+/// A periodic health check: what to probe and how often.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthCheck {
+    #[serde(flatten)]
+    pub check: HealthCheckType,
+    /// Seconds between attempts.
+    #[serde(default = "default_health_interval")]
+    pub interval: u64,
+    /// Seconds before one attempt counts as failed.
+    #[serde(default = "default_health_timeout")]
+    pub timeout: u64,
+    /// What a failing verdict means for the app.
+    #[serde(default, skip_serializing_if = "HealthRole::is_liveness")]
+    pub role: HealthRole,
+}
+
+/// What a health check guards, mirroring the Kubernetes split: liveness
+/// failures restart the app, readiness failures only mark it not ready.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthRole {
+    /// The app is broken beyond recovery; a failing verdict restarts it.
+    #[default]
+    Liveness,
+    /// The app is up but not fit for traffic; a failing verdict sets
+    /// `ready: false` in status without touching the process.
+    Readiness,
+}
+
+impl HealthRole {
+    fn is_liveness(&self) -> bool {
+        *self == Self::Liveness
+    }
+}
+
+/// Remediation applied when an app's liveness verdict keeps failing
+/// (`"health_on_failure": {"action": "exec", "argv": [...]}`).
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum HealthAction {
+    /// Restart the app.
+    #[default]
+    Restart,
+    /// Run a remediation command (an explicit argv array, no shell) with
+    /// `BUNCTL_APP` set to the app id; the process itself is left alone.
+    Exec { argv: Vec<String> },
+    /// Stop the app and leave it stopped.
+    Stop,
+    /// Do nothing beyond the `HealthChanged` event.
+    None,
+}
+
+impl HealthAction {
+    fn is_restart(&self) -> bool {
+        *self == Self::Restart
+    }
+}
+
+/// What a health check probes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HealthCheckType {
+    /// Request the URL and expect a 2xx status. `https://` URLs need a
+    /// daemon built with its `tls` feature, plus either `ca_file` or
+    /// `insecure_tls` — the prober reads no system roots, matching how the
+    /// TCP transport is configured from PEM files.
+    Http {
+        url: String,
+        /// Request method, `GET` when omitted.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        method: Option<String>,
+        /// Extra request headers, e.g. an auth token
+        /// (`{"Authorization": "Bearer ..."}`).
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        headers: BTreeMap<String, String>,
+        /// `*`-wildcard pattern the response body must match on top of the
+        /// 2xx status, e.g. `"*\"status\":\"ok\"*"`. Deliberately a
+        /// wildcard rather than a regex, to keep a regex engine out of the
+        /// daemon; `*` spans any run of characters, which covers the
+        /// "does the body mention X" checks this is for.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        body_match: Option<String>,
+        /// PEM file with the CA certificate(s) to trust for `https://`
+        /// URLs.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ca_file: Option<PathBuf>,
+        /// Skip certificate verification for `https://` URLs (self-signed
+        /// dev endpoints without a CA file at hand).
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        insecure_tls: bool,
+    },
+    /// A TCP connect to `addr` (`host:port`) succeeds.
+    Tcp { addr: String },
+    /// The command exits 0.
+    Exec { argv: Vec<String> },
+}
+
+impl HealthCheckType {
+    /// Short component label (`http <url>`, `tcp <addr>`, `exec <cmd>`)
+    /// used when a composite check reports which part failed.
+    pub fn label(&self) -> String {
+        match self {
+            HealthCheckType::Http { url, .. } => format!("http {url}"),
+            HealthCheckType::Tcp { addr } => format!("tcp {addr}"),
+            HealthCheckType::Exec { argv } => {
+                format!("exec {}", argv.first().map(String::as_str).unwrap_or(""))
+            }
+        }
+    }
+}
+
+/// Combinator applied when an app has several `health_checks`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthPolicy {
+    /// Healthy only when every check passes.
+    #[default]
+    All,
+    /// Healthy as long as at least one check passes.
+    Any,
+}
+
+impl HealthPolicy {
+    fn is_all(&self) -> bool {
+        *self == Self::All
+    }
+}
+
+/// One capture-time log filter. Lines matching `pattern` are dropped
+/// before they reach the log file — entirely, or all but one in `sample`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogFilter {
+    /// `*`-wildcard pattern matched against the whole captured line.
+    pub pattern: String,
+    /// Keep one of every `sample` matching lines instead of none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample: Option<u32>,
+}
+
+/// On-disk format of an app's captured log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// `[timestamp][stream] message` lines.
+    #[default]
+    Text,
+    /// One `{"ts", "stream", "msg" | "payload"}` JSON object per line.
+    Json,
+}
+
+impl LogFormat {
+    fn is_text(&self) -> bool {
+        *self == Self::Text
+    }
+}
+
+/// How an app's command is executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecKind {
+    /// Spawn the command as a raw child process.
+    #[default]
+    Process,
+    /// Run the command as a container image through a runtime CLI; status,
+    /// logging and restarts work the same as for processes.
+    Container,
+}
+
+impl ExecKind {
+    fn is_process(&self) -> bool {
+        *self == ExecKind::Process
+    }
+}
+
+/// PM2-style deploy workflow, declared per app under `"deploy"`.
+///
+/// Commands are explicit argv arrays (`[["bun", "install"]]`); nothing is
+/// passed through a shell.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeployConfig {
+    /// Git repository to clone/pull.
+    pub repo: String,
+    #[serde(default = "default_branch")]
+    pub branch: String,
+    /// Working copy the repo is checked out into.
+    pub path: PathBuf,
+    /// Commands run in `path` after the checkout, before the reload.
+    #[serde(default)]
+    pub pre_deploy: Vec<Vec<String>>,
+    /// Commands run in `path` after the reload.
+    #[serde(default)]
+    pub post_deploy: Vec<Vec<String>>,
+    /// Deploy into versioned `<path>/releases/<timestamp>` directories and
+    /// flip a `<path>/current` symlink, enabling `bunctl rollback`.
+    #[serde(default)]
+    pub releases: bool,
+    /// How many old releases to keep around (releases mode only).
+    #[serde(default = "default_keep_releases")]
+    pub keep_releases: u32,
+}
+
+/// Read one config file and resolve its `extends` and `include` references
+/// recursively. `visited` is the chain of files currently being loaded, for
+/// cycle detection.
+fn load_document(path: &Path, visited: &mut Vec<PathBuf>) -> Result<serde_json::Value, Error> {
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if visited.contains(&canonical) {
+        return Err(Error::InvalidConfig(format!(
+            "config reference cycle at {}",
+            path.display()
+        )));
+    }
+    visited.push(canonical);
+    let data = std::fs::read_to_string(path)?;
+    let mut doc: serde_json::Value = serde_json::from_str(&data)?;
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+    // extends: load the base first, then lay this file's keys over it.
+    let extends = doc.as_object_mut().and_then(|o| o.remove("extends"));
+    if let Some(extends) = extends {
+        let Some(rel) = extends.as_str() else {
+            return Err(Error::InvalidConfig("extends must be a path string".into()));
+        };
+        let base = load_document(&dir.join(rel), visited)?;
+        doc = merge_documents(base, doc);
+    }
+
+    let include = doc.as_object_mut().and_then(|o| o.remove("include"));
+    if let Some(include) = include {
+        let patterns: Vec<String> = match include {
+            serde_json::Value::String(s) => vec![s],
+            serde_json::Value::Array(items) => items
+                .into_iter()
+                .filter_map(|v| v.as_str().map(str::to_owned))
+                .collect(),
+            _ => {
+                return Err(Error::InvalidConfig(
+                    "include must be a pattern or a list of patterns".into(),
+                ))
+            }
+        };
+        for pattern in patterns {
+            for file in glob_paths(&dir, &pattern)? {
+                let sub = load_document(&file, visited)?;
+                append_apps(&mut doc, sub);
+            }
+        }
+    }
+
+    visited.pop();
+    Ok(doc)
+}
+
+/// Lay `child` over `base`: `apps` arrays concatenate (base first), object
+/// values like `defaults` merge key-by-key with the child winning, anything
+/// else the child replaces.
+fn merge_documents(base: serde_json::Value, child: serde_json::Value) -> serde_json::Value {
+    let serde_json::Value::Object(mut base) = base else { return child };
+    let serde_json::Value::Object(child) = child else { return child };
+    for (key, value) in child {
+        match (base.get_mut(&key), value) {
+            (Some(serde_json::Value::Array(have)), serde_json::Value::Array(mut more))
+                if key == "apps" =>
+            {
+                have.append(&mut more);
+            }
+            (Some(serde_json::Value::Object(have)), serde_json::Value::Object(over)) => {
+                for (k, v) in over {
+                    have.insert(k, v);
+                }
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+    serde_json::Value::Object(base)
+}
+
+/// Append an included file's apps to `doc`. The included document's own
+/// `defaults` and `instances` are resolved first, so per-service files are
+/// self-contained; the root's defaults still fill in what remains unset.
+fn append_apps(doc: &mut serde_json::Value, mut included: serde_json::Value) {
+    expand(&mut included);
+    let Some(serde_json::Value::Array(apps)) = included.get_mut("apps") else { return };
+    let apps = std::mem::take(apps);
+    let Some(obj) = doc.as_object_mut() else { return };
+    match obj.get_mut("apps") {
+        Some(serde_json::Value::Array(have)) => have.extend(apps),
+        _ => {
+            obj.insert("apps".into(), serde_json::Value::Array(apps));
+        }
+    }
+}
+
+/// Expand an include pattern relative to `dir`. Only the file name part may
+/// contain `*`; a pattern without one names a single file.
+fn glob_paths(dir: &Path, pattern: &str) -> Result<Vec<PathBuf>, Error> {
+    let full = dir.join(pattern);
+    if !pattern.contains('*') {
+        return Ok(vec![full]);
+    }
+    let file_pattern = full
+        .file_name()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| Error::InvalidConfig(format!("invalid include pattern: {pattern}")))?
+        .to_owned();
+    let parent = full.parent().unwrap_or(dir);
+    if parent.to_string_lossy().contains('*') {
+        return Err(Error::InvalidConfig(format!(
+            "only the file name of an include pattern may contain '*': {pattern}"
+        )));
+    }
+    let mut matches = Vec::new();
+    for entry in std::fs::read_dir(parent)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else { continue };
+        if wildcard_match(&file_pattern, name) {
+            matches.push(entry.path());
+        }
+    }
+    // read_dir order is platform-dependent; keep app order stable.
+    matches.sort();
+    Ok(matches)
+}
+
+/// Match a name against a pattern where `*` spans any run of
+/// characters. Used for include patterns and event subscription globs.
+pub fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let segments: Vec<&str> = pattern.split('*').collect();
+    let mut rest = name;
+    let last = segments.len() - 1;
+    for (idx, seg) in segments.iter().enumerate() {
+        if seg.is_empty() {
+            continue;
+        }
+        if idx == 0 {
+            match rest.strip_prefix(seg) {
+                Some(r) => rest = r,
+                None => return false,
+            }
+        } else if idx == last {
+            return rest.ends_with(seg);
+        } else {
+            match rest.find(seg) {
+                Some(pos) => rest = &rest[pos + seg.len()..],
+                None => return false,
+            }
+        }
+    }
+    true
+}
+
+/// Resolve the `defaults` section and `instances` templates in a parsed
+/// config document, in place.
+fn expand(root: &mut serde_json::Value) {
+    let Some(obj) = root.as_object_mut() else { return };
+    let defaults = match obj.remove("defaults") {
+        Some(serde_json::Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    let Some(serde_json::Value::Array(apps)) = obj.get_mut("apps") else { return };
+    let mut expanded = Vec::with_capacity(apps.len());
+    for mut app in std::mem::take(apps) {
+        let Some(entry) = app.as_object_mut() else {
+            expanded.push(app);
+            continue;
+        };
+        for (key, value) in &defaults {
+            match (entry.get_mut(key), value) {
+                (None, value) => {
+                    entry.insert(key.clone(), value.clone());
+                }
+                // Object-valued fields (env) merge one level deep, the
+                // app's own entries winning.
+                (Some(serde_json::Value::Object(have)), serde_json::Value::Object(shared)) => {
+                    for (k, v) in shared {
+                        have.entry(k.clone()).or_insert_with(|| v.clone());
+                    }
+                }
+                // A scalar the app already sets overrides the default.
+                _ => {}
+            }
+        }
+        let instances = entry.remove("instances").and_then(|v| v.as_u64()).unwrap_or(1);
+        if instances <= 1 {
+            expanded.push(app);
+            continue;
+        }
+        for i in 1..=instances {
+            let mut copy = entry.clone();
+            if let Some(serde_json::Value::String(name)) = copy.get_mut("name") {
+                if !name.contains("{i}") {
+                    name.push_str("-{i}");
+                }
+            }
+            // Record cluster membership so status can group the members
+            // back together after they become independent apps.
+            copy.insert("instance".into(), i.into());
+            copy.insert("instances_total".into(), instances.into());
+            let mut copy = serde_json::Value::Object(copy);
+            substitute(&mut copy, i);
+            expanded.push(copy);
+        }
+    }
+    *apps = expanded;
+}
+
+/// Replace `{i}` with the instance number in every string of a templated
+/// app entry.
+fn substitute(value: &mut serde_json::Value, i: u64) {
+    match value {
+        serde_json::Value::String(s) if s.contains("{i}") => {
+            *s = s.replace("{i}", &i.to_string());
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(|v| substitute(v, i)),
+        serde_json::Value::Object(map) => map.values_mut().for_each(|v| substitute(v, i)),
+        _ => {}
+    }
+}
+
+fn default_branch() -> String {
+    "main".to_owned()
+}
+
+fn default_keep_releases() -> u32 {
+    5
+}
+
+fn default_keep_core_dumps() -> u32 {
+    3
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_health_interval() -> u64 {
+    10
+}
+
+fn default_health_timeout() -> u64 {
+    5
+}
+
+fn default_health_failures() -> u32 {
+    1
+}
+
+fn default_window_secs() -> u64 {
+    3600
+}
+
+fn default_idle_timeout() -> Duration {
+    Duration::from_secs(300)
+}
+
+fn default_stop_timeout() -> Duration {
+    Duration::from_secs(10)
+}
+
+fn default_kill_timeout() -> Duration {
+    Duration::from_secs(5)
+}
+
+/// Serialize `Duration` fields as integer seconds, matching the JSON config
+/// format (`"stop_timeout": 10`).
+mod duration_secs {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(d: &Duration, ser: S) -> Result<S::Ok, S::Error> {
+        ser.serialize_u64(d.as_secs())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<Duration, D::Error> {
+        Ok(Duration::from_secs(u64::deserialize(de)?))
+    }
+}
+
+/// [`duration_secs`] for optional fields.
+mod duration_secs_opt {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(d: &Option<Duration>, ser: S) -> Result<S::Ok, S::Error> {
+        match d {
+            Some(d) => ser.serialize_some(&d.as_secs()),
+            None => ser.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<u64>::deserialize(de)?.map(Duration::from_secs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_fill_in_and_env_merges() {
+        let config = BunctlConfig::parse(
+            r#"{
+                "defaults": {
+                    "max_restarts": 3,
+                    "env": {"NODE_ENV": "production", "PORT": "3000"}
+                },
+                "apps": [
+                    {"name": "api", "command": "bun", "env": {"PORT": "8080"}},
+                    {"name": "worker", "command": "bun", "max_restarts": 10}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let api = config.app("api").unwrap();
+        assert_eq!(api.max_restarts, Some(3));
+        assert_eq!(api.env["PORT"], "8080");
+        assert_eq!(api.env["NODE_ENV"], "production");
+        assert_eq!(config.app("worker").unwrap().max_restarts, Some(10));
+    }
+
+    #[test]
+    fn backoff_strategy_parses_and_defaults_to_exponential() {
+        let config = BunctlConfig::parse(
+            r#"{
+                "apps": [
+                    {"name": "api", "command": "bun"},
+                    {
+                        "name": "worker",
+                        "command": "bun",
+                        "backoff": {"strategy": "schedule", "delays": [1, 5, 30, 300]}
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.app("api").unwrap().backoff, BackoffKind::Exponential);
+        assert_eq!(
+            config.app("worker").unwrap().backoff,
+            BackoffKind::Schedule { delays: vec![1, 5, 30, 300] }
+        );
+    }
+
+    #[test]
+    fn extends_and_include_compose_files() {
+        let dir = std::env::temp_dir().join(format!("bunctl-config-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("apps")).unwrap();
+        std::fs::write(
+            dir.join("base.json"),
+            r#"{"defaults": {"max_restarts": 3, "env": {"NODE_ENV": "production"}}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("apps/api.json"),
+            r#"{"apps": [{"name": "api", "command": "bun"}]}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("apps/worker.json"),
+            r#"{"apps": [{"name": "worker", "command": "bun", "max_restarts": 10}]}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("bunctl.json"),
+            r#"{"extends": "./base.json", "include": ["apps/*.json"]}"#,
+        )
+        .unwrap();
+
+        let config = BunctlConfig::load(&dir.join("bunctl.json")).unwrap();
+        assert_eq!(config.apps.len(), 2);
+        // Root defaults (via extends) reach included apps too.
+        assert_eq!(config.app("api").unwrap().max_restarts, Some(3));
+        assert_eq!(config.app("api").unwrap().env["NODE_ENV"], "production");
+        assert_eq!(config.app("worker").unwrap().max_restarts, Some(10));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn detects_reference_cycles() {
+        let dir = std::env::temp_dir().join(format!("bunctl-config-cycle-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.json"), r#"{"extends": "./b.json"}"#).unwrap();
+        std::fs::write(dir.join("b.json"), r#"{"extends": "./a.json"}"#).unwrap();
+        let err = BunctlConfig::load(&dir.join("a.json")).unwrap_err();
+        assert!(matches!(err, Error::InvalidConfig(msg) if msg.contains("cycle")));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn relative_paths_resolve_against_the_config_file() {
+        let dir = std::env::temp_dir().join(format!("bunctl-config-paths-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("bunctl.json"),
+            r#"{
+                "apps": [{
+                    "name": "api",
+                    "command": "bun",
+                    "cwd": "./api",
+                    "env_secrets": {"DB_PASSWORD": {"from": "file", "path": "secrets/db"}}
+                }]
+            }"#,
+        )
+        .unwrap();
+        let config = BunctlConfig::load(&dir.join("bunctl.json")).unwrap();
+        let app = config.app("api").unwrap();
+        let base = dir.canonicalize().unwrap();
+        assert_eq!(app.cwd.as_deref(), Some(base.join("api").as_path()));
+        let SecretSource::File { path } = &app.env_secrets["DB_PASSWORD"] else {
+            panic!("expected a file secret");
+        };
+        assert_eq!(path, &base.join("secrets/db"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn instances_expand_with_placeholder() {
+        let config = BunctlConfig::parse(
+            r#"{
+                "apps": [{
+                    "name": "worker",
+                    "command": "bun",
+                    "args": ["worker.ts", "--shard", "{i}"],
+                    "instances": 4
+                }]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.apps.len(), 4);
+        assert_eq!(config.apps[0].name, "worker-1");
+        assert_eq!(config.apps[3].name, "worker-4");
+        assert_eq!(config.apps[2].args, ["worker.ts", "--shard", "3"]);
+        assert_eq!(config.apps[2].instance, Some(3));
+        assert_eq!(config.apps[2].instances_total, Some(4));
+    }
+}
@@ -0,0 +1,157 @@
+//! A small cron-expression matcher so scheduled restarts don't need a
+//! date-time dependency.
+
+use crate::Error;
+
+/// A parsed five-field cron expression (`minute hour day-of-month month
+/// day-of-week`), evaluated in UTC.
+///
+/// Fields take `*`, single values, ranges (`1-5`), lists (`1,15,30`) and
+/// steps (`*/10`, `8-18/2`); day-of-week runs 0–7 with both 0 and 7
+/// meaning Sunday. As in classic cron, when the two day fields are both
+/// restricted the expression fires when either matches.
+#[derive(Debug, Clone)]
+pub struct CronExpr {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether each day field was `*`, for the either-day rule above.
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl CronExpr {
+    pub fn parse(expr: &str) -> Result<Self, Error> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(Error::InvalidConfig(format!(
+                "cron expression needs 5 fields (minute hour day month weekday): '{expr}'"
+            )));
+        };
+        let mut weekdays = field(weekday, 0, 7, expr)?;
+        // Both 0 and 7 are Sunday.
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays & !(1 << 7)) | 1;
+        }
+        Ok(Self {
+            minutes: field(minute, 0, 59, expr)?,
+            hours: field(hour, 0, 23, expr)?,
+            days: field(day, 1, 31, expr)?,
+            months: field(month, 1, 12, expr)?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+
+    /// Whether the minute containing `epoch_secs` matches, in UTC.
+    pub fn matches(&self, epoch_secs: u64) -> bool {
+        let days = epoch_secs / 86_400;
+        let secs_of_day = epoch_secs % 86_400;
+        let (_, month, day) = crate::time::civil_from_days(days as i64);
+        let weekday = (days + 4) % 7; // day zero of the epoch was a Thursday
+        if self.minutes & (1 << ((secs_of_day % 3600) / 60)) == 0
+            || self.hours & (1 << (secs_of_day / 3600)) == 0
+            || self.months & (1 << month) == 0
+        {
+            return false;
+        }
+        let day_ok = self.days & (1 << day) != 0;
+        let weekday_ok = self.weekdays & (1 << weekday) != 0;
+        if !self.any_day && !self.any_weekday {
+            day_ok || weekday_ok
+        } else {
+            day_ok && weekday_ok
+        }
+    }
+}
+
+/// Parse one field into a bitmask of allowed values.
+fn field(spec: &str, min: u64, max: u64, expr: &str) -> Result<u64, Error> {
+    let bad = || Error::InvalidConfig(format!("bad cron field '{spec}' in '{expr}'"));
+    let mut mask = 0u64;
+    for part in spec.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u64>().map_err(|_| bad())?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(bad());
+        }
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((lo, hi)) = range.split_once('-') {
+            (lo.parse().map_err(|_| bad())?, hi.parse().map_err(|_| bad())?)
+        } else {
+            let value: u64 = range.parse().map_err(|_| bad())?;
+            // A bare value with a step (`5/10`) runs from it to the top.
+            if part.contains('/') { (value, max) } else { (value, value) }
+        };
+        if lo < min || hi > max || lo > hi {
+            return Err(bad());
+        }
+        let mut value = lo;
+        while value <= hi {
+            mask |= 1 << value;
+            value += step;
+        }
+    }
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2025-08-27 (a Wednesday) at 04:00:00 UTC.
+    const WED_4AM: u64 = 1_756_267_200;
+
+    #[test]
+    fn matches_minute_and_hour() {
+        let cron = CronExpr::parse("0 4 * * *").unwrap();
+        assert!(cron.matches(WED_4AM));
+        assert!(cron.matches(WED_4AM + 59)); // same minute
+        assert!(!cron.matches(WED_4AM + 60));
+        assert!(!cron.matches(WED_4AM + 3600));
+    }
+
+    #[test]
+    fn steps_lists_and_ranges() {
+        let cron = CronExpr::parse("*/15 8-18 * * *").unwrap();
+        assert!(cron.matches(WED_4AM + 4 * 3600)); // 08:00
+        assert!(cron.matches(WED_4AM + 4 * 3600 + 45 * 60)); // 08:45
+        assert!(!cron.matches(WED_4AM + 4 * 3600 + 10 * 60)); // 08:10
+        assert!(!cron.matches(WED_4AM + 16 * 3600)); // 20:00, past the range
+        let cron = CronExpr::parse("0,30 4 * * *").unwrap();
+        assert!(cron.matches(WED_4AM + 30 * 60));
+        assert!(!cron.matches(WED_4AM + 15 * 60));
+    }
+
+    #[test]
+    fn weekdays_with_sunday_as_0_or_7() {
+        assert!(CronExpr::parse("0 4 * * 3").unwrap().matches(WED_4AM));
+        assert!(!CronExpr::parse("0 4 * * 0").unwrap().matches(WED_4AM));
+        let sunday = WED_4AM + 4 * 86_400;
+        assert!(CronExpr::parse("0 4 * * 0").unwrap().matches(sunday));
+        assert!(CronExpr::parse("0 4 * * 7").unwrap().matches(sunday));
+    }
+
+    #[test]
+    fn either_day_field_fires_when_both_are_restricted() {
+        // The 27th matches even though the weekday (Monday) does not.
+        assert!(CronExpr::parse("0 4 27 * 1").unwrap().matches(WED_4AM));
+        // With only the weekday restricted both fields must agree.
+        assert!(!CronExpr::parse("0 4 * * 1").unwrap().matches(WED_4AM));
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        assert!(CronExpr::parse("0 4 * *").is_err());
+        assert!(CronExpr::parse("60 * * * *").is_err());
+        assert!(CronExpr::parse("* * * * 8").is_err());
+        assert!(CronExpr::parse("*/0 * * * *").is_err());
+        assert!(CronExpr::parse("5-2 * * * *").is_err());
+    }
+}
@@ -0,0 +1,17 @@
+use thiserror::Error;
+
+/// Errors shared across bunctl crates.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("app not found: {0}")]
+    AppNotFound(String),
+
+    #[error("invalid config: {0}")]
+    InvalidConfig(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
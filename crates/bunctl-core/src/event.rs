@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+
+use crate::AppState;
+
+/// Which output stream a captured log line came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+/// How notable an event is, for subscription filtering. Ordered: a
+/// `min_severity` filter keeps everything at or above the named level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventSeverity {
+    /// Routine lifecycle traffic and log output.
+    Info,
+    /// Something an operator may want to look at, but the app still runs.
+    Warning,
+    /// The app is failing or supervision gave up on it.
+    Error,
+}
+
+/// A typed event emitted by the daemon.
+///
+/// This is the one event vocabulary shared by the daemon, the CLI and
+/// `bunctl-client`; consumers match on the enum instead of re-parsing
+/// untyped JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum DaemonEvent {
+    /// A process was spawned.
+    ProcessStarted { pid: u32 },
+    /// A process exited; `code` is `None` when it was killed by a signal.
+    /// `reason` classifies the exit further (code vs signal vs OOM kill).
+    ProcessExited {
+        code: Option<i32>,
+        #[serde(default = "unknown_exit")]
+        reason: crate::ExitReason,
+        /// Collected core dump of the crash, when the app has `core_dumps`
+        /// enabled and the kernel left one to pick up.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        core: Option<std::path::PathBuf>,
+    },
+    /// The app moved to a new lifecycle state.
+    StatusChange { state: AppState },
+    /// The app did not reach a stable run within its `start_timeout`; the
+    /// attempt was abandoned.
+    StartTimeout { elapsed_secs: u64 },
+    /// The app used up its `max_restarts_per_window` budget and was paused;
+    /// supervision resumes in `resume_secs` unless an operator acts first.
+    RestartBudgetExhausted { resume_secs: u64 },
+    /// The app was restarted gracefully by a recycle policy — not a crash.
+    Recycled {
+        /// Which policy fired: `max_uptime`, `memory_growth` or
+        /// `max_memory`.
+        trigger: String,
+        /// How long the recycled generation had been up.
+        uptime_secs: u64,
+    },
+    /// One captured line of app output.
+    LogLine { stream: LogStream, line: String },
+    /// The app's combined liveness verdict flipped; `failed` labels the
+    /// components that did not pass (empty when it became healthy).
+    HealthChanged {
+        healthy: bool,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        failed: Vec<String>,
+    },
+    /// The app's combined readiness verdict flipped; unlike a liveness
+    /// failure this never restarts the process.
+    ReadinessChanged {
+        ready: bool,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        failed: Vec<String>,
+    },
+    /// Usage is above a configured soft resource limit; emitted once per
+    /// sample for as long as it stays over.
+    ResourceLimitExceeded {
+        /// Which limit, e.g. `cpu`.
+        resource: String,
+        current: f64,
+        limit: f64,
+        /// How long usage has been over the limit.
+        over_secs: u64,
+    },
+    /// A connection was turned away because the concurrent-connection
+    /// limit was reached.
+    ConnectionLimitReached { active: u32 },
+    /// The daemon is shutting down.
+    DaemonShutdown,
+}
+
+impl DaemonEvent {
+    /// Stable snake_case name of the variant, for filtering and display.
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            DaemonEvent::ProcessStarted { .. } => "process_started",
+            DaemonEvent::ProcessExited { .. } => "process_exited",
+            DaemonEvent::StatusChange { .. } => "status_change",
+            DaemonEvent::StartTimeout { .. } => "start_timeout",
+            DaemonEvent::RestartBudgetExhausted { .. } => "restart_budget_exhausted",
+            DaemonEvent::Recycled { .. } => "recycled",
+            DaemonEvent::LogLine { .. } => "log_line",
+            DaemonEvent::HealthChanged { .. } => "health_changed",
+            DaemonEvent::ReadinessChanged { .. } => "readiness_changed",
+            DaemonEvent::ResourceLimitExceeded { .. } => "resource_limit_exceeded",
+            DaemonEvent::ConnectionLimitReached { .. } => "connection_limit_reached",
+            DaemonEvent::DaemonShutdown => "daemon_shutdown",
+        }
+    }
+
+    /// Severity of this event, judged by what it says rather than the
+    /// variant alone: a clean exit is routine, a crash is not.
+    pub fn severity(&self) -> EventSeverity {
+        match self {
+            DaemonEvent::ProcessStarted { .. }
+            | DaemonEvent::StatusChange { .. }
+            | DaemonEvent::Recycled { .. }
+            | DaemonEvent::LogLine { .. } => EventSeverity::Info,
+            DaemonEvent::ProcessExited { code, .. } => match code {
+                Some(0) => EventSeverity::Info,
+                _ => EventSeverity::Error,
+            },
+            DaemonEvent::StartTimeout { .. } | DaemonEvent::RestartBudgetExhausted { .. } => {
+                EventSeverity::Error
+            }
+            DaemonEvent::HealthChanged { healthy, .. } => {
+                if *healthy {
+                    EventSeverity::Info
+                } else {
+                    EventSeverity::Error
+                }
+            }
+            DaemonEvent::ReadinessChanged { ready, .. } => {
+                if *ready {
+                    EventSeverity::Info
+                } else {
+                    EventSeverity::Warning
+                }
+            }
+            DaemonEvent::ResourceLimitExceeded { .. }
+            | DaemonEvent::ConnectionLimitReached { .. }
+            | DaemonEvent::DaemonShutdown => EventSeverity::Warning,
+        }
+    }
+}
+
+/// One persisted daemon event, as stored in the on-disk event log and
+/// returned by the `Events` query: the envelope metadata plus the event
+/// itself, flattened the same way the wire format flattens it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventRecord {
+    /// Seconds since the Unix epoch when the event was emitted.
+    pub ts: u64,
+    /// The daemon-lifetime sequence number assigned at emission.
+    #[serde(default)]
+    pub seq: u64,
+    /// App the event concerns, when app-scoped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app: Option<String>,
+    #[serde(flatten)]
+    pub event: DaemonEvent,
+}
+
+/// Serde default for events recorded before exit classification existed.
+fn unknown_exit() -> crate::ExitReason {
+    crate::ExitReason::Unknown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_with_event_tag() {
+        let event = DaemonEvent::ProcessExited {
+            code: Some(1),
+            reason: crate::ExitReason::Code { code: 1 },
+            core: None,
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["event"], "process_exited");
+        assert_eq!(json["code"], 1);
+        assert_eq!(json["reason"]["kind"], "code");
+        let back: DaemonEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(back, event);
+    }
+}
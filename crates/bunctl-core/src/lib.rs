@@ -0,0 +1,24 @@
+//! Core types shared by the bunctl daemon, CLI and client libraries.
+//!
+//! This crate deliberately contains no I/O: it is the vocabulary the other
+//! crates speak — app identifiers, lifecycle states, configuration and
+//! status snapshots.
+
+pub mod app;
+pub mod audit;
+pub mod backoff;
+pub mod config;
+pub mod cron;
+pub mod error;
+pub mod event;
+pub mod metrics;
+pub mod snapshot;
+pub mod time;
+pub mod units;
+
+pub use app::{
+    AppId, AppState, AppStatus, ExitReason, HealthRecord, InstanceStatus, ProcessNode, SpawnInfo,
+};
+pub use config::{AppConfig, BunctlConfig};
+pub use error::Error;
+pub use event::{DaemonEvent, EventRecord, EventSeverity, LogStream};
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+/// One persisted resource sample for an app, as stored by `bunctl-metrics`
+/// and returned from the `Metrics` IPC query.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MetricSample {
+    /// Unix timestamp of the sample.
+    pub ts: u64,
+    pub cpu_percent: f32,
+    pub memory_bytes: u64,
+    /// Cumulative restart counter at sample time.
+    pub restarts: u64,
+}
+
+/// Counters of an app's capture-time log filters: lines dropped outright
+/// and lines sampled away (kept one-in-N).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogMetrics {
+    pub dropped: u64,
+    pub sampled: u64,
+}
+
+impl LogMetrics {
+    pub fn is_empty(&self) -> bool {
+        self.dropped == 0 && self.sampled == 0
+    }
+}
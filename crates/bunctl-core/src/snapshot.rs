@@ -0,0 +1,30 @@
+//! Portable snapshot of the daemon's app registry.
+//!
+//! `bunctl export` serializes one of these; `bunctl import` replays it into
+//! another daemon, e.g. when migrating a host or reproducing a production
+//! setup locally.
+
+use serde::{Deserialize, Serialize};
+
+use crate::AppConfig;
+
+/// One registered app as captured at export time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppSnapshot {
+    pub config: AppConfig,
+    /// Whether the app was (meant to be) running; import starts these.
+    pub running: bool,
+    /// Restart counter at export time, carried over for continuity.
+    #[serde(default)]
+    pub restarts: u64,
+}
+
+/// Everything needed to recreate a daemon's registry elsewhere.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonSnapshot {
+    /// bunctl version that produced the snapshot.
+    pub version: String,
+    /// Unix timestamp of the export.
+    pub exported_at: u64,
+    pub apps: Vec<AppSnapshot>,
+}
@@ -0,0 +1,2378 @@
+//! Daemon state and app lifecycle management.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use bunctl_core::backoff::BackoffStrategy;
+use bunctl_core::config::{
+    CpuLimitMode, ExecKind, HealthAction, HealthCheck, HealthPolicy, HealthRole,
+};
+use bunctl_core::metrics::MetricSample;
+use bunctl_core::snapshot::{AppSnapshot, DaemonSnapshot};
+use bunctl_core::{AppConfig, AppId, AppState, AppStatus, DaemonEvent, ExitReason, LogStream};
+use bunctl_ipc::message::{ClientInfo, ErrorCode};
+use bunctl_logging::{AuditLog, EventLog, LogManager, LogWriter};
+use bunctl_metrics::MetricsStore;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::{broadcast, Mutex};
+
+use crate::pidfile::{PidRecord, PidRegistry};
+
+/// What a Bun app writes to its `BUNCTL_STATS_FILE`: heap usage and event
+/// loop lag, sampled in-process where OS numbers cannot see.
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+struct BunStats {
+    heap_used: Option<u64>,
+    event_loop_lag_ms: Option<f64>,
+}
+
+/// An event together with the app it concerns, as broadcast to subscribers.
+#[derive(Debug, Clone)]
+pub struct EventEnvelope {
+    pub app: Option<String>,
+    pub event: DaemonEvent,
+    /// When the event was emitted, seconds since the Unix epoch.
+    pub ts: u64,
+    /// Position in the daemon's single emission order, monotonically
+    /// increasing for the daemon's lifetime; lets clients order events
+    /// emitted within the same second and spot gaps after reconnects.
+    pub seq: u64,
+}
+
+/// How long an app must stay up for its restart backoff to reset.
+const STABLE_UPTIME_SECS: u64 = 10;
+
+/// Seconds between resource samples.
+const SAMPLE_INTERVAL_SECS: u64 = 3;
+
+/// How many samples the per-app ring buffer keeps (~3 minutes of history).
+const SAMPLE_HISTORY: usize = 60;
+
+/// How long a blue/green candidate must stay running to count as healthy.
+const CANDIDATE_CONFIRM_SECS: u64 = 2;
+
+/// Default budget for a blue/green candidate to come up (`start_timeout`
+/// overrides it).
+const CANDIDATE_WAIT_SECS: u64 = 30;
+
+/// How long the displaced instance lingers as `<name>-previous` before it
+/// is stopped and deleted.
+const PREVIOUS_LINGER_SECS: u64 = 60;
+
+/// A stats file older than this is ignored: the app stopped writing it.
+const BUN_STATS_MAX_AGE_SECS: u64 = 3 * SAMPLE_INTERVAL_SECS;
+
+/// Global budget for stopping every app at daemon shutdown.
+const SHUTDOWN_DEADLINE_SECS: u64 = 30;
+
+/// Gap between scheduled (`restart_cron`) restarts firing the same minute.
+const SCHEDULE_STAGGER_SECS: u64 = 5;
+
+/// How many health check results the per-app history keeps.
+const HEALTH_HISTORY: usize = 30;
+
+/// How many lines of each start attempt the startup banner keeps.
+const BANNER_LINES: usize = 50;
+
+/// Event-loop lag above this is logged as a warning: something is blocking
+/// the daemon's async runtime.
+const SELF_LAG_WARN_MS: u64 = 250;
+
+/// Daemon RSS above this is logged as a warning.
+const SELF_RSS_WARN_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Daemon open-descriptor count above this is logged as a warning.
+const SELF_FD_WARN: u32 = 512;
+
+struct ManagedApp {
+    config: AppConfig,
+    state: AppState,
+    pid: Option<u32>,
+    started_at: Option<Instant>,
+    restarts: u64,
+    stop_requested: bool,
+    /// Whether the open-descriptor warning already fired (reset on recovery).
+    fd_alerted: bool,
+    /// When CPU usage rose above a soft `max_cpu_percent` (reset on
+    /// recovery).
+    cpu_over_since: Option<Instant>,
+    /// Latest Bun runtime stats, when the app opts in via `bun_stats`.
+    bun_stats: Option<BunStats>,
+    /// CPU ticks and timestamp of the previous sample, for percent deltas.
+    prev_cpu: Option<(u64, Instant)>,
+    /// Ring buffer of recent (cpu_percent, memory_bytes) samples.
+    samples: std::collections::VecDeque<(f32, u64)>,
+    /// Capture-filter counters shared with the app's log writer, when
+    /// `log_filters` are configured.
+    log_metrics: Option<Arc<std::sync::Mutex<bunctl_core::metrics::LogMetrics>>>,
+    /// Health-check bookkeeping, when `health_checks` are configured.
+    health: HealthState,
+    /// First lines of output of the most recent start attempt (bounded by
+    /// [`BANNER_LINES`]), shared with the capture tasks and shown by
+    /// `bunctl describe`. Reset at every spawn, so after a crash it holds
+    /// the startup error of the attempt that failed.
+    banner: Arc<std::sync::Mutex<Vec<String>>>,
+    /// Why the process last exited, surfaced as `last_exit_reason`.
+    last_exit_reason: Option<ExitReason>,
+    /// The cgroup's cumulative `oom_kill` counter at the last exit, so a
+    /// new OOM kill shows up as an increase.
+    oom_kills: u64,
+    /// What the current process generation was started with, captured
+    /// (already redacted) at each spawn; shown by `bunctl describe`.
+    spawn_info: Option<bunctl_core::SpawnInfo>,
+    /// Memory of the first sample after the run counted as stable, the
+    /// reference for `recycle_on_memory_growth_percent`; reset each spawn.
+    memory_baseline: Option<u64>,
+    /// A recycle restart is in flight; keeps the sampler from triggering
+    /// another while the graceful stop runs. Cleared at the next spawn.
+    recycle_pending: bool,
+    /// On-demand phase shown in status (holding vs proxying), kept up to
+    /// date by the app's holder task.
+    on_demand: Option<String>,
+}
+
+/// Per-app health-check state: recent results and scheduling bookkeeping.
+#[derive(Debug, Default)]
+struct HealthState {
+    /// Most recent results, oldest first (bounded by [`HEALTH_HISTORY`]).
+    results: std::collections::VecDeque<bunctl_core::HealthRecord>,
+    /// When the last attempt started.
+    last: Option<Instant>,
+    /// An attempt is currently in flight; skip scheduling another.
+    inflight: bool,
+    /// Last-round verdicts and the consecutive-failure counter (see
+    /// [`crate::health::VerdictState`]).
+    verdicts: crate::health::VerdictState,
+}
+
+/// Shared daemon state: the app registry, log manager and event bus.
+pub struct Daemon {
+    apps: Mutex<HashMap<AppId, ManagedApp>>,
+    logs: LogManager,
+    metrics: MetricsStore,
+    pids: PidRegistry,
+    audit: AuditLog,
+    /// Persisted copy of every non-log-line event, for `bunctl events`.
+    event_log: EventLog,
+    /// Uppercased deny-list: env keys containing one of these are shown
+    /// redacted in status output.
+    redact_env: Vec<String>,
+    events: broadcast::Sender<EventEnvelope>,
+    /// Connections that introduced themselves via `Hello`, by connection id.
+    clients: Mutex<HashMap<u64, (ClientInfo, Instant)>>,
+    /// Background worker for post-rotation manifest work.
+    rotation: bunctl_logging::rotation::RotationQueue,
+    started: Instant,
+    /// Most recent event-loop lag measured by [`Daemon::run_self_monitor`],
+    /// in milliseconds; surfaced in `daemon status`.
+    self_lag_ms: std::sync::atomic::AtomicU64,
+    /// How many internal tasks [`Daemon::supervised`] restarted after a
+    /// panic; surfaced as `restarts` in `daemon status`.
+    task_restarts: std::sync::atomic::AtomicU64,
+    /// Reject app names that sanitization would change instead of silently
+    /// normalizing them (`--strict-names`).
+    strict_names: bool,
+    /// On-demand apps whose holder task is live, so the manager scan does
+    /// not spawn a second one.
+    held: Mutex<std::collections::HashSet<AppId>>,
+    /// Next event sequence number (see [`EventEnvelope::seq`]).
+    event_seq: std::sync::atomic::AtomicU64,
+}
+
+type CmdResult = Result<Option<String>, (ErrorCode, String)>;
+
+impl Daemon {
+    pub fn new(
+        logs: LogManager,
+        metrics: MetricsStore,
+        pids: PidRegistry,
+        redact_env: Vec<String>,
+        strict_names: bool,
+    ) -> Arc<Self> {
+        let (events, _) = broadcast::channel(1024);
+        let audit = logs.audit_log();
+        let event_log = logs.event_log();
+        Arc::new(Self {
+            apps: Mutex::new(HashMap::new()),
+            logs,
+            metrics,
+            pids,
+            audit,
+            event_log,
+            redact_env: redact_env.into_iter().map(|p| p.to_uppercase()).collect(),
+            events,
+            clients: Mutex::new(HashMap::new()),
+            rotation: bunctl_logging::rotation::RotationQueue::spawn(),
+            started: Instant::now(),
+            self_lag_ms: std::sync::atomic::AtomicU64::new(0),
+            task_restarts: std::sync::atomic::AtomicU64::new(0),
+            strict_names,
+            held: Mutex::new(std::collections::HashSet::new()),
+            event_seq: std::sync::atomic::AtomicU64::new(0),
+        })
+    }
+
+    /// Track a connection that introduced itself (`Hello`).
+    pub async fn register_client(&self, conn_id: u64, info: ClientInfo) {
+        self.clients.lock().await.insert(conn_id, (info, Instant::now()));
+    }
+
+    /// Forget a connection when it closes.
+    pub async fn unregister_client(&self, conn_id: u64) {
+        self.clients.lock().await.remove(&conn_id);
+    }
+
+    /// Currently connected clients, longest-connected first.
+    pub async fn list_clients(&self) -> Vec<ClientInfo> {
+        let clients = self.clients.lock().await;
+        let mut list: Vec<ClientInfo> = clients
+            .values()
+            .map(|(info, since)| {
+                let mut info = info.clone();
+                info.connected_secs = since.elapsed().as_secs();
+                info
+            })
+            .collect();
+        list.sort_by_key(|info| std::cmp::Reverse(info.connected_secs));
+        list
+    }
+
+    pub fn log_manager(&self) -> &LogManager {
+        &self.logs
+    }
+
+    /// Where an opted-in app writes its Bun runtime stats. Lives next to the
+    /// log files; the `.stats.json` suffix keeps it out of `list_logs`.
+    fn bun_stats_path(&self, id: &AppId) -> std::path::PathBuf {
+        self.logs.base_dir().join(format!("{id}.stats.json"))
+    }
+
+    /// Read and parse an app's stats file, ignoring it once stale.
+    fn read_bun_stats(&self, id: &AppId) -> Option<BunStats> {
+        let path = self.bun_stats_path(id);
+        let meta = std::fs::metadata(&path).ok()?;
+        let age = meta.modified().ok()?.elapsed().ok()?;
+        if age.as_secs() > BUN_STATS_MAX_AGE_SECS {
+            return None;
+        }
+        serde_json::from_str(&std::fs::read_to_string(&path).ok()?).ok()
+    }
+
+    pub fn subscribe_events(&self) -> broadcast::Receiver<EventEnvelope> {
+        self.events.subscribe()
+    }
+
+    pub(crate) fn emit(&self, app: Option<&AppId>, event: DaemonEvent) {
+        let ts = bunctl_core::time::unix_now();
+        let seq = self.event_seq.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        // Log lines are not persisted twice; the app's log file has them.
+        if !matches!(event, DaemonEvent::LogLine { .. }) {
+            let record = bunctl_core::EventRecord {
+                ts,
+                seq,
+                app: app.map(ToString::to_string),
+                event: event.clone(),
+            };
+            if let Err(err) = self.event_log.record(&record) {
+                tracing::warn!("cannot write event record: {err}");
+            }
+        }
+        let _ = self.events.send(EventEnvelope {
+            app: app.map(ToString::to_string),
+            event,
+            ts,
+            seq,
+        });
+    }
+
+    /// The one place app state changes. Transitions the lifecycle does not
+    /// allow (see [`AppState::can_transition_to`]) are dropped with a
+    /// warning — they are races, e.g. a supervision task reporting `Running`
+    /// after an explicit stop already moved the app to `Stopped` — and every
+    /// accepted transition is emitted as a `StatusChange` event.
+    async fn set_state(&self, id: &AppId, state: AppState) {
+        let mut apps = self.apps.lock().await;
+        if let Some(app) = apps.get_mut(id) {
+            if app.state == state {
+                return;
+            }
+            if !app.state.can_transition_to(state) {
+                tracing::warn!(
+                    app = %id,
+                    from = %app.state,
+                    to = %state,
+                    "dropping illegal state transition"
+                );
+                return;
+            }
+            app.state = state;
+            drop(apps);
+            self.emit(Some(id), DaemonEvent::StatusChange { state });
+        }
+    }
+
+    /// Register (if new) and start an app. For an app that is already
+    /// managed the semantics are explicit: a stopped or errored one is
+    /// started with the given config, a running one is an error unless
+    /// `force` asked for a restart — and the response says which of the
+    /// three happened.
+    pub async fn start_app(self: &Arc<Self>, config: AppConfig, force: bool) -> CmdResult {
+        let id = self.derive_id(&config.name)?;
+        Self::ensure_cwd(&config)?;
+        Self::ensure_command(&config)?;
+        Self::ensure_cron(&config)?;
+        let managed = {
+            let apps = self.apps.lock().await;
+            apps.get(&id).map(|app| (app.state, app.config.name.clone()))
+        };
+        // The same id under a different raw name is two user apps colliding
+        // through sanitization, not a restart of this one.
+        if let Some((_, existing)) = &managed {
+            if *existing != config.name {
+                return Err(Self::collision_error(&id, existing, &config.name));
+            }
+        }
+        match managed.map(|(state, _)| state) {
+            Some(AppState::Starting | AppState::Running) if force => {
+                return self.restart_app(id.as_str(), Some(config)).await;
+            }
+            Some(AppState::Starting | AppState::Running) => {
+                return Err((
+                    ErrorCode::AlreadyExists,
+                    format!("app already running: {id} (use --force to restart)"),
+                ));
+            }
+            Some(_) => {
+                // Stopped, errored or paused: take the new config and
+                // start it.
+                {
+                    let mut apps = self.apps.lock().await;
+                    let Some(app) = apps.get_mut(&id) else {
+                        return Err((ErrorCode::NotFound, format!("app not found: {id}")));
+                    };
+                    app.config = config;
+                    app.stop_requested = false;
+                }
+                self.set_state(&id, AppState::Starting).await;
+                let daemon = self.clone();
+                let task_id = id.clone();
+                tokio::spawn(async move { daemon.run_app(task_id).await });
+                return Ok(Some(format!("started {id} (was stopped)")));
+            }
+            None => {}
+        }
+        {
+            let mut apps = self.apps.lock().await;
+            if let Some(existing) = apps.get(&id) {
+                // Lost a registration race since the state peek above.
+                if existing.config.name != config.name {
+                    return Err(Self::collision_error(&id, &existing.config.name, &config.name));
+                }
+                return Err((ErrorCode::AlreadyExists, format!("app already exists: {id}")));
+            }
+            apps.insert(
+                id.clone(),
+                ManagedApp {
+                    config,
+                    state: AppState::Starting,
+                    pid: None,
+                    started_at: None,
+                    restarts: 0,
+                    stop_requested: false,
+                    fd_alerted: false,
+                    cpu_over_since: None,
+                    bun_stats: None,
+                    prev_cpu: None,
+                    samples: std::collections::VecDeque::with_capacity(SAMPLE_HISTORY),
+                    log_metrics: None,
+                    health: HealthState::default(),
+                    banner: Arc::default(),
+                    last_exit_reason: None,
+                    oom_kills: 0,
+                    spawn_info: None,
+                    memory_baseline: None,
+                    recycle_pending: false,
+                    on_demand: None,
+                },
+            );
+        }
+        self.emit(Some(&id), DaemonEvent::StatusChange { state: AppState::Starting });
+        let daemon = self.clone();
+        let task_id = id.clone();
+        tokio::spawn(async move { daemon.run_app(task_id).await });
+        Ok(Some(format!("started {id}")))
+    }
+
+    /// Derive the registry id for a user-supplied name. Empty-after-
+    /// sanitization names are always rejected; under `--strict-names` a
+    /// name that sanitization would change at all is too, instead of being
+    /// silently normalized.
+    fn derive_id(&self, name: &str) -> Result<AppId, (ErrorCode, String)> {
+        let id = AppId::new(name);
+        if id.as_str().is_empty() {
+            return Err((ErrorCode::InvalidRequest, format!("invalid app name: {name}")));
+        }
+        if self.strict_names && id.as_str() != name {
+            return Err((
+                ErrorCode::InvalidRequest,
+                format!("app name '{name}' would be sanitized to '{id}' (strict names are on)"),
+            ));
+        }
+        Ok(id)
+    }
+
+    /// The collision half of sanitization: registering `incoming` over an
+    /// app configured as `existing` under the same id would silently merge
+    /// two different user apps, so the error spells both names out.
+    fn collision_error(id: &AppId, existing: &str, incoming: &str) -> (ErrorCode, String) {
+        (
+            ErrorCode::AlreadyExists,
+            format!(
+                "app name '{incoming}' collides with existing app '{existing}': \
+                 both sanitize to '{id}'"
+            ),
+        )
+    }
+
+    /// Fail fast on a missing working directory instead of surfacing the
+    /// opaque OS spawn error later; with `create_cwd` the directory (and
+    /// missing parents) is created instead.
+    fn ensure_cwd(config: &AppConfig) -> Result<(), (ErrorCode, String)> {
+        let Some(cwd) = &config.cwd else { return Ok(()) };
+        if cwd.is_dir() {
+            return Ok(());
+        }
+        if config.create_cwd {
+            std::fs::create_dir_all(cwd).map_err(|err| {
+                (
+                    ErrorCode::InvalidRequest,
+                    format!("cannot create cwd {}: {err}", cwd.display()),
+                )
+            })
+        } else {
+            Err((
+                ErrorCode::InvalidRequest,
+                format!("cwd does not exist: {} (set create_cwd to create it)", cwd.display()),
+            ))
+        }
+    }
+
+    /// Move a crash's core file into the log directory as
+    /// `<app>.core.<unix-timestamp>` and prune dumps past the app's
+    /// `keep_core_dumps`. `None` when there is nothing to collect (no dump
+    /// written, or a pipe handler owns it).
+    fn collect_core_dump(
+        &self,
+        id: &AppId,
+        config: &AppConfig,
+        pid: u32,
+    ) -> Option<std::path::PathBuf> {
+        let found = bunctl_supervisor::find_core_dump(config.cwd.as_deref(), pid)?;
+        let dest = self
+            .logs
+            .base_dir()
+            .join(format!("{id}.core.{}", bunctl_core::time::unix_now()));
+        // rename fails across filesystems; fall back to copy + remove.
+        if std::fs::rename(&found, &dest).is_err() {
+            if let Err(err) = std::fs::copy(&found, &dest).and_then(|_| std::fs::remove_file(&found))
+            {
+                tracing::warn!(app = %id, "cannot collect core dump {}: {err}", found.display());
+                return None;
+            }
+        }
+        tracing::info!(app = %id, "collected core dump {}", dest.display());
+        self.prune_core_dumps(id, config.keep_core_dumps);
+        Some(dest)
+    }
+
+    /// Delete the oldest `<app>.core.*` files beyond `keep`. Timestamps in
+    /// the names sort lexically, so name order is age order.
+    fn prune_core_dumps(&self, id: &AppId, keep: u32) {
+        let prefix = format!("{id}.core.");
+        let Ok(entries) = std::fs::read_dir(self.logs.base_dir()) else { return };
+        let mut dumps: Vec<std::path::PathBuf> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(&prefix))
+            })
+            .collect();
+        dumps.sort();
+        while dumps.len() > keep as usize {
+            let oldest = dumps.remove(0);
+            if let Err(err) = std::fs::remove_file(&oldest) {
+                tracing::warn!(app = %id, "cannot prune core dump {}: {err}", oldest.display());
+            }
+        }
+    }
+
+    /// Fail fast when the program (or interpreter) cannot be found or lacks
+    /// execute permission, with a did-you-mean hint for typos, instead of
+    /// letting spawn fail after backoff churn. Container images and
+    /// pinned-bun commands are skipped: neither is a local file yet.
+    fn ensure_command(config: &AppConfig) -> Result<(), (ErrorCode, String)> {
+        if config.exec_kind == ExecKind::Container {
+            return Ok(());
+        }
+        let program = config.interpreter.as_deref().unwrap_or(&config.command);
+        let pinned_bun = config.bun_version.is_some()
+            && std::path::Path::new(program).file_stem().is_some_and(|stem| stem == "bun");
+        if pinned_bun {
+            return Ok(());
+        }
+        match bunctl_supervisor::check_command(program, config.cwd.as_deref()) {
+            Ok(_) => Ok(()),
+            Err(bunctl_supervisor::CommandProblem::NotFound { suggestion }) => {
+                let hint = suggestion
+                    .map(|name| format!(" — did you mean {name}?"))
+                    .unwrap_or_default();
+                Err((ErrorCode::InvalidRequest, format!("command not found: {program}{hint}")))
+            }
+            Err(bunctl_supervisor::CommandProblem::NotExecutable { path }) => Err((
+                ErrorCode::InvalidRequest,
+                format!("command is not executable: {} (missing execute permission)", path.display()),
+            )),
+        }
+    }
+
+    /// Reject an unparsable `restart_cron` at registration, where the user
+    /// sees the error, instead of from the scheduler at 4am.
+    fn ensure_cron(config: &AppConfig) -> Result<(), (ErrorCode, String)> {
+        match &config.restart_cron {
+            Some(expr) => bunctl_core::cron::CronExpr::parse(expr)
+                .map(|_| ())
+                .map_err(|err| (ErrorCode::InvalidRequest, err.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Fire configured `restart_cron` schedules; spawned once at daemon
+    /// startup. Checks once per minute (cron's resolution, in UTC like the
+    /// expressions) and gracefully restarts every running app whose
+    /// schedule matches, staggered [`SCHEDULE_STAGGER_SECS`] apart so apps
+    /// sharing a schedule do not all go down at once.
+    pub async fn run_scheduler(self: Arc<Self>) {
+        let mut last_minute = bunctl_core::time::unix_now() / 60;
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            let minute = bunctl_core::time::unix_now() / 60;
+            if minute == last_minute {
+                continue;
+            }
+            last_minute = minute;
+            let due: Vec<AppId> = {
+                let apps = self.apps.lock().await;
+                apps.iter()
+                    .filter(|(_, app)| {
+                        app.state == AppState::Running
+                            && app.config.restart_cron.as_deref().is_some_and(|expr| {
+                                bunctl_core::cron::CronExpr::parse(expr)
+                                    .is_ok_and(|cron| cron.matches(minute * 60))
+                            })
+                    })
+                    .map(|(id, _)| id.clone())
+                    .collect()
+            };
+            for (i, id) in due.into_iter().enumerate() {
+                let daemon = self.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(std::time::Duration::from_secs(
+                        i as u64 * SCHEDULE_STAGGER_SECS,
+                    ))
+                    .await;
+                    tracing::info!(app = %id, "scheduled restart (restart_cron)");
+                    if let Err((_, message)) = daemon.restart_app(id.as_str(), None).await {
+                        tracing::warn!(app = %id, "scheduled restart failed: {message}");
+                    }
+                });
+            }
+        }
+    }
+
+    /// On-demand apps without a live holder task; marks them held so the
+    /// scan cannot hand one out twice. Called from [`crate::ondemand::run`].
+    pub(crate) async fn unheld_on_demand(&self) -> Vec<(AppId, bunctl_core::config::OnDemand)> {
+        let apps = self.apps.lock().await;
+        let mut held = self.held.lock().await;
+        let mut due = Vec::new();
+        for (id, app) in apps.iter() {
+            if let Some(config) = &app.config.on_demand {
+                if !held.contains(id) {
+                    held.insert(id.clone());
+                    due.push((id.clone(), config.clone()));
+                }
+            }
+        }
+        due
+    }
+
+    /// The app's current on-demand settings; `None` once the app was
+    /// deleted or the mode turned off, telling its holder to wind down.
+    pub(crate) async fn on_demand_config(&self, id: &AppId) -> Option<bunctl_core::config::OnDemand> {
+        self.apps.lock().await.get(id).and_then(|app| app.config.on_demand.clone())
+    }
+
+    /// Update the phase a holder shows as `on_demand` in status output.
+    pub(crate) async fn set_on_demand_phase(&self, id: &AppId, phase: Option<String>) {
+        if let Some(app) = self.apps.lock().await.get_mut(id) {
+            app.on_demand = phase;
+        }
+    }
+
+    /// The holder task for `id` ended; let a later scan spawn a fresh one.
+    pub(crate) async fn end_hold(&self, id: &AppId) {
+        self.set_on_demand_phase(id, None).await;
+        self.held.lock().await.remove(id);
+    }
+
+    pub(crate) async fn app_state(&self, id: &AppId) -> Option<AppState> {
+        self.apps.lock().await.get(id).map(|app| app.state)
+    }
+
+    /// Start an already-registered app with its stored config — the
+    /// on-demand wake-up path; `start_app` is the user-facing entry.
+    /// Returns whether a process is now starting or already up.
+    pub(crate) async fn start_held(self: &Arc<Self>, id: &AppId) -> bool {
+        {
+            let mut apps = self.apps.lock().await;
+            let Some(app) = apps.get_mut(id) else { return false };
+            match app.state {
+                AppState::Starting | AppState::Running => return true,
+                // Still winding down; the client will have to retry.
+                AppState::Stopping => return false,
+                _ => {}
+            }
+            app.stop_requested = false;
+        }
+        self.set_state(id, AppState::Starting).await;
+        let daemon = self.clone();
+        let task_id = id.clone();
+        tokio::spawn(async move { daemon.run_app(task_id).await });
+        true
+    }
+
+    /// Supervision loop for one app: spawn, capture output, wait, restart.
+    async fn run_app(self: Arc<Self>, id: AppId) {
+        // The backoff curve is fixed at supervision start; a restart picks
+        // up a changed one.
+        let mut backoff = {
+            let apps = self.apps.lock().await;
+            match apps.get(&id) {
+                Some(app) => BackoffStrategy::with_kind(app.config.backoff.clone()),
+                None => return,
+            }
+        };
+        let first_attempt = Instant::now();
+        let mut ever_stable = false;
+        // When each recent restart happened, for the rolling budget window.
+        let mut restart_times: std::collections::VecDeque<Instant> = Default::default();
+        loop {
+            let config = {
+                let apps = self.apps.lock().await;
+                match apps.get(&id) {
+                    Some(app) if !app.stop_requested => app.config.clone(),
+                    _ => return,
+                }
+            };
+            // What the child actually gets: the registry config plus runtime
+            // additions and resolved secrets. Only the clean `config` is
+            // persisted or echoed back to clients.
+            let mut spawn_config = config.clone();
+            if spawn_config.bun_stats {
+                spawn_config.env.insert(
+                    "BUNCTL_STATS_FILE".to_owned(),
+                    self.bun_stats_path(&id).display().to_string(),
+                );
+            }
+            // GPU pinning for plain processes is env-level (CUDA honors
+            // it); containers get device isolation via `--gpus` instead.
+            if !spawn_config.gpus.is_empty() && matches!(spawn_config.exec_kind, ExecKind::Process)
+            {
+                let list = spawn_config
+                    .gpus
+                    .iter()
+                    .map(u32::to_string)
+                    .collect::<Vec<_>>()
+                    .join(",");
+                for key in ["CUDA_VISIBLE_DEVICES", "NVIDIA_VISIBLE_DEVICES"] {
+                    spawn_config.env.entry(key.to_owned()).or_insert_with(|| list.clone());
+                }
+            }
+            if let Some(version) = &spawn_config.bun_version {
+                if let Err(err) = bunctl_supervisor::bun::ensure(version).await {
+                    tracing::error!(app = %id, "{err}");
+                    self.set_state(&id, AppState::Errored).await;
+                    return;
+                }
+            }
+            // Snapshot what this generation is started with while the env
+            // is still secret-free; resolved secret values must never land
+            // in the snapshot.
+            let argv = bunctl_supervisor::argv(&spawn_config);
+            let spawn_env = self.spawn_env_snapshot(&spawn_config);
+            if let Err(err) = bunctl_supervisor::secrets::resolve(&mut spawn_config).await {
+                tracing::error!(app = %id, "{err}");
+                self.set_state(&id, AppState::Errored).await;
+                return;
+            }
+
+            let interpreter_version = bunctl_supervisor::program_version(&argv[0]).await;
+            let mut child = match bunctl_supervisor::spawn(&spawn_config) {
+                Ok(child) => child,
+                Err(err) => {
+                    tracing::error!(app = %id, "spawn failed: {err}");
+                    self.set_state(&id, AppState::Errored).await;
+                    return;
+                }
+            };
+            let pid = child.id().unwrap_or(0);
+            let started = Instant::now();
+            let banner = {
+                let mut apps = self.apps.lock().await;
+                let Some(app) = apps.get_mut(&id) else { return };
+                app.pid = Some(pid);
+                app.started_at = Some(started);
+                app.memory_baseline = None;
+                app.recycle_pending = false;
+                app.spawn_info = Some(bunctl_core::SpawnInfo {
+                    spawned_at: bunctl_core::time::unix_now(),
+                    generation: app.restarts + 1,
+                    argv,
+                    env: spawn_env,
+                    interpreter_version,
+                });
+                // Fresh banner per attempt: after a crash it holds exactly
+                // the output of the attempt that failed.
+                app.banner = Arc::default();
+                app.banner.clone()
+            };
+            self.set_state(&id, AppState::Running).await;
+            self.emit(Some(&id), DaemonEvent::ProcessStarted { pid });
+            // Containers get their quota via `--cpus` at spawn time.
+            if let (Some(percent), CpuLimitMode::Hard, ExecKind::Process) =
+                (config.max_cpu_percent, config.cpu_limit_mode, config.exec_kind)
+            {
+                bunctl_supervisor::apply_cpu_limit(pid, id.as_str(), percent);
+            }
+            // Containers schedule inside their own cpuset; pinning applies
+            // to plain processes only.
+            if !config.cpu_affinity.is_empty() && matches!(config.exec_kind, ExecKind::Process) {
+                bunctl_supervisor::apply_cpu_affinity(pid, id.as_str(), &config.cpu_affinity);
+            }
+            if let Some(job) = &config.job_object {
+                if matches!(config.exec_kind, ExecKind::Process) {
+                    bunctl_supervisor::apply_job_object(pid, id.as_str(), job);
+                }
+            }
+            // Per-generation loopback forwarders; torn down when this
+            // process exits and respawned with the next PID.
+            let forwards = if config.private_network && !config.forward_ports.is_empty() {
+                crate::netns::spawn_forwards(&id, pid, &config.forward_ports)
+            } else {
+                Vec::new()
+            };
+            self.pids.write(
+                &id,
+                &PidRecord {
+                    pid,
+                    started_unix: bunctl_core::time::unix_now(),
+                    command: bunctl_supervisor::get_process_info(pid)
+                        .map(|i| i.command)
+                        .filter(|c| !c.is_empty())
+                        .unwrap_or_else(|| config.command.clone()),
+                    config: config.clone(),
+                },
+            );
+
+            let log_metrics = self.capture_output(&id, &mut child, &config, &banner);
+            if log_metrics.is_some() {
+                if let Some(app) = self.apps.lock().await.get_mut(&id) {
+                    app.log_metrics = log_metrics;
+                }
+            }
+            let status = child.wait().await;
+            for task in forwards {
+                task.abort();
+            }
+            let code = status.as_ref().ok().and_then(|s| s.code());
+            let mut reason = bunctl_supervisor::exit_reason(status.ok());
+            self.pids.remove(&id);
+            if config.bun_stats {
+                let _ = std::fs::remove_file(self.bun_stats_path(&id));
+            }
+            // An OOM kill arrives as a plain SIGKILL; the cgroup's counter
+            // tells it apart from someone running `kill -9`.
+            let oom = bunctl_supervisor::oom_kill_count(id.as_str());
+            {
+                let mut apps = self.apps.lock().await;
+                if let Some(app) = apps.get_mut(&id) {
+                    if oom > app.oom_kills {
+                        reason = ExitReason::OomKilled;
+                    }
+                    app.oom_kills = oom;
+                    app.last_exit_reason = Some(reason.clone());
+                }
+            }
+            let core = if config.core_dumps && !matches!(reason, ExitReason::Code { .. }) {
+                self.collect_core_dump(&id, &config, pid)
+            } else {
+                None
+            };
+            self.emit(Some(&id), DaemonEvent::ProcessExited { code, reason, core });
+
+            let (stop_requested, autorestart, max_restarts, restarts) = {
+                let mut apps = self.apps.lock().await;
+                let Some(app) = apps.get_mut(&id) else { return };
+                app.pid = None;
+                app.started_at = None;
+                app.bun_stats = None;
+                (app.stop_requested, app.config.autorestart, app.config.max_restarts, app.restarts)
+            };
+
+            if stop_requested {
+                self.set_state(&id, AppState::Stopped).await;
+                return;
+            }
+            if !autorestart {
+                let state = if code == Some(0) { AppState::Stopped } else { AppState::Errored };
+                self.set_state(&id, state).await;
+                return;
+            }
+            if started.elapsed().as_secs() >= STABLE_UPTIME_SECS {
+                backoff.reset();
+                ever_stable = true;
+            }
+            // A start_timeout bounds the journey to the *first* stable run:
+            // spawn retries count against it, and once a stable run has been
+            // achieved the timeout no longer applies.
+            if let Some(start_timeout) = config.start_timeout {
+                if !ever_stable && first_attempt.elapsed() >= start_timeout {
+                    let elapsed_secs = first_attempt.elapsed().as_secs();
+                    tracing::error!(
+                        app = %id,
+                        "no stable run within start_timeout ({start_timeout:?}); giving up"
+                    );
+                    self.emit(Some(&id), DaemonEvent::StartTimeout { elapsed_secs });
+                    self.set_state(&id, AppState::Errored).await;
+                    return;
+                }
+            }
+            if let Some(max) = max_restarts {
+                if backoff.attempt() >= max {
+                    tracing::error!(app = %id, restarts, "giving up after {max} failed restarts");
+                    self.set_state(&id, AppState::Errored).await;
+                    return;
+                }
+            }
+            // The rolling budget outlives backoff resets: an app crashing
+            // once an hour never trips `max_restarts` but does spend its
+            // budget. Exhausting it pauses supervision until the oldest
+            // restart ages out of the window.
+            if let Some(budget) = config.max_restarts_per_window {
+                let window = std::time::Duration::from_secs(config.window_secs);
+                while restart_times.front().is_some_and(|t| t.elapsed() >= window) {
+                    restart_times.pop_front();
+                }
+                if restart_times.len() >= budget as usize {
+                    let resume_in = restart_times
+                        .front()
+                        .map_or(window, |t| window.saturating_sub(t.elapsed()));
+                    tracing::error!(
+                        app = %id,
+                        "restart budget exhausted ({budget} in {}s); pausing for {resume_in:?}",
+                        config.window_secs
+                    );
+                    self.emit(
+                        Some(&id),
+                        DaemonEvent::RestartBudgetExhausted { resume_secs: resume_in.as_secs() },
+                    );
+                    self.set_state(&id, AppState::Paused).await;
+                    self.schedule_resume(id, resume_in);
+                    return;
+                }
+                restart_times.push_back(Instant::now());
+            }
+            {
+                let mut apps = self.apps.lock().await;
+                if let Some(app) = apps.get_mut(&id) {
+                    app.restarts += 1;
+                }
+            }
+            let delay = backoff.next_delay();
+            tracing::info!(app = %id, "restarting in {delay:?}");
+            self.set_state(&id, AppState::Starting).await;
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Pick supervision back up after a restart-budget pause, once the
+    /// window has cleared. A no-op if an operator acted in the meantime:
+    /// both a stop and a fresh start leave `Paused` before the timer fires.
+    fn schedule_resume(self: Arc<Self>, id: AppId, after: std::time::Duration) {
+        tokio::spawn(async move {
+            tokio::time::sleep(after).await;
+            let paused = {
+                let apps = self.apps.lock().await;
+                apps.get(&id)
+                    .is_some_and(|app| app.state == AppState::Paused && !app.stop_requested)
+            };
+            if !paused {
+                return;
+            }
+            tracing::info!(app = %id, "restart budget window cleared; resuming");
+            self.set_state(&id, AppState::Starting).await;
+            self.run_app(id).await;
+        });
+    }
+
+    /// Blue/green swap: start `<name>-candidate` with the given config,
+    /// wait for it to come up and stay up, then promote it to `<name>`.
+    /// The displaced instance is stopped and kept as `<name>-previous` for
+    /// a short rollback window. The app itself must make overlapping
+    /// instances possible (e.g. SO_REUSEPORT or a front proxy). The
+    /// promoted process keeps logging to the candidate log file until its
+    /// next restart.
+    pub async fn blue_green(self: &Arc<Self>, config: AppConfig) -> CmdResult {
+        let id = AppId::new(&config.name);
+        if !self.is_managed(&id).await {
+            return Err((ErrorCode::NotFound, format!("app not found: {}", config.name)));
+        }
+        let candidate_id = AppId::new(&format!("{id}-candidate"));
+        if self.is_managed(&candidate_id).await {
+            return Err((
+                ErrorCode::AlreadyExists,
+                format!("a swap of {id} is already in progress"),
+            ));
+        }
+        let mut candidate_config = config.clone();
+        candidate_config.name = candidate_id.to_string();
+        let wait = config
+            .start_timeout
+            .unwrap_or(std::time::Duration::from_secs(CANDIDATE_WAIT_SECS));
+        self.start_app(candidate_config, false).await?;
+
+        // Health check: the candidate must reach Running and hold it.
+        let deadline = Instant::now() + wait;
+        let healthy = loop {
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+            match self.app_status(candidate_id.as_str()).await {
+                Ok(status) if status.state == AppState::Running => {
+                    if status.uptime_secs.unwrap_or(0) >= CANDIDATE_CONFIRM_SECS {
+                        break true;
+                    }
+                }
+                Ok(status) if status.state == AppState::Errored => break false,
+                Ok(_) => {}
+                Err(_) => break false,
+            }
+            if Instant::now() >= deadline {
+                break false;
+            }
+        };
+        if !healthy {
+            let _ = self.stop_app(candidate_id.as_str()).await;
+            self.apps.lock().await.remove(&candidate_id);
+            self.pids.remove(&candidate_id);
+            return Err((
+                ErrorCode::SpawnFailed,
+                format!("candidate for {id} did not become healthy; old instance untouched"),
+            ));
+        }
+
+        // Stop the old instance so its supervision task winds down before
+        // the registry keys move.
+        self.stop_app(id.as_str()).await?;
+        let previous_id = AppId::new(&format!("{id}-previous"));
+        let candidate_pid = {
+            let mut apps = self.apps.lock().await;
+            if let Some(mut old) = apps.remove(&id) {
+                old.config.name = previous_id.to_string();
+                apps.insert(previous_id.clone(), old);
+            }
+            let Some(mut candidate) = apps.remove(&candidate_id) else {
+                return Err((ErrorCode::Internal, format!("candidate for {id} disappeared")));
+            };
+            candidate.config.name = id.to_string();
+            let pid = candidate.pid;
+            apps.insert(id.clone(), candidate);
+            pid
+        };
+        self.pids.remove(&candidate_id);
+        self.emit(Some(&id), DaemonEvent::StatusChange { state: AppState::Running });
+
+        // The candidate's original supervision task still watches the old
+        // key; re-attach supervision under the promoted name.
+        if let Some(pid) = candidate_pid {
+            self.pids.write(
+                &id,
+                &PidRecord {
+                    pid,
+                    started_unix: bunctl_core::time::unix_now(),
+                    command: bunctl_supervisor::get_process_info(pid)
+                        .map(|i| i.command)
+                        .unwrap_or_else(|| config.command.clone()),
+                    config,
+                },
+            );
+            let daemon = self.clone();
+            let task_id = id.clone();
+            tokio::spawn(async move { daemon.run_adopted(task_id, pid).await });
+        }
+
+        // Retire the previous instance after the rollback window.
+        let daemon = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_secs(PREVIOUS_LINGER_SECS)).await;
+            if daemon.is_managed(&previous_id).await {
+                if let Err((_, msg)) = daemon.delete_app(previous_id.as_str(), None).await {
+                    tracing::warn!(app = %previous_id, "cannot retire previous instance: {msg}");
+                }
+            }
+        });
+        Ok(Some(format!(
+            "swapped in candidate for {id}; previous instance retires in {PREVIOUS_LINGER_SECS}s"
+        )))
+    }
+
+    /// Bring an externally started process under management. The config is
+    /// derived from the process's observed command line, so restart-on-exit
+    /// re-runs what the user originally started.
+    pub async fn adopt_pid(self: &Arc<Self>, name: &str, pid: u32) -> CmdResult {
+        let id = self.derive_id(name)?;
+        if id.as_str() == "daemon" {
+            return Err((ErrorCode::InvalidRequest, format!("invalid app name: {name}")));
+        }
+        if let Some(existing) = self.config_name_of(&id).await {
+            if existing != name {
+                return Err(Self::collision_error(&id, &existing, name));
+            }
+            return Err((ErrorCode::AlreadyExists, format!("app already managed: {id}")));
+        }
+        if !bunctl_supervisor::is_alive(pid) {
+            return Err((ErrorCode::NotFound, format!("no such process: {pid}")));
+        }
+        let Some(info) = bunctl_supervisor::get_process_info(pid) else {
+            return Err((ErrorCode::Internal, format!("cannot inspect process {pid}")));
+        };
+        let mut parts = info.command.split(' ');
+        let Some(command) = parts.next().filter(|c| !c.is_empty()) else {
+            return Err((
+                ErrorCode::Internal,
+                format!("cannot determine command line of process {pid}"),
+            ));
+        };
+        let config = AppConfig {
+            name: id.to_string(),
+            command: command.to_owned(),
+            args: parts.map(ToOwned::to_owned).collect(),
+            ..AppConfig::default()
+        };
+        {
+            let mut apps = self.apps.lock().await;
+            apps.insert(
+                id.clone(),
+                ManagedApp {
+                    config: config.clone(),
+                    state: AppState::Running,
+                    pid: Some(pid),
+                    started_at: None,
+                    restarts: 0,
+                    stop_requested: false,
+                    fd_alerted: false,
+                    cpu_over_since: None,
+                    bun_stats: None,
+                    prev_cpu: None,
+                    samples: std::collections::VecDeque::with_capacity(SAMPLE_HISTORY),
+                    log_metrics: None,
+                    health: HealthState::default(),
+                    banner: Arc::default(),
+                    last_exit_reason: None,
+                    oom_kills: 0,
+                    spawn_info: None,
+                    memory_baseline: None,
+                    recycle_pending: false,
+                    on_demand: None,
+                },
+            );
+        }
+        self.pids.write(
+            &id,
+            &PidRecord {
+                pid,
+                started_unix: bunctl_core::time::unix_now(),
+                command: info.command,
+                config,
+            },
+        );
+        self.emit(Some(&id), DaemonEvent::StatusChange { state: AppState::Running });
+        let daemon = self.clone();
+        let task_id = id.clone();
+        tokio::spawn(async move { daemon.run_adopted(task_id, pid).await });
+        Ok(Some(format!("adopted process {pid} as {id}")))
+    }
+
+    /// Re-attach to processes a previous daemon incarnation left running,
+    /// and clean up stale pid records. Called once at daemon boot.
+    pub async fn adopt_orphans(self: &Arc<Self>) {
+        for (id, record) in self.pids.load_all() {
+            let alive = bunctl_supervisor::is_alive(record.pid);
+            let command = bunctl_supervisor::get_process_info(record.pid)
+                .map(|i| i.command)
+                .unwrap_or_default();
+            // Verify the PID was not recycled for an unrelated process.
+            if !alive || (!command.is_empty() && command != record.command) {
+                tracing::info!(app = %id, pid = record.pid, "removing stale pid record");
+                self.pids.remove(&id);
+                continue;
+            }
+            tracing::info!(app = %id, pid = record.pid, "adopting running process");
+            {
+                let mut apps = self.apps.lock().await;
+                apps.insert(
+                    id.clone(),
+                    ManagedApp {
+                        config: record.config,
+                        state: AppState::Running,
+                        pid: Some(record.pid),
+                        started_at: None,
+                        restarts: 0,
+                        stop_requested: false,
+                        fd_alerted: false,
+                        cpu_over_since: None,
+                        bun_stats: None,
+                        prev_cpu: None,
+                        samples: std::collections::VecDeque::with_capacity(SAMPLE_HISTORY),
+                        log_metrics: None,
+                        health: HealthState::default(),
+                        banner: Arc::default(),
+                        last_exit_reason: None,
+                        oom_kills: 0,
+                        spawn_info: None,
+                        memory_baseline: None,
+                        recycle_pending: false,
+                        on_demand: None,
+                    },
+                );
+            }
+            self.emit(Some(&id), DaemonEvent::StatusChange { state: AppState::Running });
+            let daemon = self.clone();
+            let task_id = id.clone();
+            tokio::spawn(async move { daemon.run_adopted(task_id, record.pid).await });
+        }
+    }
+
+    /// Supervise an adopted process. It is not our child, so there is no
+    /// exit status to wait on: poll liveness, then hand over to the normal
+    /// supervision loop for restarts.
+    async fn run_adopted(self: Arc<Self>, id: AppId, pid: u32) {
+        while bunctl_supervisor::is_alive(pid) {
+            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        }
+        self.pids.remove(&id);
+        // Not our child: there is no wait status to classify.
+        self.emit(
+            Some(&id),
+            DaemonEvent::ProcessExited { code: None, reason: ExitReason::Unknown, core: None },
+        );
+        let (stop_requested, autorestart) = {
+            let mut apps = self.apps.lock().await;
+            let Some(app) = apps.get_mut(&id) else { return };
+            app.pid = None;
+            app.last_exit_reason = Some(ExitReason::Unknown);
+            (app.stop_requested, app.config.autorestart)
+        };
+        if stop_requested || !autorestart {
+            self.set_state(&id, AppState::Stopped).await;
+            return;
+        }
+        {
+            let mut apps = self.apps.lock().await;
+            if let Some(app) = apps.get_mut(&id) {
+                app.restarts += 1;
+            }
+        }
+        self.set_state(&id, AppState::Starting).await;
+        self.run_app(id).await;
+    }
+
+    /// Periodically run the configured health checks of running apps,
+    /// keeping the last [`HEALTH_HISTORY`] combined results per app;
+    /// spawned once at daemon startup. Attempts run detached so a slow
+    /// probe never delays the others, with at most one round in flight per
+    /// app. An app's components run concurrently and combine into one
+    /// verdict per its `health_policy`; a flip of the verdict emits a
+    /// `HealthChanged` event naming the failed components.
+    pub async fn run_health(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let due: Vec<(AppId, Vec<HealthCheck>, HealthPolicy, HealthAction, u32)> = {
+                let mut apps = self.apps.lock().await;
+                apps.iter_mut()
+                    .filter_map(|(id, app)| {
+                        if app.config.health_checks.is_empty()
+                            || app.state != AppState::Running
+                            || app.health.inflight
+                        {
+                            return None;
+                        }
+                        // With several checks a round runs at the shortest
+                        // configured interval.
+                        let interval = app
+                            .config
+                            .health_checks
+                            .iter()
+                            .map(|check| check.interval)
+                            .min()
+                            .unwrap_or_default();
+                        if app.health.last.is_some_and(|t| t.elapsed().as_secs() < interval) {
+                            return None;
+                        }
+                        app.health.inflight = true;
+                        app.health.last = Some(Instant::now());
+                        Some((
+                            id.clone(),
+                            app.config.health_checks.clone(),
+                            app.config.health_policy,
+                            app.config.health_on_failure.clone(),
+                            app.config.health_failures,
+                        ))
+                    })
+                    .collect()
+            };
+            for (id, checks, policy, on_failure, threshold) in due {
+                let daemon = self.clone();
+                tokio::spawn(async move {
+                    let started = Instant::now();
+                    let results: Vec<(HealthRole, Option<String>)> =
+                        futures::future::join_all(checks.iter().map(|check| async {
+                            let outcome = crate::health::probe(
+                                &check.check,
+                                std::time::Duration::from_secs(check.timeout),
+                            )
+                            .await;
+                            (
+                                check.role,
+                                outcome
+                                    .err()
+                                    .map(|err| format!("{}: {err}", check.check.label())),
+                            )
+                        }))
+                        .await;
+                    let live = crate::health::verdict(&results, policy, HealthRole::Liveness);
+                    let ready = crate::health::verdict(&results, policy, HealthRole::Readiness);
+                    let ok = live.as_ref().is_none_or(|(ok, _)| *ok)
+                        && ready.as_ref().is_none_or(|(ok, _)| *ok);
+                    let all_failed: Vec<String> =
+                        results.into_iter().filter_map(|(_, err)| err).collect();
+                    let record = bunctl_core::HealthRecord {
+                        ts: bunctl_core::time::unix_now(),
+                        ok,
+                        latency_ms: started.elapsed().as_millis() as u64,
+                        error: if ok { None } else { Some(all_failed.join("; ")) },
+                    };
+                    let outcome = {
+                        let mut apps = daemon.apps.lock().await;
+                        let Some(app) = apps.get_mut(&id) else { return };
+                        app.health.inflight = false;
+                        let outcome =
+                            crate::health::apply_round(&mut app.health.verdicts, live, ready, threshold);
+                        if app.health.results.len() >= HEALTH_HISTORY {
+                            app.health.results.pop_front();
+                        }
+                        app.health.results.push_back(record);
+                        outcome
+                    };
+                    if let Some((flipped, ok, failed)) = outcome.ready {
+                        if flipped {
+                            daemon.emit(
+                                Some(&id),
+                                DaemonEvent::ReadinessChanged { ready: ok, failed },
+                            );
+                        }
+                    }
+                    if let Some((flipped, ok, failed)) = outcome.live {
+                        if flipped {
+                            daemon
+                                .emit(Some(&id), DaemonEvent::HealthChanged { healthy: ok, failed });
+                        }
+                        if !ok && outcome.act {
+                            daemon.on_health_failure(&id, &on_failure).await;
+                        }
+                    }
+                });
+            }
+        }
+    }
+
+    /// Apply the app's configured `health_on_failure` remediation after
+    /// `health_failures` consecutive failing liveness rounds.
+    async fn on_health_failure(self: &Arc<Self>, id: &AppId, action: &HealthAction) {
+        match action {
+            HealthAction::Restart => {
+                tracing::warn!(app = %id, "liveness check failed, restarting");
+                if let Err((_, message)) = self.restart_app(id.as_str(), None).await {
+                    tracing::warn!(app = %id, error = %message, "liveness restart failed");
+                }
+            }
+            HealthAction::Stop => {
+                tracing::warn!(app = %id, "liveness check failed, stopping");
+                if let Err((_, message)) = self.stop_app(id.as_str()).await {
+                    tracing::warn!(app = %id, error = %message, "liveness stop failed");
+                }
+            }
+            HealthAction::Exec { argv } => {
+                let Some((cmd, args)) = argv.split_first() else {
+                    tracing::warn!(app = %id, "empty health_on_failure argv");
+                    return;
+                };
+                tracing::warn!(app = %id, command = %cmd, "liveness check failed, running hook");
+                let outcome = tokio::process::Command::new(cmd)
+                    .args(args)
+                    .env("BUNCTL_APP", id.as_str())
+                    .stdout(std::process::Stdio::null())
+                    .stderr(std::process::Stdio::null())
+                    .status()
+                    .await;
+                match outcome {
+                    Ok(status) if status.success() => {}
+                    Ok(status) => {
+                        tracing::warn!(app = %id, %status, "health_on_failure hook failed")
+                    }
+                    Err(err) => {
+                        tracing::warn!(app = %id, error = %err, "health_on_failure hook failed")
+                    }
+                }
+            }
+            HealthAction::None => {}
+        }
+    }
+
+    /// Recorded health check results for an app, oldest first.
+    pub async fn health_history(
+        &self,
+        name: &str,
+    ) -> Result<Vec<bunctl_core::HealthRecord>, (ErrorCode, String)> {
+        let id = AppId::new(name);
+        let apps = self.apps.lock().await;
+        let Some(app) = apps.get(&id) else {
+            return Err((ErrorCode::NotFound, format!("app not found: {name}")));
+        };
+        if app.config.health_checks.is_empty() {
+            return Err((
+                ErrorCode::InvalidRequest,
+                format!("no health_checks configured for {name}"),
+            ));
+        }
+        Ok(app.health.results.iter().cloned().collect())
+    }
+
+    /// The app's live process tree and our clock rate, for `bunctl top`.
+    /// The `/proc` scan happens outside the apps lock.
+    pub async fn process_tree(
+        &self,
+        name: &str,
+    ) -> Result<(Vec<bunctl_core::ProcessNode>, u64), (ErrorCode, String)> {
+        let id = AppId::new(name);
+        let pid = {
+            let apps = self.apps.lock().await;
+            let Some(app) = apps.get(&id) else {
+                return Err((ErrorCode::NotFound, format!("app not found: {name}")));
+            };
+            let Some(pid) = app.pid else {
+                return Err((ErrorCode::InvalidRequest, format!("app not running: {name}")));
+            };
+            pid
+        };
+        Ok((bunctl_supervisor::process_tree(pid), bunctl_supervisor::clock_ticks_per_sec()))
+    }
+
+    /// Periodically reap zombies reparented to us (we are a subreaper on
+    /// Linux), skipping PIDs owned by live tokio `Child` handles.
+    pub async fn run_reaper(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(5));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let known: Vec<u32> = {
+                let apps = self.apps.lock().await;
+                apps.values().filter_map(|app| app.pid).collect()
+            };
+            for pid in bunctl_supervisor::reap_zombies(&known) {
+                tracing::info!(pid, "reaped orphaned zombie process");
+            }
+        }
+    }
+
+    /// Pipe the child's stdout/stderr into the log file and the event bus.
+    fn capture_output(
+        self: &Arc<Self>,
+        id: &AppId,
+        child: &mut tokio::process::Child,
+        config: &AppConfig,
+        banner: &Arc<std::sync::Mutex<Vec<String>>>,
+    ) -> Option<Arc<std::sync::Mutex<bunctl_core::metrics::LogMetrics>>> {
+        let filter_metrics = (!config.log_filters.is_empty())
+            .then(Arc::<std::sync::Mutex<bunctl_core::metrics::LogMetrics>>::default);
+        let writer = match self.logs.writer(id) {
+            Ok(mut writer) => {
+                writer.set_dedup(config.log_dedup);
+                writer.set_multiline(
+                    config.log_multiline || config.log_continuation.is_some(),
+                    config.log_continuation.clone(),
+                );
+                writer.set_strip_ansi(config.strip_ansi);
+                writer.set_format(config.log_format);
+                writer.set_rotation(
+                    config.log_max_size.as_deref().and_then(bunctl_core::units::parse_memory),
+                    config.log_manifest,
+                );
+                if let Some(metrics) = &filter_metrics {
+                    writer.set_filters(config.log_filters.clone(), metrics.clone());
+                }
+                writer.set_rotation_queue(self.rotation.sender());
+                Arc::new(std::sync::Mutex::new(writer))
+            }
+            Err(err) => {
+                tracing::error!(app = %id, "cannot open log file: {err}");
+                return None;
+            }
+        };
+        if let Some(stdout) = child.stdout.take() {
+            self.spawn_capture(id.clone(), LogStream::Stdout, stdout, writer.clone(), banner.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            self.spawn_capture(id.clone(), LogStream::Stderr, stderr, writer, banner.clone());
+        }
+        filter_metrics
+    }
+
+    fn spawn_capture<R>(
+        self: &Arc<Self>,
+        id: AppId,
+        stream: LogStream,
+        reader: R,
+        writer: Arc<std::sync::Mutex<LogWriter>>,
+        banner: Arc<std::sync::Mutex<Vec<String>>>,
+    ) where
+        R: tokio::io::AsyncRead + Unpin + Send + 'static,
+    {
+        let daemon = self.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                {
+                    let mut banner = banner.lock().expect("banner poisoned");
+                    if banner.len() < BANNER_LINES {
+                        let label =
+                            if stream == LogStream::Stderr { "stderr" } else { "stdout" };
+                        banner.push(format!("[{label}] {line}"));
+                    }
+                }
+                if let Err(err) = writer.lock().expect("log writer poisoned").write_line(stream, &line) {
+                    tracing::warn!(app = %id, "log write failed: {err}");
+                }
+                daemon.emit(Some(&id), DaemonEvent::LogLine { stream, line });
+            }
+        });
+    }
+
+    /// Stop an app, escalating per its configured timeouts.
+    pub async fn stop_app(&self, name: &str) -> CmdResult {
+        let id = AppId::new(name);
+        let (pid, policy) = {
+            let mut apps = self.apps.lock().await;
+            let Some(app) = apps.get_mut(&id) else {
+                return Err((ErrorCode::NotFound, format!("app not found: {name}")));
+            };
+            if app.state == AppState::Stopped {
+                return Ok(Some(format!("{id} already stopped")));
+            }
+            app.stop_requested = true;
+            (
+                app.pid,
+                (
+                    bunctl_supervisor::StopPolicy::from_config(&app.config),
+                    app.config.exec_kind,
+                    app.config.runtime.clone(),
+                ),
+            )
+        };
+        let (policy, exec_kind, runtime) = policy;
+        if let Some(pid) = pid {
+            self.set_state(&id, AppState::Stopping).await;
+            let stopped = bunctl_supervisor::stop_with_policy(pid, &policy).await;
+            // Killing the runtime client can leave the container itself
+            // behind; force-remove it as a backstop.
+            if exec_kind == bunctl_core::config::ExecKind::Container {
+                let _ = tokio::process::Command::new(runtime.as_deref().unwrap_or("docker"))
+                    .args(["rm", "-f", &format!("bunctl-{id}")])
+                    .stdout(std::process::Stdio::null())
+                    .stderr(std::process::Stdio::null())
+                    .status()
+                    .await;
+            }
+            if !stopped {
+                return Err((
+                    ErrorCode::Internal,
+                    format!("process {pid} survived SIGKILL"),
+                ));
+            }
+        } else {
+            self.set_state(&id, AppState::Stopped).await;
+        }
+        Ok(Some(format!("stopped {id}")))
+    }
+
+    /// Restart an app (stop if running, then start its supervision loop).
+    /// A fresh config (`restart --update-env`) replaces the cached snapshot
+    /// before the new process spawns.
+    pub async fn restart_app(self: &Arc<Self>, name: &str, config: Option<AppConfig>) -> CmdResult {
+        let id = AppId::new(name);
+        {
+            let apps = self.apps.lock().await;
+            if !apps.contains_key(&id) {
+                return Err((ErrorCode::NotFound, format!("app not found: {name}")));
+            }
+        }
+        if let Some(config) = &config {
+            if AppId::new(&config.name) != id {
+                return Err((
+                    ErrorCode::InvalidRequest,
+                    format!("config is for '{}', not '{name}'", config.name),
+                ));
+            }
+            Self::ensure_cwd(config)?;
+            Self::ensure_command(config)?;
+            Self::ensure_cron(config)?;
+        }
+        self.stop_app(name).await?;
+        {
+            let mut apps = self.apps.lock().await;
+            let Some(app) = apps.get_mut(&id) else {
+                return Err((ErrorCode::NotFound, format!("app not found: {name}")));
+            };
+            if let Some(config) = config {
+                app.config = config;
+            }
+            app.stop_requested = false;
+            app.restarts += 1;
+        }
+        self.set_state(&id, AppState::Starting).await;
+        let daemon = self.clone();
+        let task_id = id.clone();
+        tokio::spawn(async move { daemon.run_app(task_id).await });
+        Ok(Some(format!("restarted {id}")))
+    }
+
+    /// Stop (if needed) and remove an app from the registry. `purge_logs`
+    /// overrides the app's configured default for whether its log files and
+    /// recorded metrics are removed too.
+    pub async fn delete_app(&self, name: &str, purge_logs: Option<bool>) -> CmdResult {
+        let id = AppId::new(name);
+        let default_purge = {
+            let apps = self.apps.lock().await;
+            match apps.get(&id) {
+                Some(app) => app.config.purge_logs,
+                None => return Err((ErrorCode::NotFound, format!("app not found: {name}"))),
+            }
+        };
+        self.stop_app(name).await?;
+        self.apps.lock().await.remove(&id);
+        if purge_logs.unwrap_or(default_purge) {
+            if let Err(err) = self.logs.purge(&id) {
+                tracing::warn!(app = %id, "cannot purge logs: {err}");
+            }
+            if let Err(err) = self.metrics.purge(&id) {
+                tracing::warn!(app = %id, "cannot purge metrics: {err}");
+            }
+            return Ok(Some(format!("deleted {id} (logs purged)")));
+        }
+        Ok(Some(format!("deleted {id}")))
+    }
+
+    /// Rename an app, moving its log files and recorded metrics with it.
+    /// The registry entry is moved, not rebuilt, so restart counters,
+    /// health history and the last exit reason survive. Only stopped apps
+    /// can be renamed: a running one has its name baked into the active
+    /// log writer, pid record and container name.
+    pub async fn rename_app(&self, old: &str, new: &str) -> CmdResult {
+        let old_id = AppId::new(old);
+        let new_id = self.derive_id(new)?;
+        if new_id.as_str() == "daemon" {
+            return Err((ErrorCode::InvalidRequest, format!("invalid app name: {new}")));
+        }
+        if new_id == old_id {
+            return Err((ErrorCode::InvalidRequest, format!("app is already named {old_id}")));
+        }
+        // One lock across the checks and the move, so no other command can
+        // register either name in between.
+        let mut apps = self.apps.lock().await;
+        let Some(app) = apps.get(&old_id) else {
+            return Err((ErrorCode::NotFound, format!("app not found: {old}")));
+        };
+        if !matches!(app.state, AppState::Stopped | AppState::Errored) {
+            return Err((
+                ErrorCode::InvalidRequest,
+                format!("cannot rename {old_id} while {}; stop it first", app.state),
+            ));
+        }
+        if apps.contains_key(&new_id) {
+            return Err((ErrorCode::AlreadyExists, format!("app already exists: {new_id}")));
+        }
+        if self.logs.has_log(&new_id) {
+            return Err((
+                ErrorCode::AlreadyExists,
+                format!("log files already exist for {new_id}"),
+            ));
+        }
+        if let Err(err) = self.logs.rename(&old_id, &new_id) {
+            return Err((ErrorCode::Internal, format!("cannot move log files: {err}")));
+        }
+        if let Err(err) = self.metrics.rename(&old_id, &new_id) {
+            tracing::warn!(app = %old_id, "cannot move metrics: {err}");
+        }
+        let mut app = apps.remove(&old_id).expect("checked above");
+        app.config.name = new_id.to_string();
+        apps.insert(new_id.clone(), app);
+        Ok(Some(format!("renamed {old_id} to {new_id}")))
+    }
+
+    /// Snapshot the whole registry for `bunctl export`.
+    pub async fn export_state(&self) -> DaemonSnapshot {
+        let apps = self.apps.lock().await;
+        let mut snapshots: Vec<AppSnapshot> = apps
+            .values()
+            .map(|app| AppSnapshot {
+                config: app.config.clone(),
+                running: matches!(app.state, AppState::Starting | AppState::Running),
+                restarts: app.restarts,
+            })
+            .collect();
+        // HashMap order is arbitrary; keep exports diffable.
+        snapshots.sort_by(|a, b| a.config.name.cmp(&b.config.name));
+        DaemonSnapshot {
+            version: env!("CARGO_PKG_VERSION").into(),
+            exported_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            apps: snapshots,
+        }
+    }
+
+    /// Replay an exported snapshot: register every app that is not already
+    /// managed and start the ones that were running at export time.
+    pub async fn import_state(self: &Arc<Self>, snapshot: DaemonSnapshot) -> CmdResult {
+        let mut registered = 0usize;
+        let mut started = 0usize;
+        let mut skipped = 0usize;
+        for entry in snapshot.apps {
+            let id = AppId::new(&entry.config.name);
+            {
+                let mut apps = self.apps.lock().await;
+                if apps.contains_key(&id) {
+                    skipped += 1;
+                    continue;
+                }
+                apps.insert(
+                    id.clone(),
+                    ManagedApp {
+                        config: entry.config,
+                        state: if entry.running { AppState::Starting } else { AppState::Stopped },
+                        pid: None,
+                        started_at: None,
+                        restarts: entry.restarts,
+                        stop_requested: false,
+                        fd_alerted: false,
+                        cpu_over_since: None,
+                        bun_stats: None,
+                        prev_cpu: None,
+                        samples: std::collections::VecDeque::with_capacity(SAMPLE_HISTORY),
+                        log_metrics: None,
+                        health: HealthState::default(),
+                        banner: Arc::default(),
+                        last_exit_reason: None,
+                        oom_kills: 0,
+                        spawn_info: None,
+                        memory_baseline: None,
+                        recycle_pending: false,
+                        on_demand: None,
+                    },
+                );
+            }
+            registered += 1;
+            if entry.running {
+                started += 1;
+                self.emit(Some(&id), DaemonEvent::StatusChange { state: AppState::Starting });
+                let daemon = self.clone();
+                let task_id = id.clone();
+                tokio::spawn(async move { daemon.run_app(task_id).await });
+            }
+        }
+        Ok(Some(format!(
+            "imported {registered} apps ({started} started, {skipped} already registered)"
+        )))
+    }
+
+    /// Record one state-changing command in the audit log.
+    pub fn record_audit(
+        &self,
+        peer: &str,
+        action: &str,
+        app: Option<String>,
+        success: bool,
+        detail: Option<String>,
+    ) {
+        let entry = bunctl_core::audit::AuditEntry {
+            ts: bunctl_core::time::unix_now(),
+            peer: peer.to_owned(),
+            action: action.to_owned(),
+            app,
+            success,
+            detail,
+        };
+        if let Err(err) = self.audit.record(&entry) {
+            tracing::warn!("cannot write audit entry: {err}");
+        }
+    }
+
+    /// Audit entries from the last `since_secs` seconds.
+    pub fn query_audit(
+        &self,
+        since_secs: u64,
+    ) -> Result<Vec<bunctl_core::audit::AuditEntry>, (ErrorCode, String)> {
+        let since = bunctl_core::time::unix_now().saturating_sub(since_secs);
+        self.audit
+            .query(since)
+            .map_err(|err| (ErrorCode::Internal, err.to_string()))
+    }
+
+    /// Persisted events from the last `since_secs` seconds, optionally
+    /// narrowed by the same filter subscriptions use (`bunctl events`).
+    pub fn query_events(
+        &self,
+        since_secs: u64,
+        filter: Option<&bunctl_ipc::message::EventFilter>,
+    ) -> Result<Vec<bunctl_core::EventRecord>, (ErrorCode, String)> {
+        let since = bunctl_core::time::unix_now().saturating_sub(since_secs);
+        let mut records = self
+            .event_log
+            .query(since)
+            .map_err(|err| (ErrorCode::Internal, err.to_string()))?;
+        if let Some(filter) = filter {
+            records.retain(|record| filter.matches(record.app.as_deref(), &record.event));
+        }
+        Ok(records)
+    }
+
+    /// The daemon's cached config of one app (`bunctl diff`).
+    pub async fn app_config(&self, name: &str) -> Result<AppConfig, (ErrorCode, String)> {
+        let id = AppId::new(name);
+        let apps = self.apps.lock().await;
+        let Some(app) = apps.get(&id) else {
+            return Err((ErrorCode::NotFound, format!("app not found: {name}")));
+        };
+        Ok(app.config.clone())
+    }
+
+    /// Status snapshot of one app. Querying the base name of an `instances`
+    /// cluster (`worker` for `worker-1..N`) answers with an aggregated
+    /// status carrying per-member detail.
+    pub async fn app_status(&self, name: &str) -> Result<AppStatus, (ErrorCode, String)> {
+        let id = AppId::new(name);
+        let apps = self.apps.lock().await;
+        let Some(app) = apps.get(&id) else {
+            if let Some(status) = self.cluster_status(&id, &apps) {
+                return Ok(status);
+            }
+            return Err((ErrorCode::NotFound, format!("app not found: {name}")));
+        };
+        Ok(self.status_of(&id, app))
+    }
+
+    /// Aggregate the members of an `instances` cluster under its base name:
+    /// summed cpu/memory/restarts, the most alive combined state, and one
+    /// [`bunctl_core::InstanceStatus`] per member. `None` when no member of
+    /// a cluster named `base` is registered.
+    fn cluster_status(
+        &self,
+        base: &AppId,
+        apps: &HashMap<AppId, ManagedApp>,
+    ) -> Option<AppStatus> {
+        let mut members: Vec<AppStatus> = apps
+            .iter()
+            .filter(|(id, app)| {
+                app.config
+                    .instance
+                    .is_some_and(|i| id.as_str() == format!("{base}-{i}"))
+            })
+            .map(|(id, app)| self.status_of(id, app))
+            .collect();
+        if members.is_empty() {
+            return None;
+        }
+        members.sort_by_key(|m| m.instance_id);
+        // The cluster is as alive as its liveliest member.
+        let state = [
+            AppState::Running,
+            AppState::Starting,
+            AppState::Stopping,
+            AppState::Paused,
+            AppState::Errored,
+            AppState::Stopped,
+        ]
+        .into_iter()
+        .find(|s| members.iter().any(|m| m.state == *s))
+        .expect("members non-empty");
+        let mut status = AppStatus {
+            name: base.clone(),
+            state,
+            instances_total: members[0].instances_total,
+            cpu_percent: members
+                .iter()
+                .filter_map(|m| m.cpu_percent)
+                .reduce(|a, b| a + b),
+            memory_bytes: members
+                .iter()
+                .filter_map(|m| m.memory_bytes)
+                .reduce(|a, b| a + b),
+            restarts: members.iter().map(|m| m.restarts).sum(),
+            ..Self::orphan_status(base.as_str())
+        };
+        status.orphan = false;
+        status.instances = members
+            .into_iter()
+            .map(|m| bunctl_core::InstanceStatus {
+                instance: m.instance_id.unwrap_or(0),
+                state: m.state,
+                pid: m.pid,
+                memory_bytes: m.memory_bytes,
+            })
+            .collect();
+        Some(status)
+    }
+
+    /// Status plus the startup banner — the first lines of output captured
+    /// at the most recent start attempt — of one app (`bunctl describe`).
+    pub async fn describe(&self, name: &str) -> Result<(AppStatus, Vec<String>), (ErrorCode, String)> {
+        let id = AppId::new(name);
+        let apps = self.apps.lock().await;
+        let Some(app) = apps.get(&id) else {
+            return Err((ErrorCode::NotFound, format!("app not found: {name}")));
+        };
+        let banner = app.banner.lock().expect("banner poisoned").clone();
+        let mut status = self.status_of(&id, app);
+        // The spawn snapshot travels in describe only; list/status stay
+        // lean.
+        status.spawn_info = app.spawn_info.clone();
+        Ok((status, banner))
+    }
+
+    /// Status snapshots of every registered app, sorted by name.
+    pub async fn all_statuses(&self) -> Vec<AppStatus> {
+        let apps = self.apps.lock().await;
+        let mut list: Vec<AppStatus> =
+            apps.iter().map(|(id, app)| self.status_of(id, app)).collect();
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        list
+    }
+
+    /// Env subset shown in status output: the app's `status_env` allow-list
+    /// (common operational prefixes when unset), with values matching the
+    /// redaction deny-list masked before they ever leave the daemon.
+    fn status_env(&self, config: &AppConfig) -> std::collections::BTreeMap<String, String> {
+        const SHOW_PREFIXES: &[&str] = &["PORT", "NODE_ENV", "DATABASE", "REDIS", "URL", "HOST"];
+        let selected = |key: &str| {
+            if config.status_env.is_empty() {
+                SHOW_PREFIXES.iter().any(|p| key.starts_with(p))
+            } else {
+                config.status_env.iter().any(|pat| match pat.strip_suffix('*') {
+                    Some(prefix) => key.starts_with(prefix),
+                    None => key == pat,
+                })
+            }
+        };
+        config
+            .env
+            .iter()
+            .filter(|(key, _)| selected(key))
+            .map(|(key, value)| (key.clone(), self.redacted_value(key, value)))
+            .collect()
+    }
+
+    /// `value` as it may leave the daemon: masked when `key` matches the
+    /// redaction deny-list.
+    fn redacted_value(&self, key: &str, value: &str) -> String {
+        let upper = key.to_uppercase();
+        if self.redact_env.iter().any(|pat| upper.contains(pat)) {
+            "[redacted]".to_owned()
+        } else {
+            value.to_owned()
+        }
+    }
+
+    /// The env snapshot recorded per spawn: everything the child gets on
+    /// top of the daemon's environment, deny-list values masked and
+    /// secret-sourced keys masked entirely. Must be built from the config
+    /// *before* secrets are resolved into it.
+    fn spawn_env_snapshot(
+        &self,
+        config: &AppConfig,
+    ) -> std::collections::BTreeMap<String, String> {
+        let mut env: std::collections::BTreeMap<String, String> = config
+            .env
+            .iter()
+            .map(|(key, value)| (key.clone(), self.redacted_value(key, value)))
+            .collect();
+        for key in config.env_secrets.keys() {
+            env.insert(key.clone(), "[secret]".to_owned());
+        }
+        env
+    }
+
+    fn status_of(&self, id: &AppId, app: &ManagedApp) -> AppStatus {
+        let info = app.pid.and_then(bunctl_supervisor::get_process_info);
+        let last_sample = app.samples.back().copied();
+        AppStatus {
+            name: id.clone(),
+            original_name: (app.config.name != id.as_str()).then(|| app.config.name.clone()),
+            instance_id: app.config.instance,
+            instances_total: app.config.instances_total,
+            instances: Vec::new(),
+            description: app.config.description.clone(),
+            tags: app.config.tags.clone(),
+            state: app.state,
+            pid: app.pid,
+            cpu_percent: last_sample
+                .map(|(cpu, _)| f64::from(cpu))
+                .or(info.as_ref().and_then(|i| i.cpu_percent)),
+            memory_bytes: info.as_ref().and_then(|i| i.memory_bytes),
+            uptime_secs: app.started_at.map(|t| t.elapsed().as_secs()),
+            ready: app.health.verdicts.ready,
+            on_demand: app.on_demand.clone(),
+            threads: info.as_ref().and_then(|i| i.threads),
+            open_files: info.as_ref().and_then(|i| i.open_files),
+            heap_bytes: app.bun_stats.and_then(|s| s.heap_used),
+            event_loop_lag_ms: app.bun_stats.and_then(|s| s.event_loop_lag_ms),
+            cwd: app.config.cwd.clone(),
+            env: self.status_env(&app.config),
+            restarts: app.restarts,
+            last_exit_reason: app.last_exit_reason.clone(),
+            log_metrics: app
+                .log_metrics
+                .as_ref()
+                .map(|m| *m.lock().expect("log metrics poisoned"))
+                .unwrap_or_default(),
+            orphan: false,
+            cpu_history: app.samples.iter().map(|(cpu, _)| *cpu).collect(),
+            memory_history: app.samples.iter().map(|(_, mem)| *mem).collect(),
+            spawn_info: None,
+        }
+    }
+
+    /// Periodically sample CPU/memory of every running app into its ring
+    /// buffer; spawned once at daemon startup.
+    pub async fn run_sampler(self: Arc<Self>) {
+        let hz = bunctl_supervisor::clock_ticks_per_sec() as f64;
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(SAMPLE_INTERVAL_SECS));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let now = Instant::now();
+            let ts = bunctl_core::time::unix_now();
+            // (app, which policy fired, uptime) — restarted after the lock
+            // is released; a graceful stop can take the full stop_timeout.
+            let mut recycle: Vec<(AppId, &'static str, u64)> = Vec::new();
+            let mut apps = self.apps.lock().await;
+            for (id, app) in apps.iter_mut() {
+                let Some(pid) = app.pid else {
+                    app.prev_cpu = None;
+                    continue;
+                };
+                let info = bunctl_supervisor::get_process_info(pid);
+                let memory = info.as_ref().and_then(|i| i.memory_bytes).unwrap_or(0);
+                if app.config.bun_stats {
+                    app.bun_stats = self.read_bun_stats(id);
+                }
+                if let (Some(limit), Some(open)) =
+                    (app.config.max_open_files, info.as_ref().and_then(|i| i.open_files))
+                {
+                    if open > limit {
+                        if !app.fd_alerted {
+                            tracing::warn!(
+                                app = %id,
+                                open,
+                                limit,
+                                "open file descriptors above configured limit"
+                            );
+                            app.fd_alerted = true;
+                        }
+                    } else {
+                        app.fd_alerted = false;
+                    }
+                }
+                let ticks = bunctl_supervisor::cpu_ticks(pid);
+                let cpu = match (ticks, app.prev_cpu) {
+                    (Some(ticks), Some((prev_ticks, prev_at))) => {
+                        let cpu_secs = ticks.saturating_sub(prev_ticks) as f64 / hz;
+                        let elapsed = now.duration_since(prev_at).as_secs_f64().max(0.001);
+                        (cpu_secs / elapsed * 100.0) as f32
+                    }
+                    _ => 0.0,
+                };
+                if let Some(ticks) = ticks {
+                    app.prev_cpu = Some((ticks, now));
+                }
+                if let (Some(limit), CpuLimitMode::Soft) =
+                    (app.config.max_cpu_percent, app.config.cpu_limit_mode)
+                {
+                    if f64::from(cpu) > limit {
+                        let since = *app.cpu_over_since.get_or_insert(now);
+                        self.emit(
+                            Some(id),
+                            DaemonEvent::ResourceLimitExceeded {
+                                resource: "cpu".into(),
+                                current: f64::from(cpu),
+                                limit,
+                                over_secs: now.duration_since(since).as_secs(),
+                            },
+                        );
+                    } else {
+                        app.cpu_over_since = None;
+                    }
+                }
+                if app.samples.len() >= SAMPLE_HISTORY {
+                    app.samples.pop_front();
+                }
+                app.samples.push_back((cpu, memory));
+                let uptime = app.started_at.map(|t| now.duration_since(t));
+                // The growth baseline is the first sample of a stable run.
+                if app.memory_baseline.is_none()
+                    && memory > 0
+                    && uptime.is_some_and(|up| up.as_secs() >= STABLE_UPTIME_SECS)
+                {
+                    app.memory_baseline = Some(memory);
+                }
+                if app.state == AppState::Running && !app.recycle_pending {
+                    let uptime_secs = uptime.unwrap_or_default().as_secs();
+                    // Containers get `max_memory` from the runtime
+                    // (`--memory`); for processes the sampler enforces it.
+                    if app.config.exec_kind == ExecKind::Process
+                        && app.config.max_memory.is_some_and(|limit| memory > limit)
+                    {
+                        app.recycle_pending = true;
+                        recycle.push((id.clone(), "max_memory", uptime_secs));
+                    } else if app
+                        .config
+                        .max_uptime
+                        .is_some_and(|max| uptime.is_some_and(|up| up >= max))
+                    {
+                        app.recycle_pending = true;
+                        recycle.push((id.clone(), "max_uptime", uptime_secs));
+                    } else if let (Some(percent), Some(baseline)) =
+                        (app.config.recycle_on_memory_growth_percent, app.memory_baseline)
+                    {
+                        if memory as f64 > baseline as f64 * (1.0 + percent / 100.0) {
+                            app.recycle_pending = true;
+                            recycle.push((id.clone(), "memory_growth", uptime_secs));
+                        }
+                    }
+                }
+                let sample = MetricSample {
+                    ts,
+                    cpu_percent: cpu,
+                    memory_bytes: memory,
+                    restarts: app.restarts,
+                };
+                if let Err(err) = self.metrics.append(id, &sample) {
+                    tracing::warn!(app = %id, "cannot persist metrics sample: {err}");
+                }
+            }
+            drop(apps);
+            for (id, trigger, uptime_secs) in recycle {
+                self.emit(
+                    Some(&id),
+                    DaemonEvent::Recycled { trigger: trigger.into(), uptime_secs },
+                );
+                let daemon = self.clone();
+                tokio::spawn(async move {
+                    tracing::info!(app = %id, "recycling ({trigger})");
+                    if let Err((_, message)) = daemon.restart_app(id.as_str(), None).await {
+                        tracing::warn!(app = %id, "recycle failed: {message}");
+                    }
+                });
+            }
+        }
+    }
+
+    /// Run an internal task with panic isolation. The task runs in its own
+    /// tokio task; if it panics (or returns, which none of the daemon's
+    /// loops should), the panic is logged — the hook installed at startup
+    /// adds the backtrace — the `task_restarts` counter is bumped and the
+    /// task is started again after a short pause. Cancellation at shutdown
+    /// ends the loop.
+    pub fn supervised<F, Fut>(
+        self: &Arc<Self>,
+        name: &'static str,
+        task: F,
+    ) -> impl std::future::Future<Output = ()>
+    where
+        F: Fn(Arc<Daemon>) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let daemon = self.clone();
+        async move {
+            loop {
+                match tokio::spawn(task(daemon.clone())).await {
+                    Ok(()) => {
+                        tracing::warn!(task = name, "background task exited; restarting");
+                    }
+                    Err(err) if err.is_panic() => {
+                        let panic = err.into_panic();
+                        let message = panic
+                            .downcast_ref::<&str>()
+                            .copied()
+                            .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+                            .unwrap_or("<non-string payload>");
+                        daemon
+                            .task_restarts
+                            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                        tracing::error!(
+                            task = name,
+                            "background task panicked: {message}; restarting"
+                        );
+                    }
+                    // Cancelled: the runtime is shutting down.
+                    Err(_) => return,
+                }
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+            }
+        }
+    }
+
+    /// Watch the daemon's own health — RSS, descriptor count and event-loop
+    /// lag — warning past thresholds, and keep systemd's watchdog fed when
+    /// one is armed; spawned once at daemon startup. Lag is measured as how
+    /// late a one-second sleep wakes up, so a blocked runtime both shows up
+    /// in `daemon status` and stops the watchdog pings.
+    pub async fn run_self_monitor(self: Arc<Self>) {
+        let watchdog = crate::watchdog::ping_interval();
+        if let Some(every) = watchdog {
+            tracing::info!("systemd watchdog armed, pinging every {every:.0?}");
+        }
+        let period = std::time::Duration::from_secs(1);
+        let mut last_ping = Instant::now();
+        let mut lag_alerted = false;
+        let mut rss_alerted = false;
+        let mut fd_alerted = false;
+        loop {
+            let before = Instant::now();
+            tokio::time::sleep(period).await;
+            let lag_ms = before.elapsed().saturating_sub(period).as_millis() as u64;
+            self.self_lag_ms.store(lag_ms, std::sync::atomic::Ordering::Relaxed);
+            if lag_ms > SELF_LAG_WARN_MS {
+                if !lag_alerted {
+                    tracing::warn!(lag_ms, "daemon event loop is lagging");
+                    lag_alerted = true;
+                }
+            } else {
+                lag_alerted = false;
+            }
+            if let Some(info) = bunctl_supervisor::get_process_info(std::process::id()) {
+                match info.memory_bytes {
+                    Some(rss) if rss > SELF_RSS_WARN_BYTES => {
+                        if !rss_alerted {
+                            tracing::warn!(rss, "daemon memory usage is high");
+                            rss_alerted = true;
+                        }
+                    }
+                    _ => rss_alerted = false,
+                }
+                match info.open_files {
+                    Some(open) if open > SELF_FD_WARN => {
+                        if !fd_alerted {
+                            tracing::warn!(open, "daemon open descriptor count is high");
+                            fd_alerted = true;
+                        }
+                    }
+                    _ => fd_alerted = false,
+                }
+            }
+            if let Some(every) = watchdog {
+                if last_ping.elapsed() >= every {
+                    crate::watchdog::notify("WATCHDOG=1");
+                    last_ping = Instant::now();
+                }
+            }
+        }
+    }
+
+    /// The daemon's own resource usage, reported under the reserved name
+    /// `daemon` (see `status --summary`).
+    pub fn self_status(&self) -> AppStatus {
+        let info = bunctl_supervisor::get_process_info(std::process::id());
+        AppStatus {
+            name: AppId::new("daemon"),
+            original_name: None,
+            instance_id: None,
+            instances_total: None,
+            instances: Vec::new(),
+            description: None,
+            tags: Vec::new(),
+            state: AppState::Running,
+            pid: Some(std::process::id()),
+            cpu_percent: info.as_ref().and_then(|i| i.cpu_percent),
+            memory_bytes: info.as_ref().and_then(|i| i.memory_bytes),
+            uptime_secs: Some(self.started.elapsed().as_secs()),
+            ready: None,
+            on_demand: None,
+            threads: info.as_ref().and_then(|i| i.threads),
+            open_files: info.as_ref().and_then(|i| i.open_files),
+            heap_bytes: None,
+            event_loop_lag_ms: Some(
+                self.self_lag_ms.load(std::sync::atomic::Ordering::Relaxed) as f64,
+            ),
+            cwd: None,
+            env: Default::default(),
+            restarts: self.task_restarts.load(std::sync::atomic::Ordering::Relaxed),
+            last_exit_reason: None,
+            log_metrics: Default::default(),
+            orphan: false,
+            cpu_history: Vec::new(),
+            memory_history: Vec::new(),
+            spawn_info: None,
+        }
+    }
+
+    /// Placeholder status for an orphan log entry (see `list --all`).
+    pub fn orphan_status(name: &str) -> AppStatus {
+        AppStatus {
+            name: AppId::new(name),
+            original_name: None,
+            instance_id: None,
+            instances_total: None,
+            instances: Vec::new(),
+            description: None,
+            tags: Vec::new(),
+            state: AppState::Stopped,
+            pid: None,
+            cpu_percent: None,
+            memory_bytes: None,
+            uptime_secs: None,
+            ready: None,
+            on_demand: None,
+            threads: None,
+            open_files: None,
+            heap_bytes: None,
+            event_loop_lag_ms: None,
+            cwd: None,
+            env: Default::default(),
+            restarts: 0,
+            last_exit_reason: None,
+            log_metrics: Default::default(),
+            orphan: true,
+            cpu_history: Vec::new(),
+            memory_history: Vec::new(),
+            spawn_info: None,
+        }
+    }
+
+    /// Persisted resource samples for an app, newer than `since_secs` ago.
+    /// Works for unmanaged apps too, as long as metrics are still on disk.
+    pub async fn query_metrics(
+        &self,
+        name: &str,
+        since_secs: u64,
+    ) -> Result<Vec<MetricSample>, (ErrorCode, String)> {
+        let id = AppId::new(name);
+        let since = bunctl_core::time::unix_now().saturating_sub(since_secs);
+        let samples = self
+            .metrics
+            .query(&id, since)
+            .map_err(|err| (ErrorCode::Internal, err.to_string()))?;
+        if samples.is_empty() && !self.is_managed(&id).await {
+            return Err((ErrorCode::NotFound, format!("no metrics for app: {name}")));
+        }
+        Ok(samples)
+    }
+
+    /// Registered app names, sorted.
+    pub async fn list_apps(&self) -> Vec<String> {
+        let apps = self.apps.lock().await;
+        let mut names: Vec<String> = apps.keys().map(ToString::to_string).collect();
+        names.sort();
+        names
+    }
+
+    /// Whether an app is registered.
+    pub async fn is_managed(&self, id: &AppId) -> bool {
+        self.apps.lock().await.contains_key(id)
+    }
+
+    /// The raw configured name of a registered app, for collision checks.
+    async fn config_name_of(&self, id: &AppId) -> Option<String> {
+        self.apps.lock().await.get(id).map(|app| app.config.name.clone())
+    }
+
+    /// Recent log lines. Managed apps always read; unmanaged names only when
+    /// `include_stopped` is set and an orphan log file exists on disk.
+    pub async fn read_logs(
+        &self,
+        name: &str,
+        lines: usize,
+        include_stopped: bool,
+        grep: Option<&str>,
+    ) -> Result<Vec<String>, (ErrorCode, String)> {
+        let id = AppId::new(name);
+        if !self.is_managed(&id).await {
+            if !include_stopped {
+                let hint = if self.logs.has_log(&id) {
+                    "; a log file exists, pass --include-stopped to read it"
+                } else {
+                    ""
+                };
+                return Err((ErrorCode::NotFound, format!("app not found: {name}{hint}")));
+            }
+            if !self.logs.has_log(&id) {
+                return Err((ErrorCode::NotFound, format!("no log file for app: {name}")));
+            }
+        }
+        self.logs
+            .read_last_entries(&id, lines, grep)
+            .map_err(|err| (ErrorCode::Internal, err.to_string()))
+    }
+
+    /// Stop every app and emit the shutdown event. Stops run concurrently
+    /// under one global deadline — sequential stops could take
+    /// `apps × stop_timeout` and outlive the supervising init's patience —
+    /// and apps that do not make the deadline are named in the shutdown
+    /// log, since their output may not be fully flushed.
+    pub async fn shutdown(&self) {
+        self.emit(None, DaemonEvent::DaemonShutdown);
+        let deadline =
+            tokio::time::Instant::now() + std::time::Duration::from_secs(SHUTDOWN_DEADLINE_SECS);
+        let names = self.list_apps().await;
+        let stops = names.iter().map(|name| async move {
+            match tokio::time::timeout_at(deadline, self.stop_app(name)).await {
+                Ok(Ok(_)) => {}
+                Ok(Err((_, msg))) => {
+                    tracing::warn!(app = %name, "stop during shutdown failed: {msg}");
+                }
+                Err(_) => {
+                    tracing::warn!(
+                        app = %name,
+                        "not stopped within the {SHUTDOWN_DEADLINE_SECS}s shutdown deadline; \
+                         its logs may not be fully flushed"
+                    );
+                }
+            }
+        });
+        futures::future::join_all(stops).await;
+    }
+}
@@ -0,0 +1,438 @@
+//! Health check probes and verdict combination.
+//!
+//! The daemon runs each app's configured checks from `Daemon::run_health`;
+//! this module knows how to perform one attempt and how a round's results
+//! combine into per-role verdicts, flips and `health_on_failure` firing.
+
+use std::time::Duration;
+
+use bunctl_core::config::{HealthCheckType, HealthPolicy, HealthRole};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Combined verdict of one round's results for `role` under `policy`:
+/// `None` when no check of that role ran, otherwise whether the role
+/// passed plus the labels of the components that did not.
+pub(crate) fn verdict(
+    results: &[(HealthRole, Option<String>)],
+    policy: HealthPolicy,
+    role: HealthRole,
+) -> Option<(bool, Vec<String>)> {
+    let of_role: Vec<&Option<String>> =
+        results.iter().filter(|(r, _)| *r == role).map(|(_, err)| err).collect();
+    if of_role.is_empty() {
+        return None;
+    }
+    let failed: Vec<String> = of_role.iter().filter_map(|err| (*err).clone()).collect();
+    let ok = match policy {
+        HealthPolicy::All => failed.is_empty(),
+        HealthPolicy::Any => failed.len() < of_role.len(),
+    };
+    Some((ok, failed))
+}
+
+/// Last-round verdicts and the consecutive-failure counter, carried
+/// between rounds per app.
+#[derive(Debug, Default)]
+pub(crate) struct VerdictState {
+    /// Combined liveness verdict of the last round, when liveness checks
+    /// ran; `None` until the first round.
+    pub live: Option<bool>,
+    /// Combined readiness verdict of the last round, when readiness checks
+    /// ran (surfaced as `AppStatus.ready`).
+    pub ready: Option<bool>,
+    /// Consecutive failing liveness rounds; reset on a pass and after
+    /// `health_on_failure` fires.
+    pub failing: u32,
+}
+
+/// What one round changed: per-role `(flipped, ok, failed)` and whether
+/// `health_on_failure` should fire.
+pub(crate) struct RoundOutcome {
+    pub live: Option<(bool, bool, Vec<String>)>,
+    pub ready: Option<(bool, bool, Vec<String>)>,
+    pub act: bool,
+}
+
+/// Fold one round's per-role verdicts into the carried state. An app with
+/// no verdict yet counts as healthy, so the first round only flips when it
+/// is a failure; the action fires once per `threshold` failing rounds, not
+/// on every one, so a slow recovery is not hammered.
+pub(crate) fn apply_round(
+    state: &mut VerdictState,
+    live: Option<(bool, Vec<String>)>,
+    ready: Option<(bool, Vec<String>)>,
+    threshold: u32,
+) -> RoundOutcome {
+    let live = live.map(|(ok, failed)| {
+        let flipped = state.live.replace(ok).unwrap_or(true) != ok;
+        state.failing = if ok { 0 } else { state.failing + 1 };
+        (flipped, ok, failed)
+    });
+    let act = state.failing >= threshold.max(1);
+    if act {
+        state.failing = 0;
+    }
+    let ready = ready.map(|(ok, failed)| {
+        let flipped = state.ready.replace(ok).unwrap_or(true) != ok;
+        (flipped, ok, failed)
+    });
+    RoundOutcome { live, ready, act }
+}
+
+/// Run one probe, bounded by `timeout`. `Ok(())` means healthy; the error
+/// string says what failed.
+pub(crate) async fn probe(check: &HealthCheckType, timeout: Duration) -> Result<(), String> {
+    match tokio::time::timeout(timeout, attempt(check)).await {
+        Ok(result) => result,
+        Err(_) => Err(format!("timed out after {}s", timeout.as_secs())),
+    }
+}
+
+async fn attempt(check: &HealthCheckType) -> Result<(), String> {
+    match check {
+        HealthCheckType::Tcp { addr } => {
+            tokio::net::TcpStream::connect(addr)
+                .await
+                .map(|_| ())
+                .map_err(|err| format!("connect {addr}: {err}"))
+        }
+        HealthCheckType::Http { url, method, headers, body_match, ca_file, insecure_tls } => {
+            http_probe(
+                url,
+                method.as_deref().unwrap_or("GET"),
+                headers,
+                body_match.as_deref(),
+                ca_file.as_deref(),
+                *insecure_tls,
+            )
+            .await
+        }
+        HealthCheckType::Exec { argv } => {
+            let Some((cmd, args)) = argv.split_first() else {
+                return Err("empty exec argv".into());
+            };
+            let status = tokio::process::Command::new(cmd)
+                .args(args)
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .status()
+                .await
+                .map_err(|err| format!("spawn {cmd}: {err}"))?;
+            if status.success() {
+                Ok(())
+            } else {
+                Err(format!("{cmd} exited with {status}"))
+            }
+        }
+    }
+}
+
+/// Bytes of the response we are willing to read when checking the body.
+const HTTP_BODY_CAP: usize = 64 * 1024;
+
+/// Minimal HTTP/1.1 request expecting a 2xx status line, with optional
+/// extra headers and a wildcard pattern the body must match. `https://`
+/// needs the `tls` feature; trust comes from `ca_file` or the
+/// `insecure_tls` toggle, never system roots.
+async fn http_probe(
+    url: &str,
+    method: &str,
+    headers: &std::collections::BTreeMap<String, String>,
+    body_match: Option<&str>,
+    ca_file: Option<&std::path::Path>,
+    insecure_tls: bool,
+) -> Result<(), String> {
+    let (secure, rest) = if let Some(rest) = url.strip_prefix("https://") {
+        (true, rest)
+    } else if let Some(rest) = url.strip_prefix("http://") {
+        (false, rest)
+    } else {
+        return Err(format!("unsupported url (only http:// and https:// are): {url}"));
+    };
+    let (host_port, path) = match rest.find('/') {
+        Some(idx) => (&rest[..idx], &rest[idx..]),
+        None => (rest, "/"),
+    };
+    let addr = if host_port.contains(':') {
+        host_port.to_string()
+    } else {
+        format!("{host_port}:{}", if secure { 443 } else { 80 })
+    };
+    let stream = tokio::net::TcpStream::connect(&addr)
+        .await
+        .map_err(|err| format!("connect {addr}: {err}"))?;
+    let mut request =
+        format!("{method} {path} HTTP/1.1\r\nHost: {host_port}\r\nConnection: close\r\n");
+    for (name, value) in headers {
+        request.push_str(&format!("{name}: {value}\r\n"));
+    }
+    request.push_str("\r\n");
+    if secure {
+        #[cfg(feature = "tls")]
+        {
+            let host = host_port.split(':').next().unwrap_or(host_port);
+            let stream = tls::connect(stream, host, ca_file, insecure_tls).await?;
+            return exchange(stream, &request, body_match).await;
+        }
+        #[cfg(not(feature = "tls"))]
+        {
+            let _ = (ca_file, insecure_tls);
+            return Err(format!(
+                "https health checks need a daemon built with the `tls` feature: {url}"
+            ));
+        }
+    }
+    exchange(stream, &request, body_match).await
+}
+
+/// Send the request and judge the response, over plain TCP or TLS.
+async fn exchange<S>(mut stream: S, request: &str, body_match: Option<&str>) -> Result<(), String>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    stream.write_all(request.as_bytes()).await.map_err(|err| format!("send: {err}"))?;
+    let mut response = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 4096];
+    loop {
+        let n = stream.read(&mut chunk).await.map_err(|err| format!("read: {err}"))?;
+        if n == 0 || response.len() >= HTTP_BODY_CAP {
+            break;
+        }
+        response.extend_from_slice(&chunk[..n]);
+        // Without a pattern the status line is all we need.
+        if body_match.is_none() && response.windows(2).any(|w| w == b"\r\n") {
+            break;
+        }
+    }
+    let text = String::from_utf8_lossy(&response);
+    // "HTTP/1.1 200 OK" — the status code is the second token.
+    let code = text.split_whitespace().nth(1).unwrap_or("");
+    if !code.starts_with('2') {
+        return Err(format!("status {}", if code.is_empty() { "unreadable" } else { code }));
+    }
+    if let Some(pattern) = body_match {
+        let body = text.split_once("\r\n\r\n").map(|(_, body)| body).unwrap_or("");
+        if !wildcard_match(pattern, body) {
+            return Err(format!("body does not match {pattern:?}"));
+        }
+    }
+    Ok(())
+}
+
+/// Match a response body against a pattern where `*` spans any run of
+/// characters.
+fn wildcard_match(pattern: &str, body: &str) -> bool {
+    let segments: Vec<&str> = pattern.split('*').collect();
+    let mut rest = body;
+    let last = segments.len() - 1;
+    for (idx, seg) in segments.iter().enumerate() {
+        if seg.is_empty() {
+            continue;
+        }
+        if idx == 0 {
+            match rest.strip_prefix(seg) {
+                Some(r) => rest = r,
+                None => return false,
+            }
+        } else if idx == last {
+            return rest.ends_with(seg);
+        } else {
+            match rest.find(seg) {
+                Some(pos) => rest = &rest[pos + seg.len()..],
+                None => return false,
+            }
+        }
+    }
+    true
+}
+
+/// TLS for `https://` health probes (`tls` feature). Like the TCP
+/// transport, trust comes from a PEM CA file rather than system roots;
+/// `insecure_tls` skips verification entirely for endpoints without one.
+#[cfg(feature = "tls")]
+mod tls {
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use tokio_rustls::client::TlsStream;
+    use tokio_rustls::rustls::client::danger::{
+        HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+    };
+    use tokio_rustls::rustls::crypto::{self, CryptoProvider};
+    use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+    use tokio_rustls::rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+    use tokio_rustls::TlsConnector;
+
+    /// Wrap an open TCP stream in TLS, trusting `ca_file` or — with
+    /// `insecure_tls` — any certificate the endpoint presents.
+    pub(super) async fn connect(
+        stream: tokio::net::TcpStream,
+        host: &str,
+        ca_file: Option<&Path>,
+        insecure_tls: bool,
+    ) -> Result<TlsStream<tokio::net::TcpStream>, String> {
+        let config = if insecure_tls {
+            ClientConfig::builder()
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(NoVerify::default()))
+                .with_no_client_auth()
+        } else {
+            let Some(ca_file) = ca_file else {
+                return Err("https health check needs ca_file or insecure_tls".into());
+            };
+            let pem = std::fs::read(ca_file)
+                .map_err(|err| format!("read {}: {err}", ca_file.display()))?;
+            let mut roots = RootCertStore::empty();
+            for cert in rustls_pemfile::certs(&mut pem.as_slice()) {
+                let cert = cert.map_err(|err| format!("parse {}: {err}", ca_file.display()))?;
+                roots.add(cert).map_err(|err| format!("bad CA certificate: {err}"))?;
+            }
+            ClientConfig::builder().with_root_certificates(roots).with_no_client_auth()
+        };
+        let name = ServerName::try_from(host.to_owned())
+            .map_err(|err| format!("invalid server name {host}: {err}"))?;
+        TlsConnector::from(Arc::new(config))
+            .connect(name, stream)
+            .await
+            .map_err(|err| format!("tls handshake: {err}"))
+    }
+
+    /// Accepts any server certificate; signatures are still checked so the
+    /// peer at least holds the key it presented.
+    #[derive(Debug)]
+    struct NoVerify(CryptoProvider);
+
+    impl Default for NoVerify {
+        fn default() -> Self {
+            Self(crypto::ring::default_provider())
+        }
+    }
+
+    impl ServerCertVerifier for NoVerify {
+        fn verify_server_cert(
+            &self,
+            _end_entity: &CertificateDer<'_>,
+            _intermediates: &[CertificateDer<'_>],
+            _server_name: &ServerName<'_>,
+            _ocsp_response: &[u8],
+            _now: UnixTime,
+        ) -> Result<ServerCertVerified, tokio_rustls::rustls::Error> {
+            Ok(ServerCertVerified::assertion())
+        }
+
+        fn verify_tls12_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+            crypto::verify_tls12_signature(
+                message,
+                cert,
+                dss,
+                &self.0.signature_verification_algorithms,
+            )
+        }
+
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+            crypto::verify_tls13_signature(
+                message,
+                cert,
+                dss,
+                &self.0.signature_verification_algorithms,
+            )
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+            self.0.signature_verification_algorithms.supported_schemes()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results(spec: &[(HealthRole, bool)]) -> Vec<(HealthRole, Option<String>)> {
+        spec.iter()
+            .map(|&(role, ok)| (role, (!ok).then(|| "probe failed".to_owned())))
+            .collect()
+    }
+
+    #[test]
+    fn verdict_combines_per_policy() {
+        let mixed = results(&[
+            (HealthRole::Liveness, true),
+            (HealthRole::Liveness, false),
+            (HealthRole::Readiness, true),
+        ]);
+        // `all` needs every liveness check passing; `any` is content with
+        // one.
+        let (ok, failed) = verdict(&mixed, HealthPolicy::All, HealthRole::Liveness).unwrap();
+        assert!(!ok);
+        assert_eq!(failed.len(), 1);
+        let (ok, _) = verdict(&mixed, HealthPolicy::Any, HealthRole::Liveness).unwrap();
+        assert!(ok);
+        // Readiness combines independently of the liveness failure.
+        let (ok, _) = verdict(&mixed, HealthPolicy::All, HealthRole::Readiness).unwrap();
+        assert!(ok);
+        // No checks of a role means no verdict, not a pass.
+        assert!(verdict(&results(&[]), HealthPolicy::All, HealthRole::Liveness).is_none());
+    }
+
+    #[test]
+    fn first_round_failure_flips_but_first_pass_does_not() {
+        let mut state = VerdictState::default();
+        let outcome = apply_round(&mut state, Some((true, Vec::new())), None, 1);
+        let (flipped, ok, _) = outcome.live.unwrap();
+        assert!(!flipped && ok);
+
+        let mut state = VerdictState::default();
+        let outcome = apply_round(&mut state, Some((false, vec!["tcp: refused".into()])), None, 1);
+        let (flipped, ok, _) = outcome.live.unwrap();
+        assert!(flipped && !ok);
+        assert!(outcome.act);
+    }
+
+    #[test]
+    fn action_fires_every_threshold_rounds_and_resets_on_pass() {
+        let mut state = VerdictState::default();
+        let fail = || Some((false, Vec::new()));
+        assert!(!apply_round(&mut state, fail(), None, 3).act);
+        assert!(!apply_round(&mut state, fail(), None, 3).act);
+        assert!(apply_round(&mut state, fail(), None, 3).act);
+        // The counter restarted after firing; two more failures are not
+        // enough.
+        assert!(!apply_round(&mut state, fail(), None, 3).act);
+        assert!(!apply_round(&mut state, fail(), None, 3).act);
+        // A pass resets the count entirely.
+        let outcome = apply_round(&mut state, Some((true, Vec::new())), None, 3);
+        assert!(!outcome.act);
+        assert_eq!(state.failing, 0);
+        // Only the recovery flips, not the repeated failures before it.
+        let (flipped, ok, _) = outcome.live.unwrap();
+        assert!(flipped && ok);
+    }
+
+    #[test]
+    fn threshold_zero_behaves_like_one() {
+        let mut state = VerdictState::default();
+        assert!(apply_round(&mut state, Some((false, Vec::new())), None, 0).act);
+    }
+
+    #[test]
+    fn readiness_flips_without_touching_the_failure_counter() {
+        let mut state = VerdictState::default();
+        let outcome =
+            apply_round(&mut state, None, Some((false, vec!["http: 503".into()])), 3);
+        let (flipped, ok, _) = outcome.ready.unwrap();
+        assert!(flipped && !ok);
+        assert!(!outcome.act);
+        assert_eq!(state.failing, 0);
+    }
+}
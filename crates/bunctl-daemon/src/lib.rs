@@ -0,0 +1,17 @@
+//! The bunctl supervision daemon.
+//!
+//! One daemon per user (or per machine) owns the managed apps: it spawns
+//! them through `bunctl-supervisor`, captures their output into
+//! `bunctl-logging`, restarts them on failure, and answers CLI/client
+//! requests over `bunctl-ipc`.
+
+pub mod daemon;
+mod health;
+mod netns;
+pub mod ondemand;
+pub mod pidfile;
+pub mod server;
+pub mod watchdog;
+pub mod webhook;
+
+pub use daemon::{Daemon, EventEnvelope};
@@ -0,0 +1,231 @@
+use std::path::PathBuf;
+
+use bunctl_daemon::pidfile::PidRegistry;
+use bunctl_daemon::{server, Daemon};
+use bunctl_ipc::IpcServer;
+use bunctl_logging::LogManager;
+use bunctl_metrics::MetricsStore;
+use clap::Parser;
+
+/// The bunctl supervision daemon.
+#[derive(Debug, Parser)]
+#[command(name = "bunctl-daemon", version, about)]
+struct Args {
+    /// Control socket path (default: per-user runtime dir).
+    #[arg(long)]
+    socket: Option<PathBuf>,
+
+    /// Additionally listen on TCP for remote control (`host:port`).
+    #[arg(long)]
+    tcp: Option<String>,
+
+    /// Auth token required from TCP clients.
+    #[arg(long, env = "BUNCTL_TOKEN")]
+    token: Option<String>,
+
+    /// Log directory (default: per-user state dir).
+    #[arg(long)]
+    log_dir: Option<PathBuf>,
+
+    /// Metrics directory (default: per-user state dir).
+    #[arg(long)]
+    metrics_dir: Option<PathBuf>,
+
+    /// PID record directory (default: per-user state dir).
+    #[arg(long)]
+    pid_dir: Option<PathBuf>,
+
+    /// Per-peer connection limit per minute (0 = unlimited).
+    #[arg(long, default_value_t = 600)]
+    max_connections_per_min: u32,
+
+    /// Per-peer request limit per minute (0 = unlimited).
+    #[arg(long, default_value_t = 6000)]
+    max_requests_per_min: u32,
+
+    /// Concurrent connection limit across all peers (0 = unlimited).
+    #[arg(long, default_value_t = 64)]
+    max_clients: usize,
+
+    /// Env keys containing one of these (case-insensitive) are redacted in
+    /// status output.
+    #[arg(long, value_delimiter = ',', default_value = "PASSWORD,SECRET,TOKEN,KEY,PRIVATE")]
+    redact_env: Vec<String>,
+
+    /// Reject app names that would be changed by sanitization (e.g.
+    /// spaces or uppercase) instead of silently normalizing them.
+    #[arg(long)]
+    strict_names: bool,
+
+    /// JSON file of webhook sinks to POST selected events to (see
+    /// `bunctl_daemon::webhook::WebhookConfig`).
+    #[arg(long)]
+    webhooks: Option<PathBuf>,
+}
+
+/// Rotate `daemon.log` once it grows past this (same mechanism as an app's
+/// `log_max_size`).
+const DAEMON_LOG_MAX_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Adapts a shared [`bunctl_logging::LogWriter`] to the `io::Write` the
+/// tracing subscriber wants; each formatted event arrives as whole lines.
+struct SelfLogWriter(std::sync::Arc<std::sync::Mutex<bunctl_logging::LogWriter>>);
+
+impl std::io::Write for SelfLogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut writer = self.0.lock().expect("daemon log writer poisoned");
+        for line in String::from_utf8_lossy(buf).lines() {
+            if !line.is_empty() {
+                let _ = writer.write_line(bunctl_core::LogStream::Stdout, line);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        let mut writer = self.0.lock().expect("daemon log writer poisoned");
+        writer.flush().map_err(std::io::Error::other)
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+    let log_dir = args.log_dir.unwrap_or_else(bunctl_logging::default_log_dir);
+
+    let logs = match LogManager::new(log_dir) {
+        Ok(logs) => logs,
+        Err(err) => {
+            eprintln!("cannot initialize log directory: {err}");
+            std::process::exit(1);
+        }
+    };
+    // The daemon's own tracing output goes through the same writer as app
+    // logs (reserved name `daemon`, so `bunctl logs --daemon` finds it):
+    // timestamp prefixes, multiline grouping and size-based rotation
+    // included. The writer adds the timestamp, so fmt's own is disabled.
+    let self_log = match logs.writer(&bunctl_core::AppId::new("daemon")) {
+        Ok(mut writer) => {
+            writer.set_rotation(Some(DAEMON_LOG_MAX_SIZE), false);
+            writer.set_multiline(true, None);
+            std::sync::Arc::new(std::sync::Mutex::new(writer))
+        }
+        Err(err) => {
+            eprintln!("cannot open daemon log: {err}");
+            std::process::exit(1);
+        }
+    };
+    tracing_subscriber::fmt()
+        .with_writer(move || SelfLogWriter(self_log.clone()))
+        .without_time()
+        .with_ansi(false)
+        .init();
+
+    // Panics in spawned tasks are caught and the task restarted (see
+    // `Daemon::supervised`); this hook makes sure the backtrace still lands
+    // in the daemon log at the moment of the panic.
+    std::panic::set_hook(Box::new(|info| {
+        let backtrace = std::backtrace::Backtrace::force_capture();
+        tracing::error!("panic: {info}\n{backtrace}");
+    }));
+    let metrics_dir = args.metrics_dir.unwrap_or_else(bunctl_metrics::default_metrics_dir);
+    let metrics = match MetricsStore::new(metrics_dir) {
+        Ok(metrics) => metrics,
+        Err(err) => {
+            eprintln!("cannot initialize metrics directory: {err}");
+            std::process::exit(1);
+        }
+    };
+    let pid_dir = args.pid_dir.unwrap_or_else(bunctl_daemon::pidfile::default_pid_dir);
+    let pids = match PidRegistry::new(pid_dir) {
+        Ok(pids) => pids,
+        Err(err) => {
+            eprintln!("cannot initialize pid directory: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    bunctl_supervisor::become_subreaper();
+    let daemon = Daemon::new(logs, metrics, pids, args.redact_env.clone(), args.strict_names);
+    daemon.adopt_orphans().await;
+    tokio::spawn(daemon.supervised("sampler", Daemon::run_sampler));
+    tokio::spawn(daemon.supervised("health", Daemon::run_health));
+    tokio::spawn(daemon.supervised("reaper", Daemon::run_reaper));
+    tokio::spawn(daemon.supervised("scheduler", Daemon::run_scheduler));
+    tokio::spawn(daemon.supervised("on-demand", bunctl_daemon::ondemand::run));
+    tokio::spawn(daemon.supervised("self-monitor", Daemon::run_self_monitor));
+    if let Some(path) = &args.webhooks {
+        let sinks = match bunctl_daemon::webhook::load(path) {
+            Ok(sinks) => sinks,
+            Err(err) => {
+                eprintln!("cannot load webhooks {}: {err}", path.display());
+                std::process::exit(1);
+            }
+        };
+        for sink in sinks {
+            tokio::spawn(daemon.supervised("webhook", move |daemon| {
+                bunctl_daemon::webhook::run_sink(daemon, sink.clone())
+            }));
+        }
+    }
+
+    let rate_limit = bunctl_ipc::RateLimit {
+        connections_per_min: args.max_connections_per_min,
+        requests_per_min: args.max_requests_per_min,
+    };
+    let socket = args.socket.unwrap_or_else(bunctl_ipc::socket_path::default_socket_path);
+    let ipc = match IpcServer::bind(&socket) {
+        Ok(server) => std::sync::Arc::new(server.with_rate_limit(rate_limit)),
+        Err(err) => {
+            eprintln!("cannot bind control socket {}: {err}", socket.display());
+            std::process::exit(1);
+        }
+    };
+    tracing::info!("listening on {}", socket.display());
+    bunctl_daemon::watchdog::notify("READY=1");
+
+    if let Some(addr) = &args.tcp {
+        match IpcServer::bind_tcp(addr, args.token.clone()).await {
+            Ok(tcp) => {
+                let tcp = std::sync::Arc::new(tcp.with_rate_limit(rate_limit));
+                tracing::info!("listening on tcp {addr}");
+                let max_clients = args.max_clients;
+                tokio::spawn(daemon.supervised("tcp-accept", move |daemon| {
+                    server::run(daemon, tcp.clone(), max_clients)
+                }));
+            }
+            Err(err) => {
+                eprintln!("cannot bind tcp listener {addr}: {err}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let max_clients = args.max_clients;
+    let accept =
+        daemon.supervised("ipc-accept", move |daemon| server::run(daemon, ipc.clone(), max_clients));
+    tokio::select! {
+        _ = accept => {}
+        _ = shutdown_signal() => {
+            tracing::info!("shutdown signal received");
+            bunctl_daemon::watchdog::notify("STOPPING=1");
+            daemon.shutdown().await;
+        }
+    }
+}
+
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut term = signal(SignalKind::terminate()).expect("install SIGTERM handler");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = term.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
@@ -0,0 +1,65 @@
+//! Loopback port forwarding into apps running in a private network
+//! namespace (`private_network` with `forward_ports`).
+//!
+//! The daemon listens on the host's `127.0.0.1` and splices each
+//! connection through to the same port inside the app's namespace, so an
+//! isolated internal tool stays reachable from the local machine — and
+//! from nowhere else.
+
+use bunctl_core::AppId;
+
+/// Spawn one forwarder per port for the process generation `pid`. The
+/// supervision loop aborts the returned handles when the process exits;
+/// the next generation gets fresh ones with the new PID.
+pub(crate) fn spawn_forwards(
+    id: &AppId,
+    pid: u32,
+    ports: &[u16],
+) -> Vec<tokio::task::JoinHandle<()>> {
+    ports
+        .iter()
+        .map(|&port| {
+            let id = id.clone();
+            tokio::spawn(async move { forward(id, pid, port).await })
+        })
+        .collect()
+}
+
+async fn forward(id: AppId, pid: u32, port: u16) {
+    let listener = match tokio::net::TcpListener::bind(("127.0.0.1", port)).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            tracing::warn!(app = %id, "cannot forward port {port}: {err}");
+            return;
+        }
+    };
+    loop {
+        let Ok((mut client, _)) = listener.accept().await else { break };
+        let id = id.clone();
+        tokio::spawn(async move {
+            // The upstream socket must be created inside the app's
+            // namespace, which means joining it on a thread of our own.
+            let upstream = tokio::task::spawn_blocking(move || {
+                bunctl_supervisor::connect_in_namespace(pid, port)
+            })
+            .await;
+            let upstream = match upstream {
+                Ok(Ok(upstream)) => upstream,
+                Ok(Err(err)) => {
+                    tracing::debug!(app = %id, "forward {port}: {err}");
+                    return;
+                }
+                Err(_) => return,
+            };
+            let converted = upstream
+                .set_nonblocking(true)
+                .and_then(|()| tokio::net::TcpStream::from_std(upstream));
+            match converted {
+                Ok(mut upstream) => {
+                    let _ = tokio::io::copy_bidirectional(&mut client, &mut upstream).await;
+                }
+                Err(err) => tracing::debug!(app = %id, "forward {port}: {err}"),
+            }
+        });
+    }
+}
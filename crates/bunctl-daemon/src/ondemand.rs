@@ -0,0 +1,143 @@
+//! On-demand mode: socket-activation-style lifecycle for dev environments.
+//!
+//! While an `on_demand` app is down the daemon listens on the app's public
+//! address; the first incoming connection starts the app, traffic is
+//! proxied to the app's own port, and once no connection has been open for
+//! the idle timeout the app is stopped again. From the outside the port is
+//! simply always there.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use bunctl_core::config::OnDemand;
+use bunctl_core::{AppId, AppState};
+
+use crate::daemon::Daemon;
+
+/// How long a wake-up waits for the app's port before giving up on the
+/// connection that triggered it.
+const WAKE_TIMEOUT_SECS: u64 = 30;
+
+/// Spawn a holder task for every on-demand app that lacks one; spawned
+/// once at daemon startup (supervised as "on-demand"). Apps gaining or
+/// losing the mode at runtime are picked up within a scan interval.
+pub async fn run(daemon: Arc<Daemon>) {
+    loop {
+        for (id, config) in daemon.unheld_on_demand().await {
+            let daemon = daemon.clone();
+            tokio::spawn(async move { hold(daemon, id, config).await });
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+}
+
+/// One app's holder: owns the public listener for as long as the app has
+/// `on_demand` configured, waking the app for connections and stopping it
+/// again when idle.
+async fn hold(daemon: Arc<Daemon>, id: AppId, config: OnDemand) {
+    let listener = match tokio::net::TcpListener::bind(&config.listen).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            tracing::warn!(app = %id, "cannot hold {}: {err}", config.listen);
+            daemon.end_hold(&id).await;
+            return;
+        }
+    };
+    tracing::info!(app = %id, "holding {}; starts on first connection", config.listen);
+    let active = Arc::new(AtomicU32::new(0));
+    let last_activity = Arc::new(std::sync::Mutex::new(Instant::now()));
+    let mut check = tokio::time::interval(std::time::Duration::from_secs(1));
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let conn = match accepted {
+                    Ok((conn, _)) => conn,
+                    Err(err) => {
+                        tracing::warn!(app = %id, "accept on {} failed: {err}", config.listen);
+                        break;
+                    }
+                };
+                // Wake the app if needed; an unregistered app or one that
+                // never comes up costs the client its connection, nothing
+                // more — the next one tries again.
+                if !daemon.start_held(&id).await || !wait_for_port(&daemon, &id, &config).await {
+                    continue;
+                }
+                proxy(conn, &config.forward, &active, &last_activity);
+            }
+            _ = check.tick() => {
+                if daemon.on_demand_config(&id).await.as_ref() != Some(&config) {
+                    // Deleted, mode turned off or resettled: wind down and
+                    // let the scan spawn a fresh holder if one is wanted.
+                    break;
+                }
+                let idle = active.load(Ordering::SeqCst) == 0
+                    && last_activity.lock().expect("not poisoned").elapsed()
+                        >= config.idle_timeout;
+                if idle && daemon.app_state(&id).await == Some(AppState::Running) {
+                    tracing::info!(
+                        app = %id,
+                        "idle for {}s; stopping until the next connection",
+                        config.idle_timeout.as_secs()
+                    );
+                    if let Err((_, message)) = daemon.stop_app(id.as_str()).await {
+                        tracing::warn!(app = %id, "idle stop failed: {message}");
+                    }
+                }
+                let phase = match daemon.app_state(&id).await {
+                    Some(AppState::Running) => {
+                        format!("proxying {} -> {}", config.listen, config.forward)
+                    }
+                    _ => format!("holding {}; starts on first connection", config.listen),
+                };
+                daemon.set_on_demand_phase(&id, Some(phase)).await;
+            }
+        }
+    }
+    daemon.end_hold(&id).await;
+}
+
+/// Wait for the app's own port to accept connections, giving up when the
+/// app dies on the way up or [`WAKE_TIMEOUT_SECS`] pass.
+async fn wait_for_port(daemon: &Arc<Daemon>, id: &AppId, config: &OnDemand) -> bool {
+    let deadline = Instant::now() + std::time::Duration::from_secs(WAKE_TIMEOUT_SECS);
+    loop {
+        if tokio::net::TcpStream::connect(&config.forward).await.is_ok() {
+            return true;
+        }
+        let alive = matches!(
+            daemon.app_state(id).await,
+            Some(AppState::Starting | AppState::Running)
+        );
+        if !alive || Instant::now() >= deadline {
+            tracing::warn!(app = %id, "woke for a connection but {} never opened", config.forward);
+            return false;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+    }
+}
+
+/// Splice one client connection through to the app, keeping the counters
+/// the idle check reads.
+fn proxy(
+    mut client: tokio::net::TcpStream,
+    forward: &str,
+    active: &Arc<AtomicU32>,
+    last_activity: &Arc<std::sync::Mutex<Instant>>,
+) {
+    let forward = forward.to_owned();
+    let active = active.clone();
+    let last_activity = last_activity.clone();
+    active.fetch_add(1, Ordering::SeqCst);
+    tokio::spawn(async move {
+        match tokio::net::TcpStream::connect(&forward).await {
+            Ok(mut upstream) => {
+                let _ = tokio::io::copy_bidirectional(&mut client, &mut upstream).await;
+            }
+            Err(err) => tracing::debug!("on-demand connect {forward}: {err}"),
+        }
+        active.fetch_sub(1, Ordering::SeqCst);
+        *last_activity.lock().expect("not poisoned") = Instant::now();
+    });
+}
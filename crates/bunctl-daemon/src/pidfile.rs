@@ -0,0 +1,125 @@
+//! On-disk PID records, so a restarted daemon can find and re-adopt the
+//! processes its previous incarnation spawned.
+
+use std::path::PathBuf;
+
+use bunctl_core::{AppConfig, AppId};
+use serde::{Deserialize, Serialize};
+
+/// What the daemon remembers about a spawned process, one JSON file per app
+/// under the pid directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PidRecord {
+    pub pid: u32,
+    /// Unix timestamp of the spawn, for staleness checks.
+    pub started_unix: u64,
+    /// Observed command line at spawn time; adoption verifies the PID still
+    /// runs the same command before re-attaching.
+    pub command: String,
+    /// Full config, so supervision can resume without the original request.
+    pub config: AppConfig,
+}
+
+/// Owns the pid directory and the per-app record files.
+#[derive(Debug, Clone)]
+pub struct PidRegistry {
+    dir: PathBuf,
+}
+
+impl PidRegistry {
+    pub fn new(dir: PathBuf) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, id: &AppId) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+
+    pub fn write(&self, id: &AppId, record: &PidRecord) {
+        let path = self.path(id);
+        match serde_json::to_vec_pretty(record) {
+            Ok(data) => {
+                if let Err(err) = std::fs::write(&path, data) {
+                    tracing::warn!(app = %id, "cannot write pid record: {err}");
+                }
+            }
+            Err(err) => tracing::warn!(app = %id, "cannot serialize pid record: {err}"),
+        }
+    }
+
+    pub fn remove(&self, id: &AppId) {
+        let path = self.path(id);
+        if let Err(err) = std::fs::remove_file(&path) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!(app = %id, "cannot remove pid record: {err}");
+            }
+        }
+    }
+
+    /// All records on disk, in no particular order; unreadable files are
+    /// skipped with a warning.
+    pub fn load_all(&self) -> Vec<(AppId, PidRecord)> {
+        let mut records = Vec::new();
+        let Ok(entries) = std::fs::read_dir(&self.dir) else { return records };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|e| e != "json") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else { continue };
+            match std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|data| serde_json::from_str(&data).map_err(|e| e.to_string()))
+            {
+                Ok(record) => records.push((AppId::new(name), record)),
+                Err(err) => {
+                    tracing::warn!("skipping unreadable pid record {}: {err}", path.display());
+                }
+            }
+        }
+        records
+    }
+}
+
+/// Per-user default pid directory, next to the log directory. A root
+/// daemon is the system instance and keeps its records under /var.
+pub fn default_pid_dir() -> PathBuf {
+    #[cfg(unix)]
+    {
+        if is_root() {
+            return PathBuf::from("/var/lib/bunctl/pids");
+        }
+    }
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(dir) = std::env::var_os("XDG_STATE_HOME").filter(|d| !d.is_empty()) {
+            return PathBuf::from(dir).join("bunctl").join("pids");
+        }
+        home().join(".local/state/bunctl/pids")
+    }
+    #[cfg(target_os = "macos")]
+    {
+        home().join("Library/Application Support/bunctl/pids")
+    }
+    #[cfg(windows)]
+    {
+        match std::env::var_os("LOCALAPPDATA") {
+            Some(dir) => PathBuf::from(dir).join("bunctl").join("pids"),
+            None => PathBuf::from(r"C:\bunctl\pids"),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn home() -> PathBuf {
+    std::env::var_os("HOME").map(PathBuf::from).unwrap_or_else(|| PathBuf::from("/tmp"))
+}
+
+#[cfg(unix)]
+fn is_root() -> bool {
+    extern "C" {
+        fn geteuid() -> u32;
+    }
+    unsafe { geteuid() == 0 }
+}
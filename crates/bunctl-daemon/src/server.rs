@@ -0,0 +1,361 @@
+//! IPC accept loop and per-connection request dispatch.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use bunctl_core::{AppId, DaemonEvent};
+use bunctl_ipc::message::{
+    ClientInfo, ErrorCode, EventFilter, IpcRequest, IpcResponse, SubscriptionType,
+};
+use bunctl_ipc::{IpcConnection, IpcError, IpcServer};
+
+use crate::daemon::Daemon;
+
+/// Initial pause after a failed accept; doubled up to [`ACCEPT_BACKOFF_MAX`].
+const ACCEPT_BACKOFF_START: Duration = Duration::from_millis(100);
+/// Longest pause between accept retries.
+const ACCEPT_BACKOFF_MAX: Duration = Duration::from_secs(5);
+
+/// Accept connections until shutdown is requested.
+///
+/// Accept errors are never fatal: the listener outlives transient failures
+/// (EMFILE, ECONNABORTED, ...) by backing off and retrying. At most
+/// `max_clients` connections are served concurrently (0 = unlimited);
+/// connections beyond the limit are turned away with a `RateLimited` error.
+pub async fn run(daemon: Arc<Daemon>, server: Arc<IpcServer>, max_clients: usize) {
+    let active = Arc::new(AtomicUsize::new(0));
+    let mut backoff = ACCEPT_BACKOFF_START;
+    loop {
+        let mut conn = match server.accept().await {
+            Ok(conn) => {
+                backoff = ACCEPT_BACKOFF_START;
+                conn
+            }
+            Err(err) => {
+                tracing::warn!("accept failed: {err}; retrying in {backoff:?}");
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(ACCEPT_BACKOFF_MAX);
+                continue;
+            }
+        };
+        let current = active.load(Ordering::Relaxed);
+        if max_clients != 0 && current >= max_clients {
+            tracing::warn!("rejecting {}: {current} connections already open", conn.peer());
+            daemon.emit(None, DaemonEvent::ConnectionLimitReached { active: current as u32 });
+            let resp = IpcResponse::Error {
+                code: ErrorCode::RateLimited,
+                message: format!("too many concurrent connections (limit {max_clients})"),
+            };
+            let _ = conn.write_response(&resp).await;
+            continue;
+        }
+        active.fetch_add(1, Ordering::Relaxed);
+        let daemon = daemon.clone();
+        let active = active.clone();
+        tokio::spawn(async move {
+            handle_connection(daemon, conn).await;
+            active.fetch_sub(1, Ordering::Relaxed);
+        });
+    }
+}
+
+/// Monotonic id per accepted connection, keying the client registry.
+static NEXT_CONN_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+async fn handle_connection(daemon: Arc<Daemon>, conn: IpcConnection) {
+    let conn_id = NEXT_CONN_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    serve_requests(&daemon, conn, conn_id).await;
+    daemon.unregister_client(conn_id).await;
+}
+
+async fn serve_requests(daemon: &Arc<Daemon>, mut conn: IpcConnection, conn_id: u64) {
+    // Audit identity: the transport peer, enriched once the client says
+    // hello.
+    let mut peer_label = conn.peer().to_string();
+    loop {
+        let req = match conn.read_request().await {
+            Ok(req) => req,
+            Err(IpcError::ConnectionClosed) => return,
+            Err(err) => {
+                tracing::debug!("connection error: {err}");
+                return;
+            }
+        };
+
+        if let IpcRequest::Hello { pid, user, version } = req {
+            // Prefer the transport-verified PID over the self-reported one.
+            let pid = conn.peer_pid().unwrap_or(pid);
+            peer_label = format!("{user}({})", conn.peer());
+            daemon
+                .register_client(
+                    conn_id,
+                    ClientInfo {
+                        peer: conn.peer().to_string(),
+                        pid,
+                        user,
+                        version,
+                        connected_secs: 0,
+                    },
+                )
+                .await;
+            if conn.write_response(&IpcResponse::Success { message: None }).await.is_err() {
+                return;
+            }
+            continue;
+        }
+
+        if let IpcRequest::Subscribe { subscription, app, filter } = req {
+            serve_subscription(daemon.clone(), conn, subscription, app, filter).await;
+            return;
+        }
+
+        if let IpcRequest::Batch { requests } = req {
+            let mut responses = Vec::with_capacity(requests.len());
+            for sub in requests {
+                responses.push(dispatch_batched(daemon, sub, &peer_label).await);
+            }
+            if conn.write_response(&IpcResponse::Batch { responses }).await.is_err() {
+                return;
+            }
+            continue;
+        }
+
+        let shutdown = matches!(req, IpcRequest::Shutdown);
+        let audited = audit_info(&req);
+        let resp = dispatch(daemon, req).await;
+        if let Some((action, app)) = audited {
+            let detail = match &resp {
+                IpcResponse::Error { message, .. } => Some(message.clone()),
+                _ => None,
+            };
+            daemon.record_audit(&peer_label, action, app, detail.is_none(), detail);
+        }
+        if conn.write_response(&resp).await.is_err() {
+            return;
+        }
+        if shutdown {
+            daemon.shutdown().await;
+            std::process::exit(0);
+        }
+    }
+}
+
+/// Dispatch one entry of a batch, auditing it like a standalone request.
+/// Connection-level requests make no sense mid-batch and are rejected.
+async fn dispatch_batched(daemon: &Arc<Daemon>, sub: IpcRequest, peer_label: &str) -> IpcResponse {
+    match sub {
+        IpcRequest::Batch { .. }
+        | IpcRequest::Auth { .. }
+        | IpcRequest::Hello { .. }
+        | IpcRequest::Subscribe { .. }
+        | IpcRequest::Shutdown => IpcResponse::Error {
+            code: ErrorCode::InvalidRequest,
+            message: "request type not allowed inside a batch".into(),
+        },
+        sub => {
+            let audited = audit_info(&sub);
+            let resp = dispatch(daemon, sub).await;
+            if let Some((action, app)) = audited {
+                let detail = match &resp {
+                    IpcResponse::Error { message, .. } => Some(message.clone()),
+                    _ => None,
+                };
+                daemon.record_audit(peer_label, action, app, detail.is_none(), detail);
+            }
+            resp
+        }
+    }
+}
+
+/// Action name and target app for state-changing requests; read-only
+/// requests are not audited.
+fn audit_info(req: &IpcRequest) -> Option<(&'static str, Option<String>)> {
+    match req {
+        IpcRequest::Start { config, .. } => Some(("start", Some(config.name.clone()))),
+        IpcRequest::Adopt { name, .. } => Some(("adopt", Some(name.clone()))),
+        IpcRequest::BlueGreen { config } => Some(("swap", Some(config.name.clone()))),
+        IpcRequest::Stop { name } => Some(("stop", Some(name.clone()))),
+        IpcRequest::Restart { name, .. } => Some(("restart", Some(name.clone()))),
+        IpcRequest::Delete { name, .. } => Some(("delete", Some(name.clone()))),
+        IpcRequest::Rename { old, .. } => Some(("rename", Some(old.clone()))),
+        IpcRequest::Import { .. } => Some(("import", None)),
+        IpcRequest::Shutdown => Some(("shutdown", None)),
+        _ => None,
+    }
+}
+
+async fn dispatch(daemon: &Arc<Daemon>, req: IpcRequest) -> IpcResponse {
+    let result = match req {
+        IpcRequest::Start { config, force } => daemon.start_app(*config, force).await,
+        IpcRequest::Adopt { name, pid } => daemon.adopt_pid(&name, pid).await,
+        IpcRequest::BlueGreen { config } => daemon.blue_green(*config).await,
+        IpcRequest::Stop { name } => daemon.stop_app(&name).await,
+        IpcRequest::Restart { name, config } => {
+            daemon.restart_app(&name, config.map(|c| *c)).await
+        }
+        IpcRequest::Delete { name, purge_logs } => daemon.delete_app(&name, purge_logs).await,
+        IpcRequest::Rename { old, new } => daemon.rename_app(&old, &new).await,
+        // The reserved name "daemon" reports the daemon's own usage.
+        IpcRequest::Status { name: Some(name) } if name == "daemon" => {
+            return IpcResponse::Status(Box::new(daemon.self_status()));
+        }
+        IpcRequest::Status { name: Some(name) } => {
+            return match daemon.app_status(&name).await {
+                Ok(status) => IpcResponse::Status(Box::new(status)),
+                Err((code, message)) => IpcResponse::Error { code, message },
+            };
+        }
+        IpcRequest::Status { name: None } => {
+            return IpcResponse::StatusList { statuses: daemon.all_statuses().await };
+        }
+        IpcRequest::List { all } => {
+            let mut statuses = daemon.all_statuses().await;
+            if all {
+                // Orphan logs: log files on disk for apps no longer managed.
+                if let Ok(logged) = daemon.log_manager().list_logs() {
+                    for name in logged {
+                        // "daemon" is the daemon's own log, not an app.
+                        if name == "daemon" {
+                            continue;
+                        }
+                        if !daemon.is_managed(&AppId::new(&name)).await {
+                            statuses.push(Daemon::orphan_status(&name));
+                        }
+                    }
+                }
+            }
+            return IpcResponse::StatusList { statuses };
+        }
+        IpcRequest::Logs { name, lines, include_stopped, grep } => {
+            return match daemon.read_logs(&name, lines, include_stopped, grep.as_deref()).await {
+                Ok(lines) => IpcResponse::Logs { lines },
+                Err((code, message)) => IpcResponse::Error { code, message },
+            };
+        }
+        IpcRequest::Describe { name } => {
+            return match daemon.describe(&name).await {
+                Ok((status, banner)) => {
+                    IpcResponse::Describe { status: Box::new(status), banner }
+                }
+                Err((code, message)) => IpcResponse::Error { code, message },
+            };
+        }
+        IpcRequest::Health { name } => {
+            return match daemon.health_history(&name).await {
+                Ok(records) => IpcResponse::Health { records },
+                Err((code, message)) => IpcResponse::Error { code, message },
+            };
+        }
+        IpcRequest::ProcessTree { name } => {
+            return match daemon.process_tree(&name).await {
+                Ok((procs, clock_hz)) => IpcResponse::ProcessTree { procs, clock_hz },
+                Err((code, message)) => IpcResponse::Error { code, message },
+            };
+        }
+        IpcRequest::Metrics { name, since_secs } => {
+            return match daemon.query_metrics(&name, since_secs).await {
+                Ok(samples) => IpcResponse::Metrics { samples },
+                Err((code, message)) => IpcResponse::Error { code, message },
+            };
+        }
+        IpcRequest::GetConfig { name } => {
+            return match daemon.app_config(&name).await {
+                Ok(config) => IpcResponse::Config { config: Box::new(config) },
+                Err((code, message)) => IpcResponse::Error { code, message },
+            };
+        }
+        IpcRequest::Clients => {
+            return IpcResponse::Clients { clients: daemon.list_clients().await };
+        }
+        IpcRequest::Export => {
+            return IpcResponse::Snapshot { snapshot: Box::new(daemon.export_state().await) };
+        }
+        IpcRequest::Import { snapshot } => daemon.import_state(*snapshot).await,
+        IpcRequest::Audit { since_secs } => {
+            return match daemon.query_audit(since_secs) {
+                Ok(entries) => IpcResponse::Audit { entries },
+                Err((code, message)) => IpcResponse::Error { code, message },
+            };
+        }
+        IpcRequest::Events { since_secs, filter } => {
+            return match daemon.query_events(since_secs, filter.as_ref()) {
+                Ok(events) => IpcResponse::Events { events },
+                Err((code, message)) => IpcResponse::Error { code, message },
+            };
+        }
+        IpcRequest::Ping => Ok(Some("pong".into())),
+        IpcRequest::Shutdown => Ok(Some("shutting down".into())),
+        IpcRequest::Auth { .. } => {
+            // Auth is consumed by the transport; seeing it here means it was
+            // sent on an already-authenticated connection.
+            Err((ErrorCode::InvalidRequest, "unexpected auth request".into()))
+        }
+        IpcRequest::Subscribe { .. } | IpcRequest::Hello { .. } | IpcRequest::Batch { .. } => {
+            unreachable!("handled by caller")
+        }
+    };
+    match result {
+        Ok(message) => IpcResponse::Success { message },
+        Err((code, message)) => IpcResponse::Error { code, message },
+    }
+}
+
+/// Forward matching events to the client until it disconnects.
+async fn serve_subscription(
+    daemon: Arc<Daemon>,
+    mut conn: IpcConnection,
+    subscription: SubscriptionType,
+    app: Option<String>,
+    filter: Option<EventFilter>,
+) {
+    let mut rx = daemon.subscribe_events();
+    if conn
+        .write_response(&IpcResponse::Success { message: None })
+        .await
+        .is_err()
+    {
+        return;
+    }
+    let app_filter = app.map(|a| AppId::new(&a).to_string());
+    loop {
+        let envelope = match rx.recv().await {
+            Ok(envelope) => envelope,
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!("event subscriber lagged; skipped {skipped} events");
+                continue;
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => return,
+        };
+        if !matches_subscription(subscription, &envelope.event) {
+            continue;
+        }
+        if let Some(filter) = &app_filter {
+            if envelope.app.as_deref() != Some(filter.as_str()) {
+                continue;
+            }
+        }
+        if let Some(filter) = &filter {
+            if !filter.matches(envelope.app.as_deref(), &envelope.event) {
+                continue;
+            }
+        }
+        let resp = IpcResponse::Event {
+            seq: envelope.seq,
+            ts: bunctl_core::time::rfc3339(envelope.ts),
+            app: envelope.app,
+            event: envelope.event,
+        };
+        if conn.write_response(&resp).await.is_err() {
+            return;
+        }
+    }
+}
+
+fn matches_subscription(subscription: SubscriptionType, event: &DaemonEvent) -> bool {
+    match subscription {
+        SubscriptionType::All => true,
+        SubscriptionType::Logs => matches!(event, DaemonEvent::LogLine { .. }),
+        SubscriptionType::Status => !matches!(event, DaemonEvent::LogLine { .. }),
+    }
+}
@@ -0,0 +1,62 @@
+//! Minimal sd_notify support, so the daemon can run as a systemd
+//! `Type=notify` service with `WatchdogSec=` protection.
+//!
+//! Only the two messages bunctl needs are sent (`READY=1` at startup and
+//! periodic `WATCHDOG=1` pings); when `NOTIFY_SOCKET` is not set everything
+//! here is a no-op.
+
+use std::time::Duration;
+
+/// Send one state string to the systemd notify socket, if there is one.
+/// Failures are logged and ignored: losing a ping is better than wedging
+/// the daemon over it.
+pub fn notify(state: &str) {
+    #[cfg(unix)]
+    {
+        let Some(socket) = std::env::var_os("NOTIFY_SOCKET") else { return };
+        if let Err(err) = send(&socket, state) {
+            tracing::warn!("cannot notify systemd: {err}");
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = state;
+}
+
+/// How often to send `WATCHDOG=1`, when systemd armed a watchdog for us:
+/// half the `WATCHDOG_USEC` budget, per the sd_watchdog recommendation.
+/// `None` when no watchdog is configured.
+pub fn ping_interval() -> Option<Duration> {
+    // WATCHDOG_PID guards against an inherited variable meant for a parent.
+    if let Ok(pid) = std::env::var("WATCHDOG_PID") {
+        if pid != std::process::id().to_string() {
+            return None;
+        }
+    }
+    let usec: u64 = std::env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+    Some(Duration::from_micros(usec / 2))
+}
+
+#[cfg(unix)]
+fn send(socket: &std::ffi::OsStr, state: &str) -> std::io::Result<()> {
+    use std::os::unix::net::UnixDatagram;
+
+    let sock = UnixDatagram::unbound()?;
+    let bytes = socket.as_encoded_bytes();
+    if let Some(name) = bytes.strip_prefix(b"@") {
+        // Abstract-namespace socket (the usual systemd layout on Linux).
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::linux::net::SocketAddrExt;
+            let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+            sock.send_to_addr(state.as_bytes(), &addr)?;
+            return Ok(());
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = name;
+            return Err(std::io::Error::other("abstract notify sockets need Linux"));
+        }
+    }
+    sock.send_to(state.as_bytes(), socket)?;
+    Ok(())
+}
@@ -0,0 +1,220 @@
+//! Webhook event forwarding: POST selected events to configured URLs,
+//! batched and HMAC-signed, so chat integrations and dashboards need no
+//! separate watcher process holding a subscription open.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bunctl_core::backoff::BackoffStrategy;
+use bunctl_core::{DaemonEvent, EventRecord};
+use bunctl_ipc::message::EventFilter;
+use serde::Deserialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::daemon::{Daemon, EventEnvelope};
+
+/// Delivery attempts per batch before it is dropped with a warning.
+const MAX_ATTEMPTS: u32 = 5;
+/// First retry delay; doubles up to [`RETRY_MAX`].
+const RETRY_BASE: Duration = Duration::from_secs(1);
+/// Longest pause between delivery retries.
+const RETRY_MAX: Duration = Duration::from_secs(30);
+
+/// One configured webhook sink; the `--webhooks` file is a JSON array of
+/// these.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookConfig {
+    /// Where batches are POSTed. Only plain `http://` is supported, like
+    /// health-check URLs; terminate TLS in front of the daemon if needed.
+    pub url: String,
+    /// HMAC-SHA256 key; when set, each request carries an
+    /// `X-Bunctl-Signature: sha256=<hex>` header over the exact body, so
+    /// the receiver can verify who sent it.
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Which events to forward, using the same filter subscriptions use.
+    /// Log lines are only forwarded when `event_types` names `log_line`
+    /// explicitly — an unfiltered sink would otherwise relay every line
+    /// of app output.
+    #[serde(default)]
+    pub filter: Option<EventFilter>,
+    /// Events per POST; a full batch is flushed immediately.
+    #[serde(default = "default_batch_max")]
+    pub batch_max: usize,
+    /// How long a partial batch may sit before it is flushed anyway.
+    #[serde(default = "default_batch_wait_ms")]
+    pub batch_wait_ms: u64,
+}
+
+fn default_batch_max() -> usize {
+    20
+}
+
+fn default_batch_wait_ms() -> u64 {
+    1000
+}
+
+/// Parse the `--webhooks` file: a JSON array of [`WebhookConfig`].
+pub fn load(path: &Path) -> Result<Vec<WebhookConfig>, String> {
+    let data = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    serde_json::from_str(&data).map_err(|err| err.to_string())
+}
+
+/// Forward matching events to one sink until the daemon shuts down.
+pub async fn run_sink(daemon: Arc<Daemon>, config: WebhookConfig) {
+    let mut rx = daemon.subscribe_events();
+    let mut batch: Vec<EventRecord> = Vec::new();
+    let mut deadline: Option<tokio::time::Instant> = None;
+    loop {
+        let received = match deadline {
+            // An elapsed timeout means the oldest buffered event waited
+            // long enough.
+            Some(at) => tokio::time::timeout_at(at, rx.recv()).await.ok(),
+            None => Some(rx.recv().await),
+        };
+        match received {
+            Some(Ok(envelope)) if wants(&config, &envelope) => {
+                if batch.is_empty() {
+                    deadline = Some(
+                        tokio::time::Instant::now() + Duration::from_millis(config.batch_wait_ms),
+                    );
+                }
+                batch.push(record(envelope));
+            }
+            Some(Ok(_)) => {}
+            Some(Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped))) => {
+                tracing::warn!(url = %config.url, "webhook sink lagged; skipped {skipped} events");
+            }
+            Some(Err(tokio::sync::broadcast::error::RecvError::Closed)) => {
+                if !batch.is_empty() {
+                    deliver(&config, &batch).await;
+                }
+                return;
+            }
+            None => {}
+        }
+        let due = deadline.is_some_and(|at| tokio::time::Instant::now() >= at);
+        if !batch.is_empty() && (batch.len() >= config.batch_max || due) {
+            deliver(&config, &batch).await;
+            batch.clear();
+            deadline = None;
+        }
+    }
+}
+
+/// Whether this sink forwards the event.
+fn wants(config: &WebhookConfig, envelope: &EventEnvelope) -> bool {
+    if matches!(envelope.event, DaemonEvent::LogLine { .. })
+        && !config
+            .filter
+            .as_ref()
+            .is_some_and(|f| f.event_types.iter().any(|t| t == "log_line"))
+    {
+        return false;
+    }
+    match &config.filter {
+        Some(filter) => filter.matches(envelope.app.as_deref(), &envelope.event),
+        None => true,
+    }
+}
+
+fn record(envelope: EventEnvelope) -> EventRecord {
+    EventRecord {
+        ts: envelope.ts,
+        seq: envelope.seq,
+        app: envelope.app,
+        event: envelope.event,
+    }
+}
+
+/// POST one batch, retrying with backoff; a batch that still fails after
+/// [`MAX_ATTEMPTS`] is dropped — a dead receiver must not buffer events
+/// without bound.
+async fn deliver(config: &WebhookConfig, batch: &[EventRecord]) {
+    let body = match serde_json::to_vec(batch) {
+        Ok(body) => body,
+        Err(err) => {
+            tracing::warn!(url = %config.url, "cannot serialize webhook batch: {err}");
+            return;
+        }
+    };
+    let mut backoff = BackoffStrategy::new(RETRY_BASE, RETRY_MAX);
+    for attempt in 1..=MAX_ATTEMPTS {
+        match post(&config.url, config.secret.as_deref(), &body).await {
+            Ok(()) => return,
+            Err(err) => {
+                tracing::warn!(url = %config.url, "webhook delivery attempt {attempt} failed: {err}");
+                if attempt < MAX_ATTEMPTS {
+                    tokio::time::sleep(backoff.next_delay()).await;
+                }
+            }
+        }
+    }
+    tracing::warn!(
+        url = %config.url,
+        "dropping batch of {} events after {MAX_ATTEMPTS} attempts",
+        batch.len()
+    );
+}
+
+/// Minimal HTTP/1.1 POST expecting a 2xx status line, in the same spirit
+/// as the http health probe: plain `http://` only, `Connection: close`.
+async fn post(url: &str, secret: Option<&str>, body: &[u8]) -> Result<(), String> {
+    let Some(rest) = url.strip_prefix("http://") else {
+        return Err(format!("unsupported url (only http:// is): {url}"));
+    };
+    let (host_port, path) = match rest.find('/') {
+        Some(idx) => (&rest[..idx], &rest[idx..]),
+        None => (rest, "/"),
+    };
+    let addr = if host_port.contains(':') {
+        host_port.to_string()
+    } else {
+        format!("{host_port}:80")
+    };
+    let mut stream = tokio::net::TcpStream::connect(&addr)
+        .await
+        .map_err(|err| format!("connect {addr}: {err}"))?;
+    let mut request = format!(
+        "POST {path} HTTP/1.1\r\nHost: {host_port}\r\nConnection: close\r\n\
+         Content-Type: application/json\r\nContent-Length: {}\r\n",
+        body.len()
+    );
+    if let Some(secret) = secret {
+        request.push_str(&format!("X-Bunctl-Signature: sha256={}\r\n", signature(secret, body)));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await.map_err(|err| format!("send: {err}"))?;
+    stream.write_all(body).await.map_err(|err| format!("send: {err}"))?;
+    let mut response = Vec::with_capacity(256);
+    let mut chunk = [0u8; 1024];
+    loop {
+        let n = stream.read(&mut chunk).await.map_err(|err| format!("read: {err}"))?;
+        if n == 0 {
+            break;
+        }
+        response.extend_from_slice(&chunk[..n]);
+        // The status line is all we need.
+        if response.windows(2).any(|w| w == b"\r\n") {
+            break;
+        }
+    }
+    let text = String::from_utf8_lossy(&response);
+    let code = text.split_whitespace().nth(1).unwrap_or("");
+    if !code.starts_with('2') {
+        return Err(format!("status {}", if code.is_empty() { "unreadable" } else { code }));
+    }
+    Ok(())
+}
+
+/// Hex HMAC-SHA256 of the request body.
+fn signature(secret: &str, body: &[u8]) -> String {
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret.as_bytes());
+    let tag = ring::hmac::sign(&key, body);
+    let mut out = String::with_capacity(64);
+    for byte in tag.as_ref() {
+        out.push_str(&format!("{byte:02x}"));
+    }
+    out
+}
@@ -0,0 +1,108 @@
+//! Length-prefixed JSON framing shared by every transport.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{IpcError, MAX_MESSAGE_SIZE};
+
+/// Write one framed message: little-endian `u32` length followed by the JSON
+/// encoding of `msg`.
+pub async fn write_message<W, T>(writer: &mut W, msg: &T) -> Result<(), IpcError>
+where
+    W: AsyncWrite + Unpin,
+    T: Serialize,
+{
+    let payload = serde_json::to_vec(msg)?;
+    if payload.len() > MAX_MESSAGE_SIZE {
+        return Err(IpcError::MessageTooLarge {
+            size: payload.len(),
+            limit: MAX_MESSAGE_SIZE,
+        });
+    }
+    writer.write_all(&(payload.len() as u32).to_le_bytes()).await?;
+    writer.write_all(&payload).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Read one framed message. Returns [`IpcError::ConnectionClosed`] on a clean
+/// EOF at a frame boundary.
+pub async fn read_message<R, T>(reader: &mut R) -> Result<T, IpcError>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    let mut len_buf = [0u8; 4];
+    match reader.read_exact(&mut len_buf).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+            return Err(IpcError::ConnectionClosed)
+        }
+        Err(e) => return Err(e.into()),
+    }
+    let len = u32::from_le_bytes(len_buf) as usize;
+    if len > MAX_MESSAGE_SIZE {
+        // Drain the oversized payload so the stream stays at a frame
+        // boundary; the caller can then report the error and keep reading.
+        let mut sink = tokio::io::sink();
+        tokio::io::copy(&mut (&mut *reader).take(len as u64), &mut sink).await?;
+        return Err(IpcError::MessageTooLarge {
+            size: len,
+            limit: MAX_MESSAGE_SIZE,
+        });
+    }
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload).await?;
+    Ok(serde_json::from_slice(&payload)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::{IpcRequest, IpcResponse};
+
+    #[tokio::test]
+    async fn round_trips_requests() {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        write_message(&mut client, &IpcRequest::List { all: false }).await.unwrap();
+        let req: IpcRequest = read_message(&mut server).await.unwrap();
+        assert!(matches!(req, IpcRequest::List { all: false }));
+    }
+
+    #[tokio::test]
+    async fn reports_closed_connection() {
+        let (client, mut server) = tokio::io::duplex(1024);
+        drop(client);
+        let err = read_message::<_, IpcResponse>(&mut server).await.unwrap_err();
+        assert!(matches!(err, IpcError::ConnectionClosed));
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_frames() {
+        let (mut client, mut server) = tokio::io::duplex(64);
+        tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+            let bogus = (MAX_MESSAGE_SIZE as u32 + 1).to_le_bytes();
+            client.write_all(&bogus).await.unwrap();
+        });
+        let err = read_message::<_, IpcResponse>(&mut server).await.unwrap_err();
+        assert!(matches!(err, IpcError::MessageTooLarge { .. }));
+    }
+
+    #[tokio::test]
+    async fn drains_oversized_frame_and_reads_the_next() {
+        let (mut client, mut server) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+            let oversized = MAX_MESSAGE_SIZE as u32 + 1;
+            client.write_all(&oversized.to_le_bytes()).await.unwrap();
+            client.write_all(&vec![b' '; oversized as usize]).await.unwrap();
+            write_message(&mut client, &IpcRequest::Ping).await.unwrap();
+        });
+        let err = read_message::<_, IpcRequest>(&mut server).await.unwrap_err();
+        assert!(matches!(err, IpcError::MessageTooLarge { .. }));
+        let req: IpcRequest = read_message(&mut server).await.unwrap();
+        assert!(matches!(req, IpcRequest::Ping));
+    }
+}
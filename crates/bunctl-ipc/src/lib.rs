@@ -0,0 +1,61 @@
+//! IPC transport and message types for talking to the bunctl daemon.
+//!
+//! Messages are JSON values framed with a little-endian `u32` length prefix.
+//! On Unix platforms (Linux and macOS) the transport is a Unix domain
+//! socket; see [`socket_path`] for where the socket lives on each platform.
+
+pub mod codec;
+pub mod message;
+mod ratelimit;
+pub mod socket_path;
+#[cfg(feature = "tls")]
+pub mod tls;
+mod transport;
+
+pub use ratelimit::RateLimit;
+pub use transport::{IpcClient, IpcConnection, IpcServer, IpcStream};
+
+use std::time::Duration;
+
+use thiserror::Error;
+
+/// Upper bound on a single framed message. Frames larger than this are
+/// rejected before any allocation happens.
+pub const MAX_MESSAGE_SIZE: usize = 8 * 1024 * 1024;
+
+/// Default timeout for a request/response exchange without a more specific
+/// class (handshakes, subscriptions).
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Timeout for read-only queries (status, list, logs, ...); these should
+/// fail fast when the daemon is wedged.
+pub const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Timeout for lifecycle operations (start, stop, swap, ...), which may
+/// legitimately wait on a slow app to come up or drain.
+pub const CONTROL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Errors produced by the IPC layer.
+#[derive(Debug, Error)]
+pub enum IpcError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("failed to encode or decode message: {0}")]
+    Codec(#[from] serde_json::Error),
+
+    #[error("message of {size} bytes exceeds maximum of {limit} bytes")]
+    MessageTooLarge { size: usize, limit: usize },
+
+    #[error("connection closed by peer")]
+    ConnectionClosed,
+
+    #[error("peer did not authenticate")]
+    Unauthorized,
+
+    #[error("authentication failed: {0}")]
+    AuthFailed(String),
+
+    #[error("timed out after {0:?} waiting for response")]
+    Timeout(Duration),
+}
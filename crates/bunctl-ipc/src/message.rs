@@ -0,0 +1,423 @@
+//! Request and response types exchanged between CLI and daemon.
+
+use bunctl_core::audit::AuditEntry;
+use bunctl_core::metrics::MetricSample;
+use bunctl_core::snapshot::DaemonSnapshot;
+use bunctl_core::{AppConfig, AppStatus, DaemonEvent, EventSeverity};
+use serde::{Deserialize, Serialize};
+
+/// A command sent to the daemon.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IpcRequest {
+    /// Present the shared token; must be the first request on
+    /// token-protected transports (TCP/TLS).
+    Auth { token: String },
+    /// Optional introduction sent right after connecting: the client's PID,
+    /// username and CLI version, shown in audit logs and
+    /// `status --clients`. The PID is only trusted where the transport can
+    /// verify it (SO_PEERCRED).
+    Hello { pid: u32, user: String, version: String },
+    /// Clients currently connected to the daemon.
+    Clients,
+    /// Register (if needed) and start an app. Starting an app that is
+    /// already running is an error unless `force`, which restarts it.
+    Start {
+        config: Box<AppConfig>,
+        #[serde(default)]
+        force: bool,
+    },
+    /// Bring an externally started process under management, deriving the
+    /// app's config from its observed command line.
+    Adopt { name: String, pid: u32 },
+    /// Blue/green swap: start a candidate instance under a temporary name,
+    /// wait for it to come up healthy, then atomically promote it to the
+    /// app's name; the old instance is kept briefly as `<name>-previous`.
+    BlueGreen { config: Box<AppConfig> },
+    /// Stop an app, escalating to a hard kill after its stop timeout.
+    Stop { name: String },
+    /// Stop then start an app. When `config` is present the daemon swaps it
+    /// in before the restart (`restart --update-env`) instead of reusing its
+    /// cached snapshot.
+    Restart {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        config: Option<Box<AppConfig>>,
+    },
+    /// Remove an app from the registry, stopping it first if running.
+    /// `purge_logs` overrides the app's configured default for whether its
+    /// log files and metrics are removed too.
+    Delete {
+        name: String,
+        #[serde(default)]
+        purge_logs: Option<bool>,
+    },
+    /// Rename a stopped app, moving its log files and recorded metrics
+    /// with it; restart counters and history are kept.
+    Rename { old: String, new: String },
+    /// Status of one app, or of all apps when `name` is `None`.
+    Status { name: Option<String> },
+    /// Status of all registered apps; with `all`, orphan log files from
+    /// previously managed apps are listed too.
+    List {
+        #[serde(default)]
+        all: bool,
+    },
+    /// Recent log lines for an app. `include_stopped` reads straight from
+    /// the log directory when the app is no longer managed. `grep` returns
+    /// only entries containing the substring, whole (including any grouped
+    /// continuation lines).
+    Logs {
+        name: String,
+        lines: usize,
+        #[serde(default)]
+        include_stopped: bool,
+        #[serde(default)]
+        grep: Option<String>,
+    },
+    /// Status of one app together with its startup banner: the first lines
+    /// of output captured at the most recent start attempt.
+    Describe { name: String },
+    /// Recorded health check results for an app, oldest first.
+    Health { name: String },
+    /// The app's live process tree: its main process and every descendant
+    /// (`bunctl top`).
+    ProcessTree { name: String },
+    /// Persisted resource samples for an app with timestamps in the last
+    /// `since_secs` seconds.
+    Metrics { name: String, since_secs: u64 },
+    /// The daemon's in-memory config snapshot of an app (`bunctl diff`).
+    GetConfig { name: String },
+    /// Audit entries recorded in the last `since_secs` seconds.
+    Audit { since_secs: u64 },
+    /// Persisted daemon events from the last `since_secs` seconds,
+    /// optionally narrowed by the same filter subscriptions use
+    /// (`bunctl events`).
+    Events {
+        since_secs: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        filter: Option<EventFilter>,
+    },
+    /// Snapshot of the whole app registry (`bunctl export`).
+    Export,
+    /// Replay an exported snapshot into this daemon (`bunctl import`).
+    /// Already-registered apps are left untouched.
+    Import { snapshot: Box<DaemonSnapshot> },
+    /// Subscribe this connection to daemon events; after the `Success`
+    /// acknowledgment the server pushes [`IpcResponse::Event`] messages.
+    Subscribe {
+        subscription: SubscriptionType,
+        /// Restrict to a single app by exact name.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        app: Option<String>,
+        /// Finer-grained restriction applied on top of `subscription`
+        /// and `app`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        filter: Option<EventFilter>,
+    },
+    /// Several requests answered in one round-trip with
+    /// [`IpcResponse::Batch`], in order. Nesting and connection-level
+    /// requests (auth, hello, subscribe, shutdown) are rejected per entry.
+    Batch { requests: Vec<IpcRequest> },
+    /// Liveness probe; the daemon answers with `Success`.
+    Ping,
+    /// Ask the daemon to shut down.
+    Shutdown,
+}
+
+impl IpcRequest {
+    /// Client-side timeout class for this request: lifecycle operations get
+    /// [`crate::CONTROL_TIMEOUT`], read-only queries fail fast with
+    /// [`crate::QUERY_TIMEOUT`], everything else falls back to
+    /// [`crate::DEFAULT_TIMEOUT`]. [`IpcClient::set_timeout`] overrides all
+    /// three.
+    ///
+    /// [`IpcClient::set_timeout`]: crate::IpcClient::set_timeout
+    pub fn default_timeout(&self) -> std::time::Duration {
+        match self {
+            IpcRequest::Start { .. }
+            | IpcRequest::Adopt { .. }
+            | IpcRequest::BlueGreen { .. }
+            | IpcRequest::Stop { .. }
+            | IpcRequest::Restart { .. }
+            | IpcRequest::Delete { .. }
+            | IpcRequest::Rename { .. }
+            | IpcRequest::Import { .. }
+            | IpcRequest::Shutdown => crate::CONTROL_TIMEOUT,
+            IpcRequest::Status { .. }
+            | IpcRequest::Describe { .. }
+            | IpcRequest::List { .. }
+            | IpcRequest::Logs { .. }
+            | IpcRequest::Health { .. }
+            | IpcRequest::ProcessTree { .. }
+            | IpcRequest::Metrics { .. }
+            | IpcRequest::GetConfig { .. }
+            | IpcRequest::Audit { .. }
+            | IpcRequest::Events { .. }
+            | IpcRequest::Export
+            | IpcRequest::Clients
+            | IpcRequest::Hello { .. }
+            | IpcRequest::Ping => crate::QUERY_TIMEOUT,
+            // A batch is as slow as its slowest entry.
+            IpcRequest::Batch { requests } => requests
+                .iter()
+                .map(IpcRequest::default_timeout)
+                .max()
+                .unwrap_or(crate::QUERY_TIMEOUT),
+            IpcRequest::Auth { .. } | IpcRequest::Subscribe { .. } => crate::DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+/// A reply from the daemon.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IpcResponse {
+    Success {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    Error {
+        code: ErrorCode,
+        message: String,
+    },
+    Status(Box<AppStatus>),
+    /// Status plus startup banner answering [`IpcRequest::Describe`].
+    Describe {
+        status: Box<AppStatus>,
+        banner: Vec<String>,
+    },
+    // Note: sequences can't live in newtype variants of an internally
+    // tagged enum, hence the struct variant.
+    StatusList { statuses: Vec<AppStatus> },
+    Logs {
+        lines: Vec<String>,
+    },
+    // Struct variant for the same serde reason as `StatusList`.
+    Metrics {
+        samples: Vec<MetricSample>,
+    },
+    /// Health check history answering [`IpcRequest::Health`], oldest first.
+    Health {
+        records: Vec<bunctl_core::HealthRecord>,
+    },
+    /// The app's process tree answering [`IpcRequest::ProcessTree`], each
+    /// process after its parent. `clock_hz` converts `cpu_ticks` deltas to
+    /// seconds on whatever machine the client runs on.
+    ProcessTree {
+        procs: Vec<bunctl_core::ProcessNode>,
+        clock_hz: u64,
+    },
+    /// The daemon's cached config of an app.
+    Config {
+        config: Box<AppConfig>,
+    },
+    // Struct variant for the same serde reason as `StatusList`.
+    Audit {
+        entries: Vec<AuditEntry>,
+    },
+    /// Persisted events answering [`IpcRequest::Events`], oldest first.
+    Events {
+        events: Vec<bunctl_core::EventRecord>,
+    },
+    // Struct variant for the same serde reason as `StatusList`.
+    Clients {
+        clients: Vec<ClientInfo>,
+    },
+    /// The registry snapshot answering [`IpcRequest::Export`].
+    Snapshot {
+        snapshot: Box<DaemonSnapshot>,
+    },
+    /// Responses to an [`IpcRequest::Batch`], one per request, in order.
+    Batch {
+        responses: Vec<IpcResponse>,
+    },
+    /// An asynchronous daemon event delivered to subscribed clients.
+    Event {
+        /// Position in the daemon's emission order; monotonically
+        /// increasing for the daemon's lifetime, so clients can order
+        /// events and spot gaps after a reconnect.
+        #[serde(default)]
+        seq: u64,
+        /// When the daemon emitted the event, RFC 3339.
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        ts: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        app: Option<String>,
+        #[serde(flatten)]
+        event: DaemonEvent,
+    },
+}
+
+/// A client currently connected to the daemon, as reported by
+/// [`IpcRequest::Clients`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientInfo {
+    /// Transport-level identity (`uid:<n>` or remote address).
+    pub peer: String,
+    /// Client PID: from SO_PEERCRED where available, else self-reported.
+    pub pid: u32,
+    pub user: String,
+    /// CLI version the client reported.
+    pub version: String,
+    /// How long the connection has been open.
+    pub connected_secs: u64,
+}
+
+/// Which events a subscription receives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionType {
+    /// Everything the daemon emits.
+    All,
+    /// Process lifecycle and state changes only.
+    Status,
+    /// Captured log output only.
+    Logs,
+}
+
+/// A finer-grained event filter layered on top of [`SubscriptionType`],
+/// so dashboards receive exactly the traffic they render. All set parts
+/// must match; an empty filter passes everything.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EventFilter {
+    /// Deliver only these event types, by the names
+    /// [`DaemonEvent::event_type`] reports; empty means every type.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub event_types: Vec<String>,
+    /// Glob over app names where `*` spans any run of characters, e.g.
+    /// `worker-*`. Events not tied to an app (daemon shutdown) only pass
+    /// when no glob is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_glob: Option<String>,
+    /// Drop events below this severity.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_severity: Option<EventSeverity>,
+}
+
+impl EventFilter {
+    /// Whether an event for `app` passes every set part of the filter.
+    pub fn matches(&self, app: Option<&str>, event: &DaemonEvent) -> bool {
+        if !self.event_types.is_empty()
+            && !self.event_types.iter().any(|t| t == event.event_type())
+        {
+            return false;
+        }
+        if let Some(glob) = &self.app_glob {
+            match app {
+                Some(app) => {
+                    if !bunctl_core::config::wildcard_match(glob, app) {
+                        return false;
+                    }
+                }
+                None => return false,
+            }
+        }
+        if let Some(min) = self.min_severity {
+            if event.severity() < min {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Machine-readable error category carried in [`IpcResponse::Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    NotFound,
+    AlreadyExists,
+    Unauthorized,
+    InvalidRequest,
+    MessageTooLarge,
+    RateLimited,
+    SpawnFailed,
+    Timeout,
+    Internal,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bunctl_core::LogStream;
+
+    #[test]
+    fn list_responses_round_trip() {
+        let resp = IpcResponse::StatusList { statuses: Vec::new() };
+        let json = serde_json::to_value(&resp).unwrap();
+        let _: IpcResponse = serde_json::from_value(json).unwrap();
+    }
+
+    #[test]
+    fn batch_round_trips_nested_messages() {
+        let req = IpcRequest::Batch {
+            requests: vec![IpcRequest::Ping, IpcRequest::Status { name: None }],
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["type"], "batch");
+        let back: IpcRequest = serde_json::from_value(json).unwrap();
+        assert!(matches!(back, IpcRequest::Batch { ref requests } if requests.len() == 2));
+        let resp = IpcResponse::Batch {
+            responses: vec![IpcResponse::Success { message: None }],
+        };
+        let json = serde_json::to_value(&resp).unwrap();
+        let _: IpcResponse = serde_json::from_value(json).unwrap();
+    }
+
+    #[test]
+    fn event_response_flattens_typed_event() {
+        let resp = IpcResponse::Event {
+            seq: 7,
+            ts: "2024-01-01T00:00:00Z".into(),
+            app: Some("api".into()),
+            event: DaemonEvent::LogLine { stream: LogStream::Stderr, line: "boom".into() },
+        };
+        let json = serde_json::to_value(&resp).unwrap();
+        assert_eq!(json["type"], "event");
+        assert_eq!(json["event"], "log_line");
+        assert_eq!(json["stream"], "stderr");
+        assert_eq!(json["seq"], 7);
+        let back: IpcResponse = serde_json::from_value(json).unwrap();
+        match back {
+            IpcResponse::Event { seq, ts, app, event } => {
+                assert_eq!(seq, 7);
+                assert_eq!(ts, "2024-01-01T00:00:00Z");
+                assert_eq!(app.as_deref(), Some("api"));
+                assert_eq!(event.event_type(), "log_line");
+            }
+            other => panic!("unexpected response: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn event_filter_applies_types_glob_and_severity() {
+        let filter = EventFilter {
+            event_types: vec!["process_exited".into()],
+            app_glob: Some("worker-*".into()),
+            min_severity: Some(bunctl_core::EventSeverity::Warning),
+        };
+        let crash = DaemonEvent::ProcessExited {
+            code: Some(1),
+            reason: bunctl_core::ExitReason::Code { code: 1 },
+            core: None,
+        };
+        assert!(filter.matches(Some("worker-3"), &crash));
+        // Wrong app, wrong type, and below-threshold severity all drop.
+        assert!(!filter.matches(Some("api"), &crash));
+        assert!(!filter.matches(Some("worker-3"), &DaemonEvent::ProcessStarted { pid: 1 }));
+        let clean = DaemonEvent::ProcessExited {
+            code: Some(0),
+            reason: bunctl_core::ExitReason::Code { code: 0 },
+            core: None,
+        };
+        assert!(!filter.matches(Some("worker-3"), &clean));
+        // App-less events never pass a glob.
+        assert!(!EventFilter {
+            app_glob: Some("*".into()),
+            ..EventFilter::default()
+        }
+        .matches(None, &DaemonEvent::DaemonShutdown));
+        assert!(EventFilter::default().matches(None, &DaemonEvent::DaemonShutdown));
+    }
+}
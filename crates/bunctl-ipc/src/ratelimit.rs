@@ -0,0 +1,114 @@
+//! Per-peer flood protection for the IPC server.
+//!
+//! A buggy script reconnecting (or re-sending) in a tight loop must not be
+//! able to starve the daemon. Limits are counted per peer — the uid for
+//! local socket clients, the source IP for TCP clients — over a fixed
+//! one-minute window.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Per-peer limits over a one-minute window; `0` disables that limit.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    pub connections_per_min: u32,
+    pub requests_per_min: u32,
+}
+
+struct Window {
+    started: Instant,
+    connections: u32,
+    requests: u32,
+}
+
+/// Tracks per-peer windows and counts rejections.
+pub(crate) struct RateLimiter {
+    limit: RateLimit,
+    windows: Mutex<HashMap<String, Window>>,
+    rejected: AtomicU64,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            windows: Mutex::new(HashMap::new()),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn allow_connection(&self, peer: &str) -> bool {
+        self.allow(peer, self.limit.connections_per_min, |w| &mut w.connections)
+    }
+
+    pub(crate) fn allow_request(&self, peer: &str) -> bool {
+        self.allow(peer, self.limit.requests_per_min, |w| &mut w.requests)
+    }
+
+    /// Total connections and requests rejected since startup (the metric
+    /// counter behind `IpcServer::rate_limited_total`).
+    pub(crate) fn rejected_total(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+
+    fn allow(&self, peer: &str, limit: u32, counter: impl Fn(&mut Window) -> &mut u32) -> bool {
+        if limit == 0 {
+            return true;
+        }
+        let mut windows = self.windows.lock().expect("rate limiter poisoned");
+        let window = windows.entry(normalize_peer(peer)).or_insert_with(|| Window {
+            started: Instant::now(),
+            connections: 0,
+            requests: 0,
+        });
+        if window.started.elapsed().as_secs() >= 60 {
+            *window = Window { started: Instant::now(), connections: 0, requests: 0 };
+        }
+        let count = counter(window);
+        if *count >= limit {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+        *count += 1;
+        true
+    }
+}
+
+/// Limits are per source, not per connection: strip the ephemeral port off
+/// TCP peers so reconnects share one window. Non-address peers (`uid:<n>`)
+/// are used as-is.
+fn normalize_peer(peer: &str) -> String {
+    match peer.parse::<std::net::SocketAddr>() {
+        Ok(addr) => addr.ip().to_string(),
+        Err(_) => peer.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enforces_connection_limit_per_peer() {
+        let limiter =
+            RateLimiter::new(RateLimit { connections_per_min: 2, requests_per_min: 0 });
+        assert!(limiter.allow_connection("uid:0"));
+        assert!(limiter.allow_connection("uid:0"));
+        assert!(!limiter.allow_connection("uid:0"));
+        // A different peer has its own window.
+        assert!(limiter.allow_connection("uid:1000"));
+        assert_eq!(limiter.rejected_total(), 1);
+        // Disabled limits always pass.
+        assert!(limiter.allow_request("uid:0"));
+    }
+
+    #[test]
+    fn tcp_peers_share_a_window_across_ports() {
+        let limiter =
+            RateLimiter::new(RateLimit { connections_per_min: 1, requests_per_min: 0 });
+        assert!(limiter.allow_connection("10.0.0.5:50001"));
+        assert!(!limiter.allow_connection("10.0.0.5:50002"));
+    }
+}
@@ -0,0 +1,95 @@
+//! Platform-specific defaults for where the daemon's control socket lives.
+//!
+//! * **Linux** — `$XDG_RUNTIME_DIR/bunctl/bunctl.sock`, falling back to
+//!   `/tmp/bunctl-<uid>/bunctl.sock` when no runtime dir is set (e.g. under
+//!   cron or a system service).
+//! * **macOS** — there is no XDG runtime dir; the per-user `$TMPDIR` (of the
+//!   form `/var/folders/…/T/`) plays the same role, with the same `/tmp`
+//!   fallback.
+//! * **Windows** — a named pipe, `\\.\pipe\bunctl`.
+//!
+//! A root daemon (euid 0) is the system instance and uses `/run/bunctl`
+//! instead; unprivileged users never need paths outside their own home and
+//! runtime directories.
+
+use std::path::PathBuf;
+
+/// Default path of the daemon control socket for the current user.
+#[cfg(target_os = "linux")]
+pub fn default_socket_path() -> PathBuf {
+    if unsafe { libc_uid() } == 0 {
+        return PathBuf::from("/run/bunctl/bunctl.sock");
+    }
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir).join("bunctl").join("bunctl.sock"),
+        _ => fallback_tmp_path(),
+    }
+}
+
+/// Default path of the daemon control socket for the current user.
+#[cfg(target_os = "macos")]
+pub fn default_socket_path() -> PathBuf {
+    if unsafe { libc_uid() } == 0 {
+        return PathBuf::from("/var/run/bunctl/bunctl.sock");
+    }
+    match std::env::var_os("TMPDIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir).join("bunctl").join("bunctl.sock"),
+        _ => fallback_tmp_path(),
+    }
+}
+
+/// Default name of the daemon control pipe.
+#[cfg(windows)]
+pub fn default_socket_path() -> PathBuf {
+    PathBuf::from(r"\\.\pipe\bunctl")
+}
+
+#[cfg(unix)]
+fn fallback_tmp_path() -> PathBuf {
+    // Scope the fallback by uid so unprivileged users on a shared host don't
+    // collide in /tmp.
+    let uid = unsafe { libc_uid() };
+    PathBuf::from(format!("/tmp/bunctl-{uid}")).join("bunctl.sock")
+}
+
+#[cfg(unix)]
+unsafe fn libc_uid() -> u32 {
+    // Direct syscall wrapper via std is not exposed; geteuid is always safe
+    // to call.
+    extern "C" {
+        fn geteuid() -> u32;
+    }
+    geteuid()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_prefers_xdg_runtime_dir() {
+        // Environment mutation is process-global; this test only asserts on
+        // the shape of the fallback to stay order-independent.
+        let path = fallback_tmp_path();
+        assert!(path.starts_with("/tmp"));
+        assert!(path.ends_with("bunctl.sock"));
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn macos_uses_tmpdir() {
+        // macOS always sets TMPDIR for login sessions; the default must live
+        // under it rather than under a (nonexistent) XDG runtime dir.
+        let path = default_socket_path();
+        assert!(path.ends_with("bunctl/bunctl.sock") || path.ends_with("bunctl.sock"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn fallback_is_per_user() {
+        let path = fallback_tmp_path();
+        let s = path.to_string_lossy();
+        assert!(s.starts_with("/tmp/bunctl-"), "unexpected fallback: {s}");
+    }
+}
@@ -0,0 +1,76 @@
+//! TLS configuration for the TCP transport (`tls` feature).
+//!
+//! Remote deployments typically use a self-signed or internal CA, so both
+//! sides are configured from PEM files on disk rather than system roots.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::{ClientConfig, RootCertStore, ServerConfig};
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+
+use crate::IpcError;
+
+/// Server-side TLS material for [`crate::IpcServer::bind_tls`].
+#[derive(Debug, Clone)]
+pub struct TlsServerConfig {
+    /// PEM file with the server certificate chain.
+    pub cert_chain: PathBuf,
+    /// PEM file with the server private key.
+    pub private_key: PathBuf,
+}
+
+impl TlsServerConfig {
+    pub(crate) fn acceptor(&self) -> Result<TlsAcceptor, IpcError> {
+        let certs = load_certs(&self.cert_chain)?;
+        let key = load_key(&self.private_key)?;
+        let config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .map_err(tls_err)?;
+        Ok(TlsAcceptor::from(Arc::new(config)))
+    }
+}
+
+/// Client-side TLS trust for [`crate::IpcClient::connect_tls`].
+#[derive(Debug, Clone)]
+pub struct TlsClientConfig {
+    /// PEM file with the CA certificate(s) that signed the daemon cert.
+    pub ca_certs: PathBuf,
+}
+
+impl TlsClientConfig {
+    pub(crate) fn connector(&self) -> Result<TlsConnector, IpcError> {
+        let mut roots = RootCertStore::empty();
+        for cert in load_certs(&self.ca_certs)? {
+            roots.add(cert).map_err(tls_err)?;
+        }
+        let config = ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        Ok(TlsConnector::from(Arc::new(config)))
+    }
+}
+
+fn load_certs(path: &std::path::Path) -> Result<Vec<CertificateDer<'static>>, IpcError> {
+    let pem = std::fs::read(path)?;
+    rustls_pemfile::certs(&mut pem.as_slice())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(IpcError::Io)
+}
+
+fn load_key(path: &std::path::Path) -> Result<PrivateKeyDer<'static>, IpcError> {
+    let pem = std::fs::read(path)?;
+    rustls_pemfile::private_key(&mut pem.as_slice())?
+        .ok_or_else(|| {
+            IpcError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("no private key found in {}", path.display()),
+            ))
+        })
+}
+
+fn tls_err(err: tokio_rustls::rustls::Error) -> IpcError {
+    IpcError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+}
@@ -0,0 +1,572 @@
+//! Transport-agnostic server/client/connection types.
+//!
+//! The daemon normally listens on a Unix domain socket (see
+//! [`crate::socket_path`]); it can additionally listen on TCP so `bunctl
+//! --host prod-1:7070 …` can manage a daemon on another machine. Both
+//! transports carry the same framed messages. TCP listeners should always be
+//! paired with token auth, and with TLS (the `tls` feature) when the link is
+//! untrusted.
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpStream;
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+
+use crate::codec::{read_message, write_message};
+use crate::message::{ErrorCode, IpcRequest, IpcResponse};
+use crate::ratelimit::RateLimiter;
+use crate::{IpcError, RateLimit};
+
+/// A connected stream over any supported transport.
+pub enum IpcStream {
+    #[cfg(unix)]
+    Unix(UnixStream),
+    Tcp(TcpStream),
+    #[cfg(feature = "tls")]
+    TlsServer(Box<tokio_rustls::server::TlsStream<TcpStream>>),
+    #[cfg(feature = "tls")]
+    TlsClient(Box<tokio_rustls::client::TlsStream<TcpStream>>),
+}
+
+macro_rules! delegate {
+    ($self:ident, $stream:ident => $expr:expr) => {
+        match $self.get_mut() {
+            #[cfg(unix)]
+            IpcStream::Unix($stream) => $expr,
+            IpcStream::Tcp($stream) => $expr,
+            #[cfg(feature = "tls")]
+            IpcStream::TlsServer($stream) => $expr,
+            #[cfg(feature = "tls")]
+            IpcStream::TlsClient($stream) => $expr,
+        }
+    };
+}
+
+impl AsyncRead for IpcStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        delegate!(self, s => Pin::new(s).poll_read(cx, buf))
+    }
+}
+
+impl AsyncWrite for IpcStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        delegate!(self, s => Pin::new(s).poll_write(cx, buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        delegate!(self, s => Pin::new(s).poll_flush(cx))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        delegate!(self, s => Pin::new(s).poll_shutdown(cx))
+    }
+}
+
+enum Listener {
+    #[cfg(unix)]
+    Unix { listener: UnixListener, path: std::path::PathBuf },
+    Tcp(tokio::net::TcpListener),
+}
+
+/// Listening side of the IPC transport, held by the daemon.
+pub struct IpcServer {
+    listener: Listener,
+    /// Shared secret required from TCP clients before any other request.
+    token: Option<Arc<str>>,
+    /// Per-peer flood protection, when configured.
+    limiter: Option<Arc<RateLimiter>>,
+    #[cfg(feature = "tls")]
+    tls: Option<tokio_rustls::TlsAcceptor>,
+}
+
+impl IpcServer {
+    /// Bind the control socket at `path`, creating parent directories and
+    /// removing any stale socket file left by a previous daemon.
+    #[cfg(unix)]
+    pub fn bind(path: &std::path::Path) -> Result<Self, IpcError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if path.exists() {
+            // A stale socket from a crashed daemon; if another daemon were
+            // alive it would hold the bind, so removal is safe here.
+            std::fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+        Ok(Self {
+            listener: Listener::Unix { listener, path: path.to_path_buf() },
+            token: None,
+            limiter: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+        })
+    }
+
+    /// Bind a TCP listener for remote control. `token`, when set, must be
+    /// presented by every client in an [`IpcRequest::Auth`] before any other
+    /// request is accepted.
+    pub async fn bind_tcp(addr: &str, token: Option<String>) -> Result<Self, IpcError> {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        Ok(Self {
+            listener: Listener::Tcp(listener),
+            token: token.map(Arc::from),
+            limiter: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+        })
+    }
+
+    /// Enable per-peer flood protection: connections and requests beyond
+    /// the limits are answered with a `RateLimited` error and dropped.
+    pub fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.limiter = Some(Arc::new(RateLimiter::new(limit)));
+        self
+    }
+
+    /// How many connections/requests have been rejected by the rate limiter
+    /// since startup.
+    pub fn rate_limited_total(&self) -> u64 {
+        self.limiter.as_ref().map_or(0, |l| l.rejected_total())
+    }
+
+    /// Like [`bind_tcp`](Self::bind_tcp) but wraps every accepted connection
+    /// in server-side TLS.
+    #[cfg(feature = "tls")]
+    pub async fn bind_tls(
+        addr: &str,
+        token: Option<String>,
+        tls: &crate::tls::TlsServerConfig,
+    ) -> Result<Self, IpcError> {
+        let mut server = Self::bind_tcp(addr, token).await?;
+        server.tls = Some(tls.acceptor()?);
+        Ok(server)
+    }
+
+    /// Accept the next client connection, completing the TLS handshake when
+    /// configured. Connections over the configured rate limit are answered
+    /// with a `RateLimited` error, dropped, and never surfaced to the
+    /// caller.
+    pub async fn accept(&self) -> Result<IpcConnection, IpcError> {
+        loop {
+            let mut conn = self.accept_one().await?;
+            if let Some(limiter) = &self.limiter {
+                if !limiter.allow_connection(conn.peer()) {
+                    tracing::warn!(peer = %conn.peer(), "connection rate limit exceeded");
+                    let _ = conn
+                        .write_response(&IpcResponse::Error {
+                            code: ErrorCode::RateLimited,
+                            message: "connection rate limit exceeded".into(),
+                        })
+                        .await;
+                    continue;
+                }
+                conn.limiter = Some(limiter.clone());
+            }
+            return Ok(conn);
+        }
+    }
+
+    async fn accept_one(&self) -> Result<IpcConnection, IpcError> {
+        let peer;
+        let stream = match &self.listener {
+            #[cfg(unix)]
+            Listener::Unix { listener, .. } => {
+                let (stream, _) = listener.accept().await?;
+                let cred = stream.peer_cred();
+                let peer = cred
+                    .as_ref()
+                    .map(|cred| format!("uid:{}", cred.uid()))
+                    .unwrap_or_else(|_| "local".into());
+                // SO_PEERCRED also carries the client PID on Linux; keep it
+                // so self-reported Hello PIDs can be overridden.
+                #[cfg(target_os = "linux")]
+                let peer_pid = cred.ok().and_then(|cred| cred.pid()).map(|pid| pid as u32);
+                #[cfg(not(target_os = "linux"))]
+                let peer_pid = None;
+                // Local socket connections are trusted via filesystem
+                // permissions; no token handshake.
+                return Ok(IpcConnection {
+                    stream: IpcStream::Unix(stream),
+                    token: None,
+                    authed: true,
+                    peer,
+                    peer_pid,
+                    limiter: None,
+                });
+            }
+            Listener::Tcp(listener) => {
+                let (stream, addr) = listener.accept().await?;
+                peer = addr.to_string();
+                stream
+            }
+        };
+        #[cfg(feature = "tls")]
+        let stream = match &self.tls {
+            Some(acceptor) => IpcStream::TlsServer(Box::new(
+                acceptor
+                    .accept(stream)
+                    .await
+                    .map_err(IpcError::Io)?,
+            )),
+            None => IpcStream::Tcp(stream),
+        };
+        #[cfg(not(feature = "tls"))]
+        let stream = IpcStream::Tcp(stream);
+        Ok(IpcConnection {
+            stream,
+            authed: self.token.is_none(),
+            token: self.token.clone(),
+            peer,
+            peer_pid: None,
+            limiter: None,
+        })
+    }
+
+    /// Path the server is bound to, when it is a Unix socket.
+    pub fn path(&self) -> Option<&std::path::Path> {
+        match &self.listener {
+            #[cfg(unix)]
+            Listener::Unix { path, .. } => Some(path),
+            Listener::Tcp(_) => None,
+        }
+    }
+
+    /// Local address, when listening on TCP.
+    pub fn local_addr(&self) -> Option<std::net::SocketAddr> {
+        match &self.listener {
+            #[cfg(unix)]
+            Listener::Unix { .. } => None,
+            Listener::Tcp(listener) => listener.local_addr().ok(),
+        }
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Listener::Unix { path, .. } = &self.listener {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// One accepted client connection, as seen by the daemon.
+pub struct IpcConnection {
+    stream: IpcStream,
+    /// Expected token; `None` once no further auth is required.
+    token: Option<Arc<str>>,
+    authed: bool,
+    /// Client identity for audit logging: `uid:<n>` for local socket
+    /// clients, the remote address for TCP clients.
+    peer: String,
+    /// Client PID from SO_PEERCRED, where the transport provides it.
+    peer_pid: Option<u32>,
+    /// Shared per-peer rate limiter, when the server has one configured.
+    limiter: Option<Arc<RateLimiter>>,
+}
+
+impl IpcConnection {
+    /// Client identity, for audit logging.
+    pub fn peer(&self) -> &str {
+        &self.peer
+    }
+
+    /// Transport-verified client PID, where available.
+    pub fn peer_pid(&self) -> Option<u32> {
+        self.peer_pid
+    }
+
+    /// Read the next request; `ConnectionClosed` when the client hangs up.
+    ///
+    /// On token-protected transports this transparently consumes the
+    /// [`IpcRequest::Auth`] handshake: the first request must carry the
+    /// right token or the connection is rejected.
+    pub async fn read_request(&mut self) -> Result<IpcRequest, IpcError> {
+        loop {
+            let req: IpcRequest = match read_message(&mut self.stream).await {
+                Ok(req) => req,
+                Err(IpcError::MessageTooLarge { size, limit }) => {
+                    // The codec drained the frame, so the connection is still
+                    // usable; tell the client how far over it went.
+                    self.write_response(&IpcResponse::Error {
+                        code: ErrorCode::MessageTooLarge,
+                        message: format!(
+                            "message of {size} bytes exceeds maximum of {limit} bytes"
+                        ),
+                    })
+                    .await?;
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            if let Some(limiter) = &self.limiter {
+                if !limiter.allow_request(&self.peer) {
+                    self.write_response(&IpcResponse::Error {
+                        code: ErrorCode::RateLimited,
+                        message: "request rate limit exceeded".into(),
+                    })
+                    .await?;
+                    continue;
+                }
+            }
+            if self.authed {
+                return Ok(req);
+            }
+            match req {
+                IpcRequest::Auth { ref token }
+                    if self.token.as_deref() == Some(token.as_str()) =>
+                {
+                    self.authed = true;
+                    self.write_response(&IpcResponse::Success { message: None }).await?;
+                }
+                _ => {
+                    self.write_response(&IpcResponse::Error {
+                        code: ErrorCode::Unauthorized,
+                        message: "authentication required".into(),
+                    })
+                    .await?;
+                    return Err(IpcError::Unauthorized);
+                }
+            }
+        }
+    }
+
+    pub async fn write_response(&mut self, resp: &IpcResponse) -> Result<(), IpcError> {
+        write_message(&mut self.stream, resp).await
+    }
+}
+
+/// Client side of the IPC transport, used by the CLI.
+pub struct IpcClient {
+    stream: IpcStream,
+    /// When set, overrides the per-request timeout class.
+    timeout: Option<std::time::Duration>,
+}
+
+impl IpcClient {
+    #[cfg(unix)]
+    pub async fn connect(path: &std::path::Path) -> Result<Self, IpcError> {
+        let stream = UnixStream::connect(path).await?;
+        Ok(Self { stream: IpcStream::Unix(stream), timeout: None })
+    }
+
+    /// Connect to a daemon's TCP listener, performing the token handshake
+    /// when `token` is given.
+    pub async fn connect_tcp(addr: &str, token: Option<&str>) -> Result<Self, IpcError> {
+        let stream = TcpStream::connect(addr).await?;
+        let mut client = Self { stream: IpcStream::Tcp(stream), timeout: None };
+        client.authenticate(token).await?;
+        Ok(client)
+    }
+
+    /// Use one fixed timeout for every request instead of the per-class
+    /// defaults from [`IpcRequest::default_timeout`].
+    pub fn set_timeout(&mut self, timeout: std::time::Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    /// Like [`connect_tcp`](Self::connect_tcp) over TLS. `server_name` must
+    /// match the daemon certificate.
+    #[cfg(feature = "tls")]
+    pub async fn connect_tls(
+        addr: &str,
+        server_name: &str,
+        token: Option<&str>,
+        tls: &crate::tls::TlsClientConfig,
+    ) -> Result<Self, IpcError> {
+        let stream = TcpStream::connect(addr).await?;
+        let connector = tls.connector()?;
+        let name = tokio_rustls::rustls::pki_types::ServerName::try_from(server_name.to_owned())
+            .map_err(|_| {
+                IpcError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("invalid TLS server name: {server_name}"),
+                ))
+            })?;
+        let stream = connector.connect(name, stream).await?;
+        let mut client = Self { stream: IpcStream::TlsClient(Box::new(stream)), timeout: None };
+        client.authenticate(token).await?;
+        Ok(client)
+    }
+
+    async fn authenticate(&mut self, token: Option<&str>) -> Result<(), IpcError> {
+        let Some(token) = token else { return Ok(()) };
+        match self.request(&IpcRequest::Auth { token: token.to_owned() }).await? {
+            IpcResponse::Success { .. } => Ok(()),
+            IpcResponse::Error { message, .. } => Err(IpcError::AuthFailed(message)),
+            _ => Err(IpcError::AuthFailed("unexpected handshake response".into())),
+        }
+    }
+
+    /// Send one request and wait for its response, up to the configured
+    /// timeout or the request's own class default.
+    pub async fn request(&mut self, req: &IpcRequest) -> Result<IpcResponse, IpcError> {
+        let timeout = self.timeout.unwrap_or_else(|| req.default_timeout());
+        write_message(&mut self.stream, req).await?;
+        match tokio::time::timeout(timeout, read_message(&mut self.stream)).await {
+            Ok(result) => result,
+            Err(_) => Err(IpcError::Timeout(timeout)),
+        }
+    }
+
+    /// Read the next server-pushed message without sending anything, used by
+    /// event subscriptions.
+    pub async fn read_response(&mut self) -> Result<IpcResponse, IpcError> {
+        read_message(&mut self.stream).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_server_and_client_exchange_messages() {
+        let dir = std::env::temp_dir().join(format!("bunctl-ipc-test-{}", std::process::id()));
+        let sock = dir.join("test.sock");
+        let server = IpcServer::bind(&sock).unwrap();
+
+        let server_task = tokio::spawn(async move {
+            let mut conn = server.accept().await.unwrap();
+            let req = conn.read_request().await.unwrap();
+            assert!(matches!(req, IpcRequest::Ping));
+            conn.write_response(&IpcResponse::Success { message: None })
+                .await
+                .unwrap();
+        });
+
+        let mut client = IpcClient::connect(&sock).await.unwrap();
+        let resp = client.request(&IpcRequest::Ping).await.unwrap();
+        assert!(matches!(resp, IpcResponse::Success { .. }));
+        server_task.await.unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn bind_replaces_stale_socket() {
+        let dir = std::env::temp_dir().join(format!("bunctl-ipc-stale-{}", std::process::id()));
+        let sock = dir.join("stale.sock");
+        drop(IpcServer::bind(&sock).unwrap());
+        // First server's Drop removed the file; simulate a crash leftover.
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&sock, b"").unwrap();
+        let server = IpcServer::bind(&sock).unwrap();
+        assert_eq!(server.path(), Some(sock.as_path()));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn tcp_round_trip_with_token() {
+        let server = IpcServer::bind_tcp("127.0.0.1:0", Some("s3cret".into()))
+            .await
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let server_task = tokio::spawn(async move {
+            let mut conn = server.accept().await.unwrap();
+            let req = conn.read_request().await.unwrap();
+            assert!(matches!(req, IpcRequest::Ping));
+            conn.write_response(&IpcResponse::Success { message: None })
+                .await
+                .unwrap();
+        });
+
+        let mut client = IpcClient::connect_tcp(&addr.to_string(), Some("s3cret"))
+            .await
+            .unwrap();
+        let resp = client.request(&IpcRequest::Ping).await.unwrap();
+        assert!(matches!(resp, IpcResponse::Success { .. }));
+        server_task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn oversized_request_keeps_connection_usable() {
+        let server = IpcServer::bind_tcp("127.0.0.1:0", None).await.unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let server_task = tokio::spawn(async move {
+            let mut conn = server.accept().await.unwrap();
+            // read_request swallows the oversized frame and answers it; the
+            // next well-formed request comes through.
+            let req = conn.read_request().await.unwrap();
+            assert!(matches!(req, IpcRequest::Ping));
+            conn.write_response(&IpcResponse::Success { message: None })
+                .await
+                .unwrap();
+        });
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        {
+            use tokio::io::AsyncWriteExt;
+            let oversized = crate::MAX_MESSAGE_SIZE as u32 + 1;
+            stream.write_all(&oversized.to_le_bytes()).await.unwrap();
+            stream.write_all(&vec![b' '; oversized as usize]).await.unwrap();
+        }
+        let resp: IpcResponse = read_message(&mut stream).await.unwrap();
+        assert!(matches!(
+            resp,
+            IpcResponse::Error { code: ErrorCode::MessageTooLarge, .. }
+        ));
+        write_message(&mut stream, &IpcRequest::Ping).await.unwrap();
+        let resp: IpcResponse = read_message(&mut stream).await.unwrap();
+        assert!(matches!(resp, IpcResponse::Success { .. }));
+        server_task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn tcp_rejects_bad_token() {
+        let server = IpcServer::bind_tcp("127.0.0.1:0", Some("s3cret".into()))
+            .await
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let mut conn = server.accept().await.unwrap();
+            let _ = conn.read_request().await;
+        });
+
+        let Err(err) = IpcClient::connect_tcp(&addr.to_string(), Some("wrong")).await else {
+            panic!("connect with a bad token should fail");
+        };
+        assert!(matches!(err, IpcError::AuthFailed(_)));
+    }
+
+    #[tokio::test]
+    async fn tcp_requires_token_when_configured() {
+        let server = IpcServer::bind_tcp("127.0.0.1:0", Some("s3cret".into()))
+            .await
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let mut conn = server.accept().await.unwrap();
+            let err = conn.read_request().await.unwrap_err();
+            assert!(matches!(err, IpcError::Unauthorized));
+        });
+
+        let mut client = IpcClient::connect_tcp(&addr.to_string(), None).await.unwrap();
+        let resp = client.request(&IpcRequest::Ping).await.unwrap();
+        assert!(matches!(
+            resp,
+            IpcResponse::Error { code: ErrorCode::Unauthorized, .. }
+        ));
+    }
+}
@@ -0,0 +1,76 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use bunctl_core::audit::AuditEntry;
+
+use crate::LogError;
+
+/// Append-only audit log: one JSON entry per line. The file name carries a
+/// `.jsonl` suffix so it never shows up as an orphan app log.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Append one entry; the file is created on first use.
+    pub fn record(&self, entry: &AuditEntry) -> Result<(), LogError> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let line = serde_json::to_string(entry).map_err(std::io::Error::other)?;
+        writeln!(file, "{line}")?;
+        Ok(())
+    }
+
+    /// Entries with `ts >= since`, oldest first. Unparseable lines (torn
+    /// writes, older formats) are skipped.
+    pub fn query(&self, since: u64) -> Result<Vec<AuditEntry>, LogError> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let data = std::fs::read_to_string(&self.path)?;
+        Ok(data
+            .lines()
+            .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok())
+            .filter(|entry| entry.ts >= since)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(ts: u64, action: &str) -> AuditEntry {
+        AuditEntry {
+            ts,
+            peer: "uid:0".into(),
+            action: action.into(),
+            app: Some("api".into()),
+            success: true,
+            detail: None,
+        }
+    }
+
+    #[test]
+    fn records_and_queries_by_time() {
+        let dir = std::env::temp_dir().join(format!("bunctl-audit-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let log = AuditLog::new(dir.join("audit.jsonl"));
+        log.record(&entry(100, "start")).unwrap();
+        log.record(&entry(200, "stop")).unwrap();
+        let all = log.query(0).unwrap();
+        assert_eq!(all.len(), 2);
+        let recent = log.query(150).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].action, "stop");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
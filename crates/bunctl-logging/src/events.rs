@@ -0,0 +1,148 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use bunctl_core::event::EventRecord;
+use bunctl_core::time;
+
+use crate::LogError;
+
+/// How large the live event log may grow before it is rotated aside.
+const MAX_SIZE: u64 = 16 * 1024 * 1024;
+/// Rotated generations kept on disk; older ones are removed.
+const KEEP_ROTATED: usize = 2;
+
+/// Append-only persisted event log: one JSON record per line, rotated by
+/// size like app logs. Log-line events are deliberately not recorded here
+/// — the app's own log file already holds them — so the volume stays low
+/// enough to open the file per record, like [`crate::AuditLog`].
+#[derive(Debug, Clone)]
+pub struct EventLog {
+    path: PathBuf,
+}
+
+impl EventLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Append one record; the file is created on first use and rotated
+    /// aside once it grows past the size cap.
+    pub fn record(&self, record: &EventRecord) -> Result<(), LogError> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let line = serde_json::to_string(record).map_err(std::io::Error::other)?;
+        writeln!(file, "{line}")?;
+        if file.metadata().map(|m| m.len()).unwrap_or(0) >= MAX_SIZE {
+            self.rotate()?;
+        }
+        Ok(())
+    }
+
+    /// Records with `ts >= since` across the live file and kept rotated
+    /// generations, oldest first. Unparseable lines (torn writes, older
+    /// formats) are skipped.
+    pub fn query(&self, since: u64) -> Result<Vec<EventRecord>, LogError> {
+        let mut records = Vec::new();
+        let mut paths = self.rotated_paths()?;
+        paths.push(self.path.clone());
+        for path in paths {
+            let Ok(data) = std::fs::read_to_string(&path) else { continue };
+            records.extend(
+                data.lines()
+                    .filter_map(|line| serde_json::from_str::<EventRecord>(line).ok())
+                    .filter(|record| record.ts >= since),
+            );
+        }
+        // seq alone is not enough across daemon restarts; ts breaks the tie.
+        records.sort_by_key(|record| (record.ts, record.seq));
+        Ok(records)
+    }
+
+    /// Move the live file aside under a timestamped name and drop rotated
+    /// generations beyond [`KEEP_ROTATED`].
+    fn rotate(&self) -> Result<(), LogError> {
+        let mut rotated = PathBuf::from(format!("{}.{}", self.path.display(), time::unix_now()));
+        let mut n = 1;
+        while rotated.exists() {
+            rotated = PathBuf::from(format!("{}.{}-{n}", self.path.display(), time::unix_now()));
+            n += 1;
+        }
+        std::fs::rename(&self.path, &rotated)?;
+        let old = self.rotated_paths()?;
+        for path in old.iter().rev().skip(KEEP_ROTATED) {
+            let _ = std::fs::remove_file(path);
+        }
+        Ok(())
+    }
+
+    /// Rotated generations of this log, oldest first.
+    fn rotated_paths(&self) -> Result<Vec<PathBuf>, LogError> {
+        let Some(parent) = self.path.parent() else { return Ok(Vec::new()) };
+        let Some(name) = self.path.file_name().and_then(|s| s.to_str()) else {
+            return Ok(Vec::new());
+        };
+        let prefix = format!("{name}.");
+        let mut paths = Vec::new();
+        let Ok(entries) = std::fs::read_dir(parent) else { return Ok(Vec::new()) };
+        for entry in entries.flatten() {
+            let file_name = entry.file_name();
+            let Some(file_name) = file_name.to_str() else { continue };
+            if file_name.starts_with(&prefix) {
+                paths.push(entry.path());
+            }
+        }
+        // Timestamped suffixes sort chronologically.
+        paths.sort();
+        Ok(paths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bunctl_core::DaemonEvent;
+
+    fn record(ts: u64, seq: u64) -> EventRecord {
+        EventRecord {
+            ts,
+            seq,
+            app: Some("api".into()),
+            event: DaemonEvent::ProcessStarted { pid: 42 },
+        }
+    }
+
+    #[test]
+    fn records_and_queries_by_time() {
+        let dir = std::env::temp_dir().join(format!("bunctl-events-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let log = EventLog::new(dir.join("events.jsonl"));
+        log.record(&record(100, 0)).unwrap();
+        log.record(&record(200, 1)).unwrap();
+        let all = log.query(0).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].seq, 0);
+        let recent = log.query(150).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].ts, 200);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn query_spans_rotated_generations() {
+        let dir = std::env::temp_dir().join(format!("bunctl-events-rot-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let log = EventLog::new(dir.join("events.jsonl"));
+        log.record(&record(100, 0)).unwrap();
+        log.rotate().unwrap();
+        log.record(&record(200, 1)).unwrap();
+        let all = log.query(0).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].ts, 100);
+        assert_eq!(all[1].ts, 200);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,84 @@
+//! Log capture, storage and rotation for bunctl-managed apps.
+//!
+//! The [`LogManager`] owns the log directory layout: one `<app>.log` file
+//! per app under a per-user base directory. Supervisors and the daemon go
+//! through it for both writing and reading so the CLI always finds logs in
+//! one place. This holds on every platform — Windows included — because
+//! child output is always captured through piped stdio rather than
+//! redirected to files by the supervisor, so there is exactly one file
+//! layout and one rotation policy.
+
+mod audit;
+mod events;
+pub mod manifest;
+mod manager;
+pub mod rotation;
+mod writer;
+
+pub use audit::AuditLog;
+pub use events::EventLog;
+pub use manager::LogManager;
+pub use writer::LogWriter;
+
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum LogError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("no log file for app '{0}'")]
+    NoLogFile(String),
+
+    #[error("no rotation manifest for app '{0}' (is log_manifest enabled?)")]
+    NoManifest(String),
+}
+
+/// Per-user default log directory.
+///
+/// * Linux: `$XDG_STATE_HOME/bunctl/logs` or `~/.local/state/bunctl/logs`
+/// * macOS: `~/Library/Logs/bunctl`
+/// * Windows: `%LOCALAPPDATA%\bunctl\logs`
+///
+/// A root daemon is the system instance and logs to `/var/log/bunctl`.
+pub fn default_log_dir() -> PathBuf {
+    #[cfg(unix)]
+    {
+        if is_root() {
+            return PathBuf::from("/var/log/bunctl");
+        }
+    }
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(dir) = std::env::var_os("XDG_STATE_HOME").filter(|d| !d.is_empty()) {
+            return PathBuf::from(dir).join("bunctl").join("logs");
+        }
+        home().join(".local/state/bunctl/logs")
+    }
+    #[cfg(target_os = "macos")]
+    {
+        home().join("Library/Logs/bunctl")
+    }
+    #[cfg(windows)]
+    {
+        match std::env::var_os("LOCALAPPDATA") {
+            Some(dir) => PathBuf::from(dir).join("bunctl").join("logs"),
+            None => PathBuf::from(r"C:\bunctl\logs"),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn home() -> PathBuf {
+    std::env::var_os("HOME").map(PathBuf::from).unwrap_or_else(|| PathBuf::from("/tmp"))
+}
+
+#[cfg(unix)]
+fn is_root() -> bool {
+    extern "C" {
+        fn geteuid() -> u32;
+    }
+    unsafe { geteuid() == 0 }
+}
@@ -0,0 +1,268 @@
+use std::path::{Path, PathBuf};
+
+use bunctl_core::AppId;
+
+use crate::writer::LogWriter;
+use crate::LogError;
+
+/// Owns the log directory and hands out per-app readers and writers.
+#[derive(Debug, Clone)]
+pub struct LogManager {
+    base_dir: PathBuf,
+}
+
+impl LogManager {
+    /// Create a manager rooted at `base_dir`, creating the directory.
+    pub fn new(base_dir: PathBuf) -> Result<Self, LogError> {
+        std::fs::create_dir_all(&base_dir)?;
+        Ok(Self { base_dir })
+    }
+
+    pub fn base_dir(&self) -> &Path {
+        &self.base_dir
+    }
+
+    /// Path of the current log file for `app`.
+    pub fn log_path(&self, app: &AppId) -> PathBuf {
+        self.base_dir.join(format!("{app}.log"))
+    }
+
+    /// Whether a log file exists for `app` (managed or not).
+    pub fn has_log(&self, app: &AppId) -> bool {
+        self.log_path(app).exists()
+    }
+
+    /// The audit log living alongside the app logs.
+    pub fn audit_log(&self) -> crate::AuditLog {
+        crate::AuditLog::new(self.base_dir.join("audit.jsonl"))
+    }
+
+    /// The persisted event log living alongside the app logs.
+    pub fn event_log(&self) -> crate::EventLog {
+        crate::EventLog::new(self.base_dir.join("events.jsonl"))
+    }
+
+    /// Open an append writer for `app`.
+    pub fn writer(&self, app: &AppId) -> Result<LogWriter, LogError> {
+        LogWriter::open(self.log_path(app))
+    }
+
+    /// Last `n` lines of the app's log.
+    pub fn read_last_lines(&self, app: &AppId, n: usize) -> Result<Vec<String>, LogError> {
+        let path = self.log_path(app);
+        if !path.exists() {
+            return Err(LogError::NoLogFile(app.to_string()));
+        }
+        let data = std::fs::read_to_string(&path)?;
+        let mut lines: Vec<String> = data.lines().map(str::to_owned).collect();
+        if lines.len() > n {
+            lines.drain(..lines.len() - n);
+        }
+        Ok(lines)
+    }
+
+    /// Last `n` entries of the app's log, where continuation lines (those
+    /// without a `[timestamp]` prefix) belong to the entry above them. With
+    /// `grep` set, only entries containing the substring anywhere — header
+    /// or continuation — are returned, whole.
+    pub fn read_last_entries(
+        &self,
+        app: &AppId,
+        n: usize,
+        grep: Option<&str>,
+    ) -> Result<Vec<String>, LogError> {
+        let path = self.log_path(app);
+        if !path.exists() {
+            return Err(LogError::NoLogFile(app.to_string()));
+        }
+        let data = std::fs::read_to_string(&path)?;
+        let mut entries: Vec<Vec<String>> = Vec::new();
+        for line in data.lines() {
+            // `[` starts a text entry, `{` a JSON-format one; anything else
+            // is a continuation of the entry above.
+            if line.starts_with(['[', '{']) || entries.is_empty() {
+                entries.push(vec![line.to_owned()]);
+            } else {
+                entries.last_mut().expect("entries non-empty").push(line.to_owned());
+            }
+        }
+        if let Some(needle) = grep {
+            entries.retain(|entry| entry.iter().any(|line| line.contains(needle)));
+        }
+        if entries.len() > n {
+            entries.drain(..entries.len() - n);
+        }
+        Ok(entries.into_iter().flatten().collect())
+    }
+
+    /// Check the app's rotation manifest against the files on disk; returns
+    /// one line per problem found (empty when the chain verifies).
+    pub fn verify(&self, app: &AppId) -> Result<Vec<String>, LogError> {
+        crate::manifest::verify(&self.log_path(app))
+    }
+
+    /// Remove every file the app left in the log directory: the live log,
+    /// rotated copies, the rotation manifest, persisted stats and collected
+    /// core dumps. Returns how many files were removed. The writer for the
+    /// app must be dropped first, or it will recreate the log on the next
+    /// line.
+    pub fn purge(&self, app: &AppId) -> Result<usize, LogError> {
+        let live = format!("{app}.log");
+        let rotated = format!("{app}.log.");
+        let manifest = format!("{app}.manifest.jsonl");
+        let stats = format!("{app}.stats.json");
+        let cores = format!("{app}.core.");
+        let mut removed = 0;
+        for entry in std::fs::read_dir(&self.base_dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else { continue };
+            if name == live
+                || name == manifest
+                || name == stats
+                || name.starts_with(&rotated)
+                || name.starts_with(&cores)
+            {
+                std::fs::remove_file(entry.path())?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Move every file the app owns in the log directory from `old` to
+    /// `new`: the live log, rotated copies, the rotation manifest, stats and
+    /// core dumps. Manifest entries reference rotated files by name, so they
+    /// are rewritten to the new prefix; hashes are over contents and still
+    /// verify afterwards.
+    pub fn rename(&self, old: &AppId, new: &AppId) -> Result<(), LogError> {
+        let live = format!("{old}.log");
+        let rotated = format!("{old}.log.");
+        let manifest = format!("{old}.manifest.jsonl");
+        let stats = format!("{old}.stats.json");
+        let cores = format!("{old}.core.");
+        for entry in std::fs::read_dir(&self.base_dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else { continue };
+            if name == live
+                || name == manifest
+                || name == stats
+                || name.starts_with(&rotated)
+                || name.starts_with(&cores)
+            {
+                let renamed = format!("{new}{}", &name[old.as_str().len()..]);
+                std::fs::rename(entry.path(), self.base_dir.join(renamed))?;
+            }
+        }
+        let manifest = self.base_dir.join(format!("{new}.manifest.jsonl"));
+        if manifest.exists() {
+            crate::manifest::rewrite_prefix(
+                &manifest,
+                &format!("{old}.log"),
+                &format!("{new}.log"),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Names of all apps that have a log file on disk, whether or not they
+    /// are currently managed.
+    pub fn list_logs(&self) -> Result<Vec<String>, LogError> {
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(&self.base_dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else { continue };
+            if let Some(app) = name.strip_suffix(".log") {
+                names.push(app.to_owned());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_manager(tag: &str) -> LogManager {
+        let dir = std::env::temp_dir().join(format!("bunctl-logs-{tag}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        LogManager::new(dir).unwrap()
+    }
+
+    #[test]
+    fn lists_orphan_logs() {
+        let mgr = temp_manager("list");
+        std::fs::write(mgr.log_path(&AppId::new("old-app")), "line\n").unwrap();
+        std::fs::write(mgr.base_dir().join("not-a-log.txt"), "x").unwrap();
+        assert_eq!(mgr.list_logs().unwrap(), vec!["old-app".to_string()]);
+        let _ = std::fs::remove_dir_all(mgr.base_dir());
+    }
+
+    #[test]
+    fn grep_returns_whole_multiline_entries() {
+        let mgr = temp_manager("grep");
+        let app = AppId::new("api");
+        std::fs::write(
+            mgr.log_path(&app),
+            "[t][stderr] Error: boom\n    at handler (app.ts:3)\n[t][stdout] listening\n",
+        )
+        .unwrap();
+        let entries = mgr.read_last_entries(&app, 10, Some("app.ts")).unwrap();
+        assert_eq!(entries, vec!["[t][stderr] Error: boom", "    at handler (app.ts:3)"]);
+        let _ = std::fs::remove_dir_all(mgr.base_dir());
+    }
+
+    #[test]
+    fn rename_moves_files_and_keeps_the_manifest_verifiable() {
+        let mgr = temp_manager("rename");
+        let old = AppId::new("api");
+        let rotated = mgr.base_dir().join("api.log.100");
+        std::fs::write(&rotated, "[t][stdout] one\n").unwrap();
+        crate::manifest::record_rotation(&mgr.log_path(&old), &rotated).unwrap();
+        std::fs::write(mgr.log_path(&old), "[t][stdout] two\n").unwrap();
+
+        let new = AppId::new("api-v2");
+        mgr.rename(&old, &new).unwrap();
+        assert!(!mgr.has_log(&old));
+        assert!(mgr.has_log(&new));
+        assert!(mgr.base_dir().join("api-v2.log.100").exists());
+        assert!(mgr.verify(&new).unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(mgr.base_dir());
+    }
+
+    #[test]
+    fn purge_removes_only_the_apps_files() {
+        let mgr = temp_manager("purge");
+        for name in [
+            "api.log",
+            "api.log.1700000000",
+            "api.manifest.jsonl",
+            "api.stats.json",
+            "api.core.1700000000",
+            "api-2.log",
+        ] {
+            std::fs::write(mgr.base_dir().join(name), "x").unwrap();
+        }
+        assert_eq!(mgr.purge(&AppId::new("api")).unwrap(), 5);
+        assert!(!mgr.has_log(&AppId::new("api")));
+        assert!(mgr.has_log(&AppId::new("api-2")));
+        let _ = std::fs::remove_dir_all(mgr.base_dir());
+    }
+
+    #[test]
+    fn reads_last_lines() {
+        let mgr = temp_manager("tail");
+        let app = AppId::new("api");
+        std::fs::write(mgr.log_path(&app), "one\ntwo\nthree\n").unwrap();
+        assert_eq!(mgr.read_last_lines(&app, 2).unwrap(), vec!["two", "three"]);
+        assert!(matches!(
+            mgr.read_last_lines(&AppId::new("missing"), 2),
+            Err(LogError::NoLogFile(_))
+        ));
+        let _ = std::fs::remove_dir_all(mgr.base_dir());
+    }
+}
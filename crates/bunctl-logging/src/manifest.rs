@@ -0,0 +1,177 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::LogError;
+
+/// One rotated log file as recorded in the app's manifest
+/// (`<app>.manifest.jsonl`, one JSON entry per line).
+///
+/// Entries chain through `prev_sha256`, so removing or reordering a rotated
+/// file is as detectable as editing one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// File name of the rotated log, relative to the log directory.
+    pub file: String,
+    /// Size in bytes at rotation time.
+    pub size: u64,
+    /// Hex SHA-256 of the whole file.
+    pub sha256: String,
+    /// Timestamp of the first entry in the file, when present.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first: Option<String>,
+    /// Timestamp of the last entry in the file, when present.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last: Option<String>,
+    /// `sha256` of the previous manifest entry; `None` on the first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_sha256: Option<String>,
+}
+
+/// Manifest path for the live log at `log_path` (`api.log` →
+/// `api.manifest.jsonl`).
+pub(crate) fn manifest_path(log_path: &Path) -> std::path::PathBuf {
+    log_path.with_extension("manifest.jsonl")
+}
+
+/// Record `rotated` (a just-rotated file of the log at `log_path`) in the
+/// manifest, chained to the previous entry.
+pub(crate) fn record_rotation(log_path: &Path, rotated: &Path) -> Result<(), LogError> {
+    let data = std::fs::read(rotated)?;
+    let manifest = manifest_path(log_path);
+    let prev_sha256 = read_entries(&manifest)
+        .unwrap_or_default()
+        .pop()
+        .map(|entry| entry.sha256);
+    let entry = ManifestEntry {
+        file: rotated
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        size: data.len() as u64,
+        sha256: sha256_hex(&data),
+        first: entry_timestamp(&data, false),
+        last: entry_timestamp(&data, true),
+        prev_sha256,
+    };
+    let line = serde_json::to_string(&entry).map_err(std::io::Error::other)?;
+    use std::io::Write;
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(manifest)?;
+    writeln!(file, "{line}")?;
+    Ok(())
+}
+
+/// Check every manifest entry against the files on disk: existence, size,
+/// SHA-256 and the `prev_sha256` chain. Returns one line per problem; an
+/// empty list means the chain verifies.
+pub fn verify(log_path: &Path) -> Result<Vec<String>, LogError> {
+    let manifest = manifest_path(log_path);
+    if !manifest.exists() {
+        return Err(LogError::NoManifest(
+            log_path.file_stem().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+        ));
+    }
+    let dir = log_path.parent().unwrap_or_else(|| Path::new("."));
+    let mut problems = Vec::new();
+    let mut prev: Option<String> = None;
+    for (idx, entry) in read_entries(&manifest)?.into_iter().enumerate() {
+        let n = idx + 1;
+        if entry.prev_sha256 != prev {
+            problems.push(format!("entry {n} ({}): broken chain to previous entry", entry.file));
+        }
+        prev = Some(entry.sha256.clone());
+        let path = dir.join(&entry.file);
+        let Ok(data) = std::fs::read(&path) else {
+            problems.push(format!("entry {n} ({}): file missing", entry.file));
+            continue;
+        };
+        if data.len() as u64 != entry.size {
+            problems.push(format!(
+                "entry {n} ({}): size changed ({} recorded, {} on disk)",
+                entry.file,
+                entry.size,
+                data.len()
+            ));
+        }
+        if sha256_hex(&data) != entry.sha256 {
+            problems.push(format!("entry {n} ({}): contents modified", entry.file));
+        }
+    }
+    Ok(problems)
+}
+
+/// Point every entry's `file` at a renamed log: `old.log.<ts>` becomes
+/// `new.log.<ts>`. Contents and hashes are untouched, so the chain still
+/// verifies against the renamed files.
+pub(crate) fn rewrite_prefix(
+    manifest: &Path,
+    old_prefix: &str,
+    new_prefix: &str,
+) -> Result<(), LogError> {
+    let mut lines = Vec::new();
+    for mut entry in read_entries(manifest)? {
+        if let Some(rest) = entry.file.strip_prefix(old_prefix) {
+            entry.file = format!("{new_prefix}{rest}");
+        }
+        lines.push(serde_json::to_string(&entry).map_err(std::io::Error::other)?);
+    }
+    std::fs::write(manifest, lines.join("\n") + "\n")?;
+    Ok(())
+}
+
+fn read_entries(manifest: &Path) -> Result<Vec<ManifestEntry>, LogError> {
+    if !manifest.exists() {
+        return Ok(Vec::new());
+    }
+    let data = std::fs::read_to_string(manifest)?;
+    let mut entries = Vec::new();
+    for line in data.lines().filter(|l| !l.trim().is_empty()) {
+        entries.push(serde_json::from_str(line).map_err(std::io::Error::other)?);
+    }
+    Ok(entries)
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    let digest = ring::digest::digest(&ring::digest::SHA256, data);
+    let mut out = String::with_capacity(64);
+    for byte in digest.as_ref() {
+        out.push_str(&format!("{byte:02x}"));
+    }
+    out
+}
+
+/// Timestamp of the first (or last) `[timestamp]`-prefixed line.
+fn entry_timestamp(data: &[u8], last: bool) -> Option<String> {
+    let text = String::from_utf8_lossy(data);
+    let mut lines = text.lines().filter(|l| l.starts_with('['));
+    let line = if last { lines.next_back() } else { lines.next() }?;
+    let end = line.find(']')?;
+    Some(line[1..end].to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_detects_modification_and_broken_chain() {
+        let dir = std::env::temp_dir().join(format!("bunctl-manifest-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let log = dir.join("api.log");
+
+        let first = dir.join("api.log.100");
+        std::fs::write(&first, "[t1][stdout] one\n").unwrap();
+        record_rotation(&log, &first).unwrap();
+        let second = dir.join("api.log.200");
+        std::fs::write(&second, "[t2][stdout] two\n").unwrap();
+        record_rotation(&log, &second).unwrap();
+        assert!(verify(&log).unwrap().is_empty());
+
+        std::fs::write(&second, "[t2][stdout] edited\n").unwrap();
+        let problems = verify(&log).unwrap();
+        assert!(problems.iter().any(|p| p.contains("contents modified")));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,67 @@
+//! Background queue for post-rotation work.
+//!
+//! Hashing a just-rotated file for the manifest is proportional to its size
+//! and has no business running inline in the writer, where it would stall
+//! every subsequent captured line. Writers hand rotated files to a
+//! [`RotationQueue`] instead; one worker drains jobs strictly one at a time
+//! on the blocking pool, which also guarantees at most one job per app is
+//! ever in flight.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// One rotated log file awaiting manifest recording.
+#[derive(Debug)]
+pub struct RotationJob {
+    /// The live log the file was rotated from.
+    pub log_path: PathBuf,
+    /// The rotated file itself.
+    pub rotated: PathBuf,
+}
+
+/// Handle to the background worker; cheap to clone.
+#[derive(Debug, Clone)]
+pub struct RotationQueue {
+    tx: tokio::sync::mpsc::UnboundedSender<RotationJob>,
+    busy_ms: Arc<AtomicU64>,
+}
+
+impl RotationQueue {
+    /// Spawn the worker task on the current runtime.
+    pub fn spawn() -> Self {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<RotationJob>();
+        let busy_ms = Arc::new(AtomicU64::new(0));
+        let busy = busy_ms.clone();
+        tokio::spawn(async move {
+            while let Some(job) = rx.recv().await {
+                let started = std::time::Instant::now();
+                let label = job.rotated.display().to_string();
+                let result = tokio::task::spawn_blocking(move || {
+                    crate::manifest::record_rotation(&job.log_path, &job.rotated)
+                })
+                .await;
+                let elapsed = started.elapsed();
+                busy.fetch_add(elapsed.as_millis() as u64, Ordering::Relaxed);
+                match result {
+                    Ok(Ok(())) => {
+                        tracing::debug!(file = %label, ms = elapsed.as_millis() as u64, "recorded rotated log in manifest");
+                    }
+                    Ok(Err(err)) => tracing::warn!(file = %label, "manifest update failed: {err}"),
+                    Err(err) => tracing::warn!(file = %label, "manifest task panicked: {err}"),
+                }
+            }
+        });
+        Self { tx, busy_ms }
+    }
+
+    /// Sender handed to writers (see [`crate::LogWriter::set_rotation_queue`]).
+    pub fn sender(&self) -> tokio::sync::mpsc::UnboundedSender<RotationJob> {
+        self.tx.clone()
+    }
+
+    /// Total time spent on post-rotation work so far, in milliseconds.
+    pub fn busy_millis(&self) -> u64 {
+        self.busy_ms.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,444 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use std::sync::Arc;
+
+use bunctl_core::config::{LogFilter, LogFormat};
+use bunctl_core::metrics::LogMetrics;
+use bunctl_core::time;
+use bunctl_core::LogStream;
+
+use crate::LogError;
+
+/// Append-only writer for one app's log file.
+///
+/// Lines are prefixed with a UTC timestamp and the source stream:
+/// `[2026-01-31T07:04:05Z][stderr] message`.
+#[derive(Debug)]
+pub struct LogWriter {
+    file: std::fs::File,
+    path: PathBuf,
+    dedup: bool,
+    last: Option<(LogStream, String)>,
+    repeats: u64,
+    multiline: bool,
+    continuation: Option<String>,
+    wrote_entry: bool,
+    strip_ansi: bool,
+    max_size: Option<u64>,
+    manifest: bool,
+    size: u64,
+    format: LogFormat,
+    filters: Vec<FilterState>,
+    filter_metrics: Arc<std::sync::Mutex<LogMetrics>>,
+    rotation_tx: Option<tokio::sync::mpsc::UnboundedSender<crate::rotation::RotationJob>>,
+}
+
+/// A configured filter plus its running match count (for 1-in-N sampling).
+#[derive(Debug)]
+struct FilterState {
+    filter: LogFilter,
+    seen: u64,
+}
+
+impl LogWriter {
+    pub(crate) fn open(path: PathBuf) -> Result<Self, LogError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self {
+            file,
+            path,
+            dedup: false,
+            last: None,
+            repeats: 0,
+            multiline: false,
+            continuation: None,
+            wrote_entry: false,
+            strip_ansi: true,
+            max_size: None,
+            manifest: false,
+            size,
+            format: LogFormat::Text,
+            filters: Vec::new(),
+            filter_metrics: Arc::default(),
+            rotation_tx: None,
+        })
+    }
+
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// Collapse consecutive identical lines into a single
+    /// `last message repeated N times` entry (the app's `log_dedup` option).
+    pub fn set_dedup(&mut self, enabled: bool) {
+        self.dedup = enabled;
+    }
+
+    /// Attach continuation lines (indented, or matching the optional `*`
+    /// wildcard pattern) to the previous entry instead of starting a new
+    /// timestamped one, so stack traces stay in one piece (the app's
+    /// `log_multiline` / `log_continuation` options).
+    pub fn set_multiline(&mut self, enabled: bool, pattern: Option<String>) {
+        self.multiline = enabled;
+        self.continuation = pattern;
+    }
+
+    /// Keep ANSI escape sequences in the file instead of stripping them
+    /// (the app's `strip_ansi: false` option).
+    pub fn set_strip_ansi(&mut self, enabled: bool) {
+        self.strip_ansi = enabled;
+    }
+
+    /// Rotate the file once it grows past `max_size` bytes; with `manifest`
+    /// set, each rotated file is recorded in the app's tamper-evident
+    /// manifest (the `log_max_size` / `log_manifest` options).
+    pub fn set_rotation(&mut self, max_size: Option<u64>, manifest: bool) {
+        self.max_size = max_size;
+        self.manifest = manifest;
+    }
+
+    /// Offload post-rotation manifest work (reading and hashing the rotated
+    /// file) to a [`crate::rotation::RotationQueue`] instead of doing it
+    /// inline, so large rotations do not stall captured writes.
+    pub fn set_rotation_queue(
+        &mut self,
+        tx: tokio::sync::mpsc::UnboundedSender<crate::rotation::RotationJob>,
+    ) {
+        self.rotation_tx = Some(tx);
+    }
+
+    /// Write entries in the given on-disk format (the app's `log_format`
+    /// option). Multiline grouping only applies to the text format.
+    pub fn set_format(&mut self, format: LogFormat) {
+        self.format = format;
+    }
+
+    /// Install capture-time filters (the app's `log_filters` option).
+    /// Counters of dropped/sampled lines accumulate in `metrics`, which the
+    /// daemon shares so status can report them.
+    pub fn set_filters(
+        &mut self,
+        filters: Vec<LogFilter>,
+        metrics: Arc<std::sync::Mutex<LogMetrics>>,
+    ) {
+        self.filters = filters.into_iter().map(|filter| FilterState { filter, seen: 0 }).collect();
+        self.filter_metrics = metrics;
+    }
+
+    /// Append one captured line.
+    pub fn write_line(&mut self, stream: LogStream, line: &str) -> Result<(), LogError> {
+        let stripped;
+        let line = if self.strip_ansi && line.contains('\x1b') {
+            stripped = strip_ansi(line);
+            stripped.as_str()
+        } else {
+            line
+        };
+        if !self.filters.is_empty() && self.filtered_out(line) {
+            return Ok(());
+        }
+        if self.format == LogFormat::Text
+            && self.multiline
+            && self.wrote_entry
+            && self.is_continuation(line)
+        {
+            // Part of the previous entry: no timestamp prefix, and it does
+            // not participate in dedup (the entry header already did).
+            self.flush_repeats()?;
+            self.last = None;
+            writeln!(self.file, "{line}")?;
+            self.size += line.len() as u64 + 1;
+            return Ok(());
+        }
+        // Rotate only between entries so grouped traces stay in one file.
+        if self.max_size.is_some_and(|max| self.size >= max) {
+            self.rotate()?;
+        }
+        if self.dedup {
+            if let Some((last_stream, last_line)) = &self.last {
+                if *last_stream == stream && last_line == line {
+                    self.repeats += 1;
+                    return Ok(());
+                }
+            }
+            self.flush_repeats()?;
+            self.last = Some((stream, line.to_owned()));
+        }
+        self.write_raw(stream, line)
+    }
+
+    /// Flush buffered output; a pending repeat count stays open so an
+    /// ongoing run of identical lines keeps collapsing across flushes.
+    pub fn flush(&mut self) -> Result<(), LogError> {
+        self.file.flush()?;
+        Ok(())
+    }
+
+    fn write_raw(&mut self, stream: LogStream, line: &str) -> Result<(), LogError> {
+        let stream = match stream {
+            LogStream::Stdout => "stdout",
+            LogStream::Stderr => "stderr",
+        };
+        let ts = time::rfc3339(time::unix_now());
+        let entry = match self.format {
+            LogFormat::Text => format!("[{ts}][{stream}] {line}\n"),
+            LogFormat::Json => {
+                // Apps already emitting JSON objects pass through as a
+                // nested payload instead of a re-quoted string.
+                let mut obj = serde_json::json!({ "ts": ts, "stream": stream });
+                match serde_json::from_str::<serde_json::Value>(line) {
+                    Ok(payload) if payload.is_object() => obj["payload"] = payload,
+                    _ => obj["msg"] = serde_json::Value::String(line.to_owned()),
+                }
+                format!("{obj}\n")
+            }
+        };
+        self.file.write_all(entry.as_bytes())?;
+        self.size += entry.len() as u64;
+        self.wrote_entry = true;
+        Ok(())
+    }
+
+    /// Move the current file aside under a timestamped name, reopen a fresh
+    /// one and, when enabled, record the rotated file in the manifest.
+    fn rotate(&mut self) -> Result<(), LogError> {
+        self.flush_repeats()?;
+        self.file.flush()?;
+        let mut rotated = PathBuf::from(format!("{}.{}", self.path.display(), time::unix_now()));
+        let mut n = 1;
+        while rotated.exists() {
+            rotated = PathBuf::from(format!("{}.{}-{n}", self.path.display(), time::unix_now()));
+            n += 1;
+        }
+        std::fs::rename(&self.path, &rotated)?;
+        self.file = std::fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.size = 0;
+        self.last = None;
+        self.wrote_entry = false;
+        if self.manifest {
+            match &self.rotation_tx {
+                Some(tx) => {
+                    let _ = tx.send(crate::rotation::RotationJob {
+                        log_path: self.path.clone(),
+                        rotated,
+                    });
+                }
+                None => crate::manifest::record_rotation(&self.path, &rotated)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the first matching filter removes this line.
+    fn filtered_out(&mut self, line: &str) -> bool {
+        for state in &mut self.filters {
+            if !wildcard_match(&state.filter.pattern, line) {
+                continue;
+            }
+            let mut metrics = self.filter_metrics.lock().expect("log metrics poisoned");
+            return match state.filter.sample {
+                // `sample: 1` keeps everything; absent or 0 drops all.
+                Some(1) => false,
+                Some(n) if n > 1 => {
+                    state.seen += 1;
+                    if (state.seen - 1) % u64::from(n) == 0 {
+                        false
+                    } else {
+                        metrics.sampled += 1;
+                        true
+                    }
+                }
+                _ => {
+                    metrics.dropped += 1;
+                    true
+                }
+            };
+        }
+        false
+    }
+
+    fn is_continuation(&self, line: &str) -> bool {
+        if line.starts_with([' ', '\t']) {
+            return true;
+        }
+        self.continuation.as_deref().is_some_and(|pattern| wildcard_match(pattern, line))
+    }
+
+    fn flush_repeats(&mut self) -> Result<(), LogError> {
+        if self.repeats > 0 {
+            let count = self.repeats;
+            self.repeats = 0;
+            if let Some((stream, _)) = self.last {
+                let line = format!("last message repeated {count} times");
+                self.write_raw(stream, &line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Remove ANSI escape sequences: CSI (`ESC [ … final-byte`), OSC
+/// (`ESC ] … BEL`/`ESC \`) and the remaining two-byte `ESC x` forms.
+fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        match chars.peek() {
+            Some('[') => {
+                chars.next();
+                // Parameter and intermediate bytes run up to the final
+                // byte in `@`..=`~`.
+                for c in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                chars.next();
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            Some(_) => {
+                chars.next();
+            }
+            None => {}
+        }
+    }
+    out
+}
+
+/// `*`-wildcard match of `pattern` against a whole line (same dialect as
+/// config `include` globs).
+fn wildcard_match(pattern: &str, line: &str) -> bool {
+    let segments: Vec<&str> = pattern.split('*').collect();
+    let mut rest = line;
+    let last = segments.len() - 1;
+    for (idx, seg) in segments.iter().enumerate() {
+        if seg.is_empty() {
+            continue;
+        }
+        if idx == 0 {
+            match rest.strip_prefix(seg) {
+                Some(r) => rest = r,
+                None => return false,
+            }
+        } else if idx == last {
+            return rest.ends_with(seg);
+        } else {
+            match rest.find(seg) {
+                Some(pos) => rest = &rest[pos + seg.len()..],
+                None => return false,
+            }
+        }
+    }
+    true
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        // Record a run of identical lines still being collapsed when the
+        // app exits; best effort, like any other write at teardown.
+        let _ = self.flush_repeats();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_ansi_sequences() {
+        assert_eq!(strip_ansi("\x1b[31merror\x1b[0m: boom"), "error: boom");
+        assert_eq!(strip_ansi("\x1b]0;title\x07plain"), "plain");
+        assert_eq!(strip_ansi("no escapes"), "no escapes");
+    }
+
+    #[test]
+    fn filters_drop_and_sample_with_counters() {
+        let path = std::env::temp_dir()
+            .join(format!("bunctl-filter-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let metrics = Arc::new(std::sync::Mutex::new(LogMetrics::default()));
+        {
+            let mut writer = LogWriter::open(path.clone()).unwrap();
+            writer.set_filters(
+                vec![
+                    LogFilter { pattern: "*DEBUG*".into(), sample: None },
+                    LogFilter { pattern: "*poll*".into(), sample: Some(3) },
+                ],
+                metrics.clone(),
+            );
+            writer.write_line(LogStream::Stdout, "DEBUG noisy").unwrap();
+            for _ in 0..6 {
+                writer.write_line(LogStream::Stdout, "poll tick").unwrap();
+            }
+            writer.write_line(LogStream::Stdout, "kept").unwrap();
+        }
+        let data = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(data.matches("poll tick").count(), 2);
+        assert!(!data.contains("DEBUG"));
+        assert!(data.contains("kept"));
+        let counters = *metrics.lock().unwrap();
+        assert_eq!(counters.dropped, 1);
+        assert_eq!(counters.sampled, 4);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn json_format_embeds_json_lines_as_payload() {
+        let path = std::env::temp_dir()
+            .join(format!("bunctl-jsonlog-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        {
+            let mut writer = LogWriter::open(path.clone()).unwrap();
+            writer.set_format(LogFormat::Json);
+            writer.write_line(LogStream::Stdout, r#"{"level":"info","msg":"up"}"#).unwrap();
+            writer.write_line(LogStream::Stderr, "plain text").unwrap();
+        }
+        let data = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> =
+            data.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines[0]["payload"]["level"], "info");
+        assert_eq!(lines[1]["msg"], "plain text");
+        assert_eq!(lines[1]["stream"], "stderr");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn collapses_repeated_lines() {
+        let path = std::env::temp_dir()
+            .join(format!("bunctl-dedup-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        {
+            let mut writer = LogWriter::open(path.clone()).unwrap();
+            writer.set_dedup(true);
+            for _ in 0..5 {
+                writer.write_line(LogStream::Stdout, "same error").unwrap();
+            }
+            writer.write_line(LogStream::Stdout, "different").unwrap();
+        }
+        let data = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = data.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with("same error"));
+        assert!(lines[1].ends_with("last message repeated 4 times"));
+        assert!(lines[2].ends_with("different"));
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,243 @@
+//! Embedded on-disk time-series store for per-app resource metrics.
+//!
+//! Storage is deliberately simple: one directory per app containing
+//! append-only segment files of JSON lines, named by the Unix timestamp of
+//! their first sample (`<base>/<app>/<start_ts>.jsonl`). A segment is rolled
+//! once it grows past [`SEGMENT_MAX_BYTES`]; the oldest segments are pruned
+//! beyond [`MAX_SEGMENTS`]. That is enough to answer "what did this app do
+//! overnight" without an external database.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use bunctl_core::metrics::MetricSample;
+use bunctl_core::AppId;
+use thiserror::Error;
+
+/// Roll a segment file after it exceeds this size.
+pub const SEGMENT_MAX_BYTES: u64 = 1024 * 1024;
+
+/// Keep at most this many segments per app.
+pub const MAX_SEGMENTS: usize = 16;
+
+#[derive(Debug, Error)]
+pub enum MetricsError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// Append-only metrics store rooted at one directory.
+#[derive(Debug, Clone)]
+pub struct MetricsStore {
+    base_dir: PathBuf,
+}
+
+impl MetricsStore {
+    pub fn new(base_dir: PathBuf) -> Result<Self, MetricsError> {
+        fs::create_dir_all(&base_dir)?;
+        Ok(Self { base_dir })
+    }
+
+    pub fn base_dir(&self) -> &Path {
+        &self.base_dir
+    }
+
+    fn app_dir(&self, app: &AppId) -> PathBuf {
+        self.base_dir.join(app.as_str())
+    }
+
+    /// Append one sample to the app's current segment, rolling and pruning
+    /// as needed.
+    pub fn append(&self, app: &AppId, sample: &MetricSample) -> Result<(), MetricsError> {
+        let dir = self.app_dir(app);
+        fs::create_dir_all(&dir)?;
+        let mut segments = list_segments(&dir)?;
+
+        let current = match segments.last() {
+            Some(seg) if fs::metadata(seg)?.len() < SEGMENT_MAX_BYTES => seg.clone(),
+            _ => {
+                let seg = dir.join(format!("{}.jsonl", sample.ts));
+                segments.push(seg.clone());
+                while segments.len() > MAX_SEGMENTS {
+                    let old = segments.remove(0);
+                    if let Err(err) = fs::remove_file(&old) {
+                        tracing::warn!("cannot prune metrics segment {}: {err}", old.display());
+                    }
+                }
+                seg
+            }
+        };
+
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(current)?;
+        let line = serde_json::to_string(sample)?;
+        writeln!(file, "{line}")?;
+        Ok(())
+    }
+
+    /// All samples for `app` with `ts >= since`, oldest first. An app with
+    /// no recorded metrics yields an empty vec.
+    pub fn query(&self, app: &AppId, since: u64) -> Result<Vec<MetricSample>, MetricsError> {
+        let dir = self.app_dir(app);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut samples = Vec::new();
+        for seg in relevant_segments(&list_segments(&dir)?, since) {
+            let data = fs::read_to_string(&seg)?;
+            for line in data.lines() {
+                match serde_json::from_str::<MetricSample>(line) {
+                    Ok(sample) if sample.ts >= since => samples.push(sample),
+                    Ok(_) => {}
+                    Err(err) => {
+                        // A torn write at the tail of a segment (crash) is
+                        // expected; skip the line rather than fail the query.
+                        tracing::debug!("skipping bad metrics line in {}: {err}", seg.display());
+                    }
+                }
+            }
+        }
+        Ok(samples)
+    }
+
+    /// Move `old`'s recorded segments to `new`'s directory. An app with no
+    /// metrics directory is a no-op.
+    pub fn rename(&self, old: &AppId, new: &AppId) -> Result<(), MetricsError> {
+        let from = self.app_dir(old);
+        if from.exists() {
+            fs::rename(from, self.app_dir(new))?;
+        }
+        Ok(())
+    }
+
+    /// Remove every recorded segment for `app`. An app with no metrics
+    /// directory is a no-op.
+    pub fn purge(&self, app: &AppId) -> Result<(), MetricsError> {
+        let dir = self.app_dir(app);
+        if dir.exists() {
+            fs::remove_dir_all(dir)?;
+        }
+        Ok(())
+    }
+}
+
+/// Segment files in a directory, sorted by their start timestamp.
+fn list_segments(dir: &Path) -> Result<Vec<PathBuf>, MetricsError> {
+    let mut segments: Vec<(u64, PathBuf)> = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else { continue };
+        if path.extension().is_some_and(|e| e == "jsonl") {
+            if let Ok(ts) = stem.parse::<u64>() {
+                segments.push((ts, path));
+            }
+        }
+    }
+    segments.sort_by_key(|(ts, _)| *ts);
+    Ok(segments.into_iter().map(|(_, p)| p).collect())
+}
+
+/// Skip segments that end before `since`: a segment can be ruled out when
+/// the *next* segment also starts before `since`.
+fn relevant_segments(segments: &[PathBuf], since: u64) -> Vec<PathBuf> {
+    let starts: Vec<u64> = segments
+        .iter()
+        .filter_map(|p| p.file_stem()?.to_str()?.parse().ok())
+        .collect();
+    segments
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| match starts.get(i + 1) {
+            Some(&next_start) => next_start > since,
+            None => true,
+        })
+        .map(|(_, p)| p.clone())
+        .collect()
+}
+
+/// Per-user default metrics directory, next to the log directory.
+pub fn default_metrics_dir() -> PathBuf {
+    #[cfg(unix)]
+    {
+        // A root daemon is the system instance and keeps state under /var.
+        if is_root() {
+            return PathBuf::from("/var/lib/bunctl/metrics");
+        }
+    }
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(dir) = std::env::var_os("XDG_STATE_HOME").filter(|d| !d.is_empty()) {
+            return PathBuf::from(dir).join("bunctl").join("metrics");
+        }
+        home().join(".local/state/bunctl/metrics")
+    }
+    #[cfg(target_os = "macos")]
+    {
+        home().join("Library/Application Support/bunctl/metrics")
+    }
+    #[cfg(windows)]
+    {
+        match std::env::var_os("LOCALAPPDATA") {
+            Some(dir) => PathBuf::from(dir).join("bunctl").join("metrics"),
+            None => PathBuf::from(r"C:\bunctl\metrics"),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn home() -> PathBuf {
+    std::env::var_os("HOME").map(PathBuf::from).unwrap_or_else(|| PathBuf::from("/tmp"))
+}
+
+#[cfg(unix)]
+fn is_root() -> bool {
+    extern "C" {
+        fn geteuid() -> u32;
+    }
+    unsafe { geteuid() == 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store(tag: &str) -> MetricsStore {
+        let dir = std::env::temp_dir().join(format!("bunctl-metrics-{tag}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        MetricsStore::new(dir).unwrap()
+    }
+
+    fn sample(ts: u64) -> MetricSample {
+        MetricSample { ts, cpu_percent: 1.5, memory_bytes: 1024, restarts: 0 }
+    }
+
+    #[test]
+    fn appends_and_queries_by_time() {
+        let store = temp_store("query");
+        let app = AppId::new("api");
+        for ts in [100, 200, 300] {
+            store.append(&app, &sample(ts)).unwrap();
+        }
+        let all = store.query(&app, 0).unwrap();
+        assert_eq!(all.len(), 3);
+        let recent = store.query(&app, 200).unwrap();
+        assert_eq!(recent.iter().map(|s| s.ts).collect::<Vec<_>>(), vec![200, 300]);
+        assert!(store.query(&AppId::new("other"), 0).unwrap().is_empty());
+        let _ = fs::remove_dir_all(store.base_dir());
+    }
+
+    #[test]
+    fn survives_torn_tail_lines() {
+        let store = temp_store("torn");
+        let app = AppId::new("api");
+        store.append(&app, &sample(100)).unwrap();
+        let seg = store.base_dir().join("api").join("100.jsonl");
+        let mut file = fs::OpenOptions::new().append(true).open(&seg).unwrap();
+        file.write_all(b"{\"ts\":200,\"cpu_p").unwrap();
+        assert_eq!(store.query(&app, 0).unwrap().len(), 1);
+        let _ = fs::remove_dir_all(store.base_dir());
+    }
+}
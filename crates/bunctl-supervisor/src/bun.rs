@@ -0,0 +1,135 @@
+//! Per-app Bun version management.
+//!
+//! Apps can pin a runtime with `bun_version`; binaries live in a per-user
+//! versions directory, one subdirectory per version. Missing versions are
+//! downloaded on demand from the official release archive (via `curl` and
+//! `unzip`, the same tools Bun's own installer uses).
+
+use std::path::PathBuf;
+
+use crate::SupervisorError;
+
+/// Per-user directory holding pinned Bun binaries, one
+/// `<version>/bun` per version. Override with `BUNCTL_BUN_VERSIONS`.
+///
+/// * Linux: `$XDG_DATA_HOME/bunctl/bun` or `~/.local/share/bunctl/bun`
+/// * macOS: `~/Library/Application Support/bunctl/bun`
+/// * Windows: `%LOCALAPPDATA%\bunctl\bun`
+pub fn versions_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("BUNCTL_BUN_VERSIONS").filter(|d| !d.is_empty()) {
+        return PathBuf::from(dir);
+    }
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(dir) = std::env::var_os("XDG_DATA_HOME").filter(|d| !d.is_empty()) {
+            return PathBuf::from(dir).join("bunctl").join("bun");
+        }
+        home().join(".local/share/bunctl/bun")
+    }
+    #[cfg(target_os = "macos")]
+    {
+        home().join("Library/Application Support/bunctl/bun")
+    }
+    #[cfg(windows)]
+    {
+        match std::env::var_os("LOCALAPPDATA") {
+            Some(dir) => PathBuf::from(dir).join("bunctl").join("bun"),
+            None => PathBuf::from(r"C:\bunctl\bun"),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn home() -> PathBuf {
+    std::env::var_os("HOME").map(PathBuf::from).unwrap_or_else(|| PathBuf::from("/tmp"))
+}
+
+/// Where the binary for `version` lives (whether or not it is installed).
+pub fn binary_path(version: &str) -> PathBuf {
+    let binary = if cfg!(windows) { "bun.exe" } else { "bun" };
+    versions_dir().join(version).join(binary)
+}
+
+/// Release-archive platform slug for the running host.
+fn platform_slug() -> &'static str {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("linux", "aarch64") => "linux-aarch64",
+        ("linux", _) => "linux-x64",
+        ("macos", "aarch64") => "darwin-aarch64",
+        ("macos", _) => "darwin-x64",
+        _ => "windows-x64",
+    }
+}
+
+/// Make sure the binary for `version` is installed, downloading it from the
+/// official releases when missing, and return its path.
+pub async fn ensure(version: &str) -> Result<PathBuf, SupervisorError> {
+    let version = version.trim_start_matches('v');
+    let path = binary_path(version);
+    if path.exists() {
+        return Ok(path);
+    }
+    let slug = platform_slug();
+    let url = format!(
+        "https://github.com/oven-sh/bun/releases/download/bun-v{version}/bun-{slug}.zip"
+    );
+    tracing::info!(%version, "downloading bun from {url}");
+    let dir = versions_dir().join(version);
+    std::fs::create_dir_all(&dir)?;
+    let archive = dir.join("bun.zip");
+    run_tool("curl", &["-fsSL", "-o", &archive.display().to_string(), &url], version).await?;
+    // The archive contains a single `bun-<slug>/bun`; flatten it.
+    run_tool(
+        "unzip",
+        &["-oqj", &archive.display().to_string(), "-d", &dir.display().to_string()],
+        version,
+    )
+    .await?;
+    let _ = std::fs::remove_file(&archive);
+    if !path.exists() {
+        return Err(SupervisorError::BunDownload {
+            version: version.to_owned(),
+            message: format!("archive did not contain the expected binary at {}", path.display()),
+        });
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(path)
+}
+
+async fn run_tool(tool: &str, args: &[&str], version: &str) -> Result<(), SupervisorError> {
+    let status = tokio::process::Command::new(tool)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .await
+        .map_err(|err| SupervisorError::BunDownload {
+            version: version.to_owned(),
+            message: format!("cannot run {tool}: {err}"),
+        })?;
+    if !status.success() {
+        return Err(SupervisorError::BunDownload {
+            version: version.to_owned(),
+            message: format!("{tool} exited with {status}"),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binary_path_honors_override() {
+        std::env::set_var("BUNCTL_BUN_VERSIONS", "/tmp/bunctl-versions-test");
+        let path = binary_path("1.1.0");
+        std::env::remove_var("BUNCTL_BUN_VERSIONS");
+        assert!(path.starts_with("/tmp/bunctl-versions-test/1.1.0"));
+    }
+}
@@ -0,0 +1,1012 @@
+//! Process spawning, supervision and inspection.
+//!
+//! The platform-specific parts (signalling, `/proc` style inspection) live
+//! in per-OS modules; the daemon only uses the platform-neutral functions
+//! exported here.
+
+pub mod bun;
+pub mod secrets;
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(windows)]
+mod windows;
+
+use std::process::Stdio;
+use std::time::Duration;
+
+use bunctl_core::config::ExecKind;
+use bunctl_core::AppConfig;
+use thiserror::Error;
+use tokio::process::{Child, Command};
+
+#[derive(Debug, Error)]
+pub enum SupervisorError {
+    #[error("failed to spawn '{command}': {source}")]
+    Spawn { command: String, source: std::io::Error },
+
+    #[error("cannot install bun {version}: {message}")]
+    BunDownload { version: String, message: String },
+
+    #[error("cannot resolve secret {key}: {message}")]
+    Secret { key: String, message: String },
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Point-in-time information about a running process.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    pub command: String,
+    pub memory_bytes: Option<u64>,
+    pub cpu_percent: Option<f64>,
+    pub threads: Option<u32>,
+    pub open_files: Option<u32>,
+}
+
+/// Spawn the app's process with piped stdout/stderr. For
+/// `exec_kind: container` the supervised child is a foreground
+/// `docker run` (or equivalent), which proxies output and signals to the
+/// container, so the rest of the stack needs no special cases.
+pub fn spawn(config: &AppConfig) -> Result<Child, SupervisorError> {
+    if config.bun_version.is_some() && config.exec_kind == ExecKind::Process {
+        let program = config.interpreter.as_deref().unwrap_or(&config.command);
+        if pinned_bun(config, program).is_none() {
+            tracing::warn!(
+                app = %config.name,
+                %program,
+                "bun_version is set but the program is not bun; ignoring"
+            );
+        }
+    }
+    let argv = argv(config);
+    // A run_as app is started through a relay that performs the logon; the
+    // password reaches it via the environment, not the command line.
+    #[cfg(windows)]
+    let argv = match &config.run_as {
+        Some(run_as) => windows::run_as_argv(&argv, run_as),
+        None => argv,
+    };
+    #[cfg(not(windows))]
+    if config.run_as.is_some() {
+        tracing::warn!(app = %config.name, "run_as is Windows-only; ignoring");
+    }
+    let mut cmd = Command::new(&argv[0]);
+    cmd.args(&argv[1..]);
+    // Containers get the same treatment: the runtime inherits the values
+    // and forwards them for the value-less `-e KEY` flags in the argv, so
+    // resolved secrets never appear on a command line.
+    cmd.envs(&config.env);
+    #[cfg(windows)]
+    if let Some(pw) = config.run_as.as_ref().and_then(|r| r.resolved_password.as_deref()) {
+        cmd.env("BUNCTL_RUNAS_PW", pw);
+    }
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(cwd) = &config.cwd {
+        cmd.current_dir(cwd);
+    }
+    if config.private_network {
+        #[cfg(target_os = "linux")]
+        unsafe {
+            cmd.pre_exec(linux::isolate_network_impl);
+        }
+        #[cfg(not(target_os = "linux"))]
+        tracing::warn!(app = %config.name, "private_network is Linux-only; ignoring");
+    }
+    #[cfg(unix)]
+    {
+        // Put each app in its own process group so signals can be scoped to
+        // it later without hitting the daemon.
+        cmd.process_group(0);
+        if config.core_dumps {
+            // Lift RLIMIT_CORE in the child so a crash leaves a dump to
+            // collect (see [`find_core_dump`]).
+            unsafe {
+                cmd.pre_exec(|| {
+                    let limit = libc::rlimit {
+                        rlim_cur: libc::RLIM_INFINITY,
+                        rlim_max: libc::RLIM_INFINITY,
+                    };
+                    libc::setrlimit(libc::RLIMIT_CORE, &limit);
+                    Ok(())
+                });
+            }
+        }
+    }
+    cmd.spawn().map_err(|source| SupervisorError::Spawn {
+        command: config.command.clone(),
+        source,
+    })
+}
+
+/// The fully resolved argv [`spawn`] executes for this config: the program
+/// (a pinned Bun binary where applicable), interpreter flags, command and
+/// args — or, for `exec_kind: container`, the complete runtime invocation.
+pub fn argv(config: &AppConfig) -> Vec<String> {
+    match config.exec_kind {
+        ExecKind::Process => {
+            // With an interpreter the spawned program is the interpreter and
+            // `command` becomes its script argument; no string concatenation.
+            let program = config.interpreter.as_deref().unwrap_or(&config.command);
+            let mut argv = vec![match pinned_bun(config, program) {
+                Some(path) => path.display().to_string(),
+                None => program.to_owned(),
+            }];
+            if config.interpreter.is_some() {
+                argv.extend(config.interpreter_args.iter().cloned());
+                argv.push(config.command.clone());
+            }
+            argv.extend(config.args.iter().cloned());
+            argv
+        }
+        ExecKind::Container => container_argv(config),
+    }
+}
+
+/// The pinned Bun binary to use instead of `program` (the command or the
+/// interpreter), when `bun_version` is set and the program actually is `bun`.
+fn pinned_bun(config: &AppConfig, program: &str) -> Option<std::path::PathBuf> {
+    let version = config.bun_version.as_deref()?;
+    let is_bun = std::path::Path::new(program)
+        .file_stem()
+        .is_some_and(|stem| stem == "bun");
+    if !is_bun {
+        return None;
+    }
+    Some(bun::binary_path(version.trim_start_matches('v')))
+}
+
+/// Why a program cannot be spawned, as diagnosed by [`check_command`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandProblem {
+    /// Nothing by this name exists in PATH (or at the given path). When a
+    /// PATH entry is a near-miss, it is offered as a did-you-mean candidate.
+    NotFound { suggestion: Option<String> },
+    /// The file exists but lacks execute permission.
+    NotExecutable { path: std::path::PathBuf },
+}
+
+/// Check that `program` can actually be executed, resolving it the way
+/// spawn will: a name with a path separator against `cwd`, a bare name
+/// against PATH. Returns the resolved path, or what is wrong with it, so
+/// callers can fail fast instead of letting the spawn-and-backoff loop
+/// churn on a typo.
+pub fn check_command(
+    program: &str,
+    cwd: Option<&std::path::Path>,
+) -> Result<std::path::PathBuf, CommandProblem> {
+    let path = std::path::Path::new(program);
+    if path.is_absolute() || path.components().count() > 1 {
+        let full = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            cwd.unwrap_or(std::path::Path::new(".")).join(path)
+        };
+        for candidate in with_extensions(&full) {
+            if is_executable(&candidate) {
+                return Ok(candidate);
+            }
+        }
+        if full.is_file() {
+            return Err(CommandProblem::NotExecutable { path: full });
+        }
+        return Err(CommandProblem::NotFound { suggestion: None });
+    }
+    let mut found_unexecutable = None;
+    if let Some(path_var) = std::env::var_os("PATH") {
+        for dir in std::env::split_paths(&path_var) {
+            for candidate in with_extensions(&dir.join(program)) {
+                if is_executable(&candidate) {
+                    return Ok(candidate);
+                }
+                if candidate.is_file() {
+                    found_unexecutable.get_or_insert(candidate);
+                }
+            }
+        }
+    }
+    match found_unexecutable {
+        Some(path) => Err(CommandProblem::NotExecutable { path }),
+        None => Err(CommandProblem::NotFound { suggestion: suggest_command(program) }),
+    }
+}
+
+/// The closest executable name in PATH to a command that did not resolve
+/// (edit distance of at most 2), for a did-you-mean hint.
+fn suggest_command(program: &str) -> Option<String> {
+    let path_var = std::env::var_os("PATH")?;
+    let mut best: Option<(usize, String)> = None;
+    for dir in std::env::split_paths(&path_var) {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else { continue };
+            if stem.len().abs_diff(program.len()) > 2 || !is_executable(&path) {
+                continue;
+            }
+            let distance = edit_distance(program, stem);
+            if distance == 0 || distance > 2 {
+                continue;
+            }
+            if best.as_ref().is_none_or(|(d, _)| distance < *d) {
+                best = Some((distance, stem.to_owned()));
+            }
+        }
+    }
+    best.map(|(_, name)| name)
+}
+
+/// Levenshtein distance between two short names.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let cost = if ca == cb { prev } else { prev + 1 };
+            prev = row[j + 1];
+            row[j + 1] = cost.min(prev + 1).min(row[j] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Whether the path is a file we could actually exec.
+fn is_executable(path: &std::path::Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::metadata(path)
+            .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+/// Windows also accepts `name.exe`/`.cmd`/`.bat` for an extensionless
+/// `name`; elsewhere the path stands alone.
+fn with_extensions(path: &std::path::Path) -> Vec<std::path::PathBuf> {
+    #[cfg(windows)]
+    {
+        let mut all = vec![path.to_path_buf()];
+        if path.extension().is_none() {
+            for ext in ["exe", "cmd", "bat"] {
+                all.push(path.with_extension(ext));
+            }
+        }
+        all
+    }
+    #[cfg(not(windows))]
+    {
+        vec![path.to_path_buf()]
+    }
+}
+
+/// Build the foreground `docker run` invocation for a container app:
+/// `command` is the image, `args` the container arguments, and the
+/// env/resource-limit config maps onto runtime flags.
+fn container_argv(config: &AppConfig) -> Vec<String> {
+    let runtime = config.runtime.as_deref().unwrap_or("docker");
+    let mut argv = vec![
+        runtime.to_owned(),
+        "run".to_owned(),
+        "--rm".to_owned(),
+        "--sig-proxy=true".to_owned(),
+        "--name".to_owned(),
+        format!("bunctl-{}", config.name),
+    ];
+    for key in config.env.keys() {
+        // Value-less `-e KEY`: the runtime reads the value from its own
+        // environment (set by [`spawn`]), keeping env_secrets resolved onto
+        // `env` out of `/proc/*/cmdline`.
+        argv.push("-e".to_owned());
+        argv.push(key.clone());
+    }
+    if let Some(bytes) = config.max_memory {
+        argv.push("--memory".to_owned());
+        argv.push(bytes.to_string());
+    }
+    if let Some(percent) = config.max_cpu_percent {
+        // Only a hard limit maps to the runtime; soft mode is alert-only
+        // and handled by the daemon's sampler.
+        if config.cpu_limit_mode == bunctl_core::config::CpuLimitMode::Hard {
+            argv.push("--cpus".to_owned());
+            argv.push(format!("{:.2}", percent / 100.0));
+        }
+    }
+    if !config.gpus.is_empty() {
+        // Inside the container the selected devices renumber from 0.
+        argv.push("--gpus".to_owned());
+        argv.push(format!(
+            "device={}",
+            config.gpus.iter().map(u32::to_string).collect::<Vec<_>>().join(",")
+        ));
+    }
+    argv.push(config.command.clone());
+    argv.extend(config.args.iter().cloned());
+    argv
+}
+
+/// First line of `<program> --version`, best effort with a short timeout;
+/// `None` when the program does not answer (or answers with a failure).
+pub async fn program_version(program: &str) -> Option<String> {
+    let output = tokio::time::timeout(
+        Duration::from_secs(2),
+        Command::new(program)
+            .arg("--version")
+            .stdin(Stdio::null())
+            .output(),
+    )
+    .await
+    .ok()?
+    .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_owned())
+        .filter(|line| !line.is_empty())
+}
+
+/// Inspect a running process; `None` when the PID is gone or inaccessible.
+pub fn get_process_info(pid: u32) -> Option<ProcessInfo> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::get_process_info_impl(pid)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        macos::get_process_info_impl(pid)
+    }
+    #[cfg(windows)]
+    {
+        windows::get_process_info_impl(pid)
+    }
+}
+
+/// The process tree rooted at `pid`: the process itself and every live
+/// descendant. Off Linux only the root is reported.
+pub fn process_tree(pid: u32) -> Vec<bunctl_core::ProcessNode> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::process_tree_impl(pid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        get_process_info(pid)
+            .map(|info| bunctl_core::ProcessNode {
+                pid,
+                ppid: 0,
+                name: info.name,
+                command: info.command,
+                memory_bytes: info.memory_bytes,
+                cpu_ticks: None,
+                threads: info.threads,
+            })
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Cumulative CPU time of the process in clock ticks (user + system), for
+/// percent-usage sampling. `None` where not implemented or the PID is gone.
+pub fn cpu_ticks(pid: u32) -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::cpu_ticks_impl(pid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+/// Clock ticks per second, for converting [`cpu_ticks`] deltas to seconds.
+pub fn clock_ticks_per_sec() -> u64 {
+    #[cfg(unix)]
+    unsafe {
+        let hz = libc::sysconf(libc::_SC_CLK_TCK);
+        if hz > 0 {
+            hz as u64
+        } else {
+            100
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        100
+    }
+}
+
+/// Per-app stop escalation chain: first signal, grace period, optional
+/// intermediate signal with its own grace period, then the hard kill.
+#[derive(Debug, Clone)]
+pub struct StopPolicy {
+    /// Signal that starts the sequence (SIGTERM by default).
+    pub first_signal: i32,
+    /// Grace period after the first signal.
+    pub stop_timeout: Duration,
+    /// Optional intermediate signal and its grace period, for apps with a
+    /// long drain phase.
+    pub second: Option<(i32, Duration)>,
+    /// How long to wait after the hard kill before giving up.
+    pub kill_timeout: Duration,
+}
+
+impl Default for StopPolicy {
+    fn default() -> Self {
+        Self {
+            first_signal: default_stop_signal(),
+            stop_timeout: Duration::from_secs(10),
+            second: None,
+            kill_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl StopPolicy {
+    /// Build the escalation chain from an app's config, falling back to
+    /// SIGTERM for missing or unrecognized signal names.
+    pub fn from_config(config: &AppConfig) -> Self {
+        let first_signal = match config.stop_signal.as_deref() {
+            Some(name) => signal_by_name(name).unwrap_or_else(|| {
+                tracing::warn!(app = %config.name, "unknown stop_signal '{name}'; using TERM");
+                default_stop_signal()
+            }),
+            None => default_stop_signal(),
+        };
+        let second = config.escalation_signal.as_deref().and_then(|name| {
+            let Some(sig) = signal_by_name(name) else {
+                tracing::warn!(app = %config.name, "unknown escalation_signal '{name}'; skipping");
+                return None;
+            };
+            Some((sig, config.escalation_timeout.unwrap_or(config.stop_timeout)))
+        });
+        Self {
+            first_signal,
+            stop_timeout: config.stop_timeout,
+            second,
+            kill_timeout: config.kill_timeout,
+        }
+    }
+}
+
+fn default_stop_signal() -> i32 {
+    #[cfg(unix)]
+    {
+        libc::SIGTERM
+    }
+    #[cfg(not(unix))]
+    {
+        15
+    }
+}
+
+/// Map a signal name (`TERM`, `SIGTERM`, `usr2`, ...) to its number.
+pub fn signal_by_name(name: &str) -> Option<i32> {
+    let name = name.trim().to_ascii_uppercase();
+    let name = name.strip_prefix("SIG").unwrap_or(&name);
+    #[cfg(unix)]
+    {
+        match name {
+            "TERM" => Some(libc::SIGTERM),
+            "INT" => Some(libc::SIGINT),
+            "QUIT" => Some(libc::SIGQUIT),
+            "HUP" => Some(libc::SIGHUP),
+            "USR1" => Some(libc::SIGUSR1),
+            "USR2" => Some(libc::SIGUSR2),
+            "KILL" => Some(libc::SIGKILL),
+            _ => None,
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        // Windows has no signals; only the name validation matters there.
+        matches!(name, "TERM" | "INT" | "QUIT" | "HUP" | "USR1" | "USR2" | "KILL").then_some(15)
+    }
+}
+
+/// Classify a wait status into an [`ExitReason`]: a plain code, or the
+/// terminating signal on Unix. OOM kills are detected separately by the
+/// daemon via [`oom_kill_count`], since they need a before/after counter.
+///
+/// [`ExitReason`]: bunctl_core::ExitReason
+pub fn exit_reason(status: Option<std::process::ExitStatus>) -> bunctl_core::ExitReason {
+    let Some(status) = status else { return bunctl_core::ExitReason::Unknown };
+    if let Some(code) = status.code() {
+        return bunctl_core::ExitReason::Code { code };
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return bunctl_core::ExitReason::Signal {
+                signal,
+                name: signal_name(signal).map(str::to_owned),
+            };
+        }
+    }
+    bunctl_core::ExitReason::Unknown
+}
+
+/// Symbolic name of a terminating signal, covering the set apps commonly
+/// die from (the inverse of [`signal_by_name`] plus the crash signals).
+pub fn signal_name(signal: i32) -> Option<&'static str> {
+    #[cfg(unix)]
+    {
+        match signal {
+            s if s == libc::SIGTERM => Some("SIGTERM"),
+            s if s == libc::SIGINT => Some("SIGINT"),
+            s if s == libc::SIGQUIT => Some("SIGQUIT"),
+            s if s == libc::SIGHUP => Some("SIGHUP"),
+            s if s == libc::SIGUSR1 => Some("SIGUSR1"),
+            s if s == libc::SIGUSR2 => Some("SIGUSR2"),
+            s if s == libc::SIGKILL => Some("SIGKILL"),
+            s if s == libc::SIGSEGV => Some("SIGSEGV"),
+            s if s == libc::SIGABRT => Some("SIGABRT"),
+            s if s == libc::SIGBUS => Some("SIGBUS"),
+            s if s == libc::SIGILL => Some("SIGILL"),
+            s if s == libc::SIGFPE => Some("SIGFPE"),
+            _ => None,
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = signal;
+        None
+    }
+}
+
+/// Cumulative OOM kills recorded in the app's per-app cgroup
+/// (`memory.events` on Linux); `0` where there is no cgroup or no
+/// platform support. The daemon compares counts across exits, since the
+/// counter survives restarts within the same cgroup.
+pub fn oom_kill_count(name: &str) -> u64 {
+    #[cfg(target_os = "linux")]
+    {
+        linux::oom_kill_count_impl(name).unwrap_or(0)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = name;
+        0
+    }
+}
+
+/// Look for the core file a crashed child left behind.
+///
+/// Linux honors `kernel.core_pattern`: a pipe handler (systemd-coredump,
+/// apport) owns the dump, so `None` is returned with a note in the log;
+/// plain patterns are searched — `%`-specifiers matching anything — in the
+/// pattern's directory, or the child's `cwd` for relative ones. On macOS
+/// dumps land in `/cores/core.<pid>`. Only files modified in the last few
+/// minutes count, so stale dumps are never picked up.
+pub fn find_core_dump(cwd: Option<&std::path::Path>, pid: u32) -> Option<std::path::PathBuf> {
+    #[cfg(target_os = "linux")]
+    {
+        let pattern = std::fs::read_to_string("/proc/sys/kernel/core_pattern").ok()?;
+        let pattern = pattern.trim();
+        if let Some(handler) = pattern.strip_prefix('|') {
+            tracing::info!(
+                pid,
+                "core dumps go to a pipe handler ({}); not collecting",
+                handler.split_whitespace().next().unwrap_or(handler)
+            );
+            return None;
+        }
+        let full = std::path::Path::new(pattern);
+        let dir = if full.is_absolute() {
+            full.parent()?.to_path_buf()
+        } else {
+            cwd.map(std::path::Path::to_path_buf)
+                .or_else(|| std::env::current_dir().ok())?
+        };
+        let name_pattern = full.file_name()?.to_str()?;
+        newest_core_match(&dir, name_pattern, pid)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let _ = cwd;
+        let path = std::path::PathBuf::from(format!("/cores/core.{pid}"));
+        recent(&path).then_some(path)
+    }
+    #[cfg(windows)]
+    {
+        let _ = (cwd, pid);
+        None
+    }
+}
+
+/// The newest recently-written file in `dir` matching a `core_pattern`
+/// file name, where each `%`-specifier spans any run of characters (`%p`
+/// is matched exactly against the crashed PID).
+#[cfg(target_os = "linux")]
+fn newest_core_match(
+    dir: &std::path::Path,
+    pattern: &str,
+    pid: u32,
+) -> Option<std::path::PathBuf> {
+    // Turn "core.%e.%p" into literal segments: ["core.", <any>, ".", pid].
+    let mut segments = Vec::new();
+    let mut rest = pattern;
+    while let Some(pos) = rest.find('%') {
+        segments.push(rest[..pos].to_owned());
+        let spec = rest.as_bytes().get(pos + 1).copied();
+        segments.push(if spec == Some(b'p') { pid.to_string() } else { String::new() });
+        rest = rest.get(pos + 2..).unwrap_or("");
+    }
+    segments.push(rest.to_owned());
+
+    let mut best: Option<(std::time::SystemTime, std::path::PathBuf)> = None;
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else { continue };
+        if !segments_match(&segments, name) || !recent(&path) {
+            continue;
+        }
+        let modified = entry.metadata().and_then(|m| m.modified()).ok()?;
+        if best.as_ref().is_none_or(|(at, _)| modified > *at) {
+            best = Some((modified, path));
+        }
+    }
+    best.map(|(_, path)| path)
+}
+
+/// Match a name against alternating literal/any segments (empty literal =
+/// match any run), the same way the config crate's `*`-wildcards work.
+#[cfg(target_os = "linux")]
+fn segments_match(segments: &[String], name: &str) -> bool {
+    let mut rest = name;
+    let last = segments.len() - 1;
+    for (idx, seg) in segments.iter().enumerate() {
+        if seg.is_empty() {
+            continue;
+        }
+        if idx == 0 {
+            match rest.strip_prefix(seg.as_str()) {
+                Some(r) => rest = r,
+                None => return false,
+            }
+        } else if idx == last {
+            return rest.ends_with(seg.as_str());
+        } else {
+            match rest.find(seg.as_str()) {
+                Some(pos) => rest = &rest[pos + seg.len()..],
+                None => return false,
+            }
+        }
+    }
+    true
+}
+
+/// Whether the file was written in the last few minutes — i.e. by the
+/// crash we are cleaning up after, not an older one.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn recent(path: &std::path::Path) -> bool {
+    const MAX_AGE_SECS: u64 = 300;
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|at| at.elapsed().ok())
+        .is_some_and(|age| age.as_secs() < MAX_AGE_SECS)
+}
+
+/// Send `signal` to the whole process group led by `pid`, falling back to
+/// the single process when it is not a group leader. No-op on Windows.
+pub fn signal_tree(pid: u32, signal: i32) {
+    #[cfg(unix)]
+    unsafe {
+        if libc::kill(-(pid as i32), signal) != 0 {
+            libc::kill(pid as i32, signal);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (pid, signal);
+}
+
+/// Enforce a hard CPU quota on a running process via cgroup v2 `cpu.max`
+/// (`cpu_limit_mode: hard` with `max_cpu_percent`). The process is moved
+/// into a child of the daemon's own cgroup, so the usual delegation rules
+/// apply. A warning (not an error) where the cgroup cannot be written or
+/// the platform has no equivalent — the app still runs, unthrottled.
+pub fn apply_cpu_limit(pid: u32, name: &str, percent: f64) {
+    #[cfg(target_os = "linux")]
+    {
+        if let Err(err) = linux::apply_cpu_limit_impl(pid, name, percent) {
+            tracing::warn!(pid, app = %name, "cannot apply cgroup cpu.max: {err}");
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (pid, percent);
+        tracing::warn!(app = %name, "cpu_limit_mode: hard is only enforced on Linux");
+    }
+}
+
+/// Open a TCP connection to `127.0.0.1:port` inside `pid`'s network
+/// namespace (Linux), for forwarding into `private_network` apps. The
+/// calling thread temporarily joins the namespace, so call this from a
+/// blocking thread only.
+pub fn connect_in_namespace(pid: u32, port: u16) -> std::io::Result<std::net::TcpStream> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::connect_in_namespace_impl(pid, port)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (pid, port);
+        Err(std::io::Error::other("network namespaces are Linux-only"))
+    }
+}
+
+/// Pin a running process to the given CPU cores (`cpu_affinity`), applied
+/// right after spawn so threads and children started later inherit it.
+/// A warning (not an error) where the call fails or the platform has no
+/// equivalent — the app still runs, unpinned.
+pub fn apply_cpu_affinity(pid: u32, name: &str, cpus: &[u32]) {
+    #[cfg(target_os = "linux")]
+    {
+        if let Err(err) = linux::apply_cpu_affinity_impl(pid, cpus) {
+            tracing::warn!(pid, app = %name, "cannot set cpu affinity: {err}");
+        }
+    }
+    #[cfg(windows)]
+    {
+        if let Err(err) = windows::apply_cpu_affinity_impl(pid, cpus) {
+            tracing::warn!(pid, app = %name, "cannot set cpu affinity: {err}");
+        }
+    }
+    #[cfg(not(any(target_os = "linux", windows)))]
+    {
+        let _ = (pid, cpus);
+        tracing::warn!(app = %name, "cpu_affinity is only enforced on Linux and Windows");
+    }
+}
+
+/// Put a freshly spawned process into a Windows job object with the
+/// app's configured flags (`job_object`): breakaway for child tools that
+/// must outlive the app, kill-on-close so nothing escapes supervision.
+/// A warning (not an error) where it fails or off Windows.
+pub fn apply_job_object(pid: u32, name: &str, config: &bunctl_core::config::JobObjectConfig) {
+    #[cfg(windows)]
+    {
+        if let Err(err) = windows::apply_job_object_impl(pid, config) {
+            tracing::warn!(pid, app = %name, "cannot assign job object: {err}");
+        }
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = (pid, config);
+        tracing::warn!(app = %name, "job_object is Windows-only; ignoring");
+    }
+}
+
+/// Mark this process as a child subreaper (Linux), so grandchildren whose
+/// parent died reparent to us instead of init and can be reaped. No-op
+/// elsewhere.
+pub fn become_subreaper() {
+    #[cfg(target_os = "linux")]
+    unsafe {
+        if libc::prctl(libc::PR_SET_CHILD_SUBREAPER, 1, 0, 0, 0) != 0 {
+            tracing::warn!("cannot set PR_SET_CHILD_SUBREAPER");
+        }
+    }
+}
+
+/// Reap zombie children of this process that are *not* in `known` (those
+/// are tokio-managed and must be waited by their own `Child` handle).
+/// Returns the PIDs reaped. Linux-only; no-op elsewhere.
+pub fn reap_zombies(known: &[u32]) -> Vec<u32> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::reap_zombies_impl(known)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = known;
+        Vec::new()
+    }
+}
+
+/// Ask the process to stop gracefully (SIGTERM on Unix). On Windows there
+/// is no graceful equivalent for console-less apps, so this is a no-op and
+/// the caller escalates to [`kill`] after the grace period.
+pub fn request_stop(pid: u32) {
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(pid as i32, libc::SIGTERM);
+    }
+    #[cfg(windows)]
+    let _ = pid;
+}
+
+/// Forcefully kill the process.
+pub fn kill(pid: u32) {
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(pid as i32, libc::SIGKILL);
+    }
+    #[cfg(windows)]
+    {
+        windows::kill_impl(pid);
+    }
+}
+
+/// Ask the whole process group led by `pid` to stop (SIGTERM to `-pgid` on
+/// Unix), so detached grandchildren get the signal too. Falls back to the
+/// single process when `pid` is not a group leader (e.g. adopted processes).
+pub fn request_stop_tree(pid: u32) {
+    #[cfg(unix)]
+    unsafe {
+        if libc::kill(-(pid as i32), libc::SIGTERM) != 0 {
+            libc::kill(pid as i32, libc::SIGTERM);
+        }
+    }
+    #[cfg(windows)]
+    let _ = pid;
+}
+
+/// Forcefully kill the whole process group led by `pid`, falling back to
+/// the single process when it is not a group leader.
+pub fn kill_tree(pid: u32) {
+    #[cfg(unix)]
+    unsafe {
+        if libc::kill(-(pid as i32), libc::SIGKILL) != 0 {
+            libc::kill(pid as i32, libc::SIGKILL);
+        }
+    }
+    #[cfg(windows)]
+    {
+        windows::kill_impl(pid);
+    }
+}
+
+/// Whether the process is still alive.
+pub fn is_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(pid as i32, 0) == 0
+    }
+    #[cfg(windows)]
+    {
+        windows::is_alive_impl(pid)
+    }
+}
+
+/// Stop a process tree with the default TERM → KILL escalation. See
+/// [`stop_with_policy`] for the configurable chain.
+pub async fn stop_with_timeout(pid: u32, stop_timeout: Duration, kill_timeout: Duration) -> bool {
+    let policy = StopPolicy { stop_timeout, kill_timeout, ..StopPolicy::default() };
+    stop_with_policy(pid, &policy).await
+}
+
+/// Walk a [`StopPolicy`] escalation chain against the process group led by
+/// `pid`. Returns `true` when the group leader is confirmed gone.
+pub async fn stop_with_policy(pid: u32, policy: &StopPolicy) -> bool {
+    signal_tree(pid, policy.first_signal);
+    if wait_gone(pid, policy.stop_timeout).await {
+        return sweep_group(pid);
+    }
+    if let Some((signal, timeout)) = policy.second {
+        tracing::warn!(pid, "process did not exit within {:?}; escalating", policy.stop_timeout);
+        signal_tree(pid, signal);
+        if wait_gone(pid, timeout).await {
+            return sweep_group(pid);
+        }
+    }
+    tracing::warn!(pid, "process survived the stop sequence; killing group");
+    kill_tree(pid);
+    wait_gone(pid, policy.kill_timeout).await
+}
+
+/// The leader is gone; make sure stragglers in its group go with it.
+/// Group-only (no single-pid fallback): the leader PID may already have
+/// been recycled.
+fn sweep_group(pid: u32) -> bool {
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(-(pid as i32), libc::SIGKILL);
+    }
+    #[cfg(not(unix))]
+    let _ = pid;
+    true
+}
+
+async fn wait_gone(pid: u32, timeout: Duration) -> bool {
+    let deadline = tokio::time::Instant::now() + timeout;
+    while tokio::time::Instant::now() < deadline {
+        if !is_alive(pid) {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    !is_alive(pid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_signal_names() {
+        assert_eq!(signal_by_name("TERM"), signal_by_name("sigterm"));
+        assert!(signal_by_name("USR2").is_some());
+        assert!(signal_by_name("NOPE").is_none());
+    }
+
+    #[test]
+    fn check_command_resolves_path_entries() {
+        #[cfg(unix)]
+        assert!(check_command("sh", None).is_ok());
+        assert!(matches!(
+            check_command("definitely-not-a-command-qqq", None),
+            Err(CommandProblem::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn edit_distance_catches_transpositions() {
+        assert_eq!(edit_distance("bun", "bun"), 0);
+        assert_eq!(edit_distance("bnu", "bun"), 2);
+        assert!(edit_distance("bun", "docker") > 2);
+    }
+
+    #[test]
+    fn container_argv_maps_gpus_to_runtime_flag() {
+        let config = AppConfig {
+            command: "tensorrt-server".into(),
+            exec_kind: ExecKind::Container,
+            gpus: vec![0, 2],
+            ..AppConfig::default()
+        };
+        let argv = container_argv(&config);
+        let at = argv.iter().position(|a| a == "--gpus").unwrap();
+        assert_eq!(argv[at + 1], "device=0,2");
+        // The image comes after the runtime flags.
+        assert!(argv.iter().position(|a| a == "tensorrt-server").unwrap() > at);
+    }
+
+    #[test]
+    fn container_argv_passes_env_by_key_only() {
+        let config = AppConfig {
+            command: "my-image".into(),
+            exec_kind: ExecKind::Container,
+            env: [("API_TOKEN".to_owned(), "hunter2".to_owned())].into(),
+            ..AppConfig::default()
+        };
+        let argv = container_argv(&config);
+        let at = argv.iter().position(|a| a == "-e").unwrap();
+        assert_eq!(argv[at + 1], "API_TOKEN");
+        // The value travels through the runtime's environment, never argv.
+        assert!(!argv.iter().any(|a| a.contains("hunter2")));
+    }
+
+    #[test]
+    fn stop_policy_falls_back_on_bad_names() {
+        let config = AppConfig {
+            stop_signal: Some("BOGUS".into()),
+            escalation_signal: Some("USR2".into()),
+            ..AppConfig::default()
+        };
+        let policy = StopPolicy::from_config(&config);
+        assert_eq!(policy.first_signal, default_stop_signal());
+        let (sig, timeout) = policy.second.unwrap();
+        assert_eq!(Some(sig), signal_by_name("USR2"));
+        // escalation_timeout defaults to stop_timeout.
+        assert_eq!(timeout, config.stop_timeout);
+    }
+}
@@ -0,0 +1,257 @@
+//! Linux process inspection via `/proc`.
+
+use crate::ProcessInfo;
+
+pub(crate) fn get_process_info_impl(pid: u32) -> Option<ProcessInfo> {
+    let status = std::fs::read_to_string(format!("/proc/{pid}/status")).ok()?;
+    let mut name = String::new();
+    let mut memory_bytes = None;
+    let mut threads = None;
+    for line in status.lines() {
+        if let Some(rest) = line.strip_prefix("Name:") {
+            name = rest.trim().to_owned();
+        } else if let Some(rest) = line.strip_prefix("VmRSS:") {
+            // "VmRSS:   12345 kB"
+            let kb: u64 = rest.trim().trim_end_matches("kB").trim().parse().ok()?;
+            memory_bytes = Some(kb * 1024);
+        } else if let Some(rest) = line.strip_prefix("Threads:") {
+            threads = rest.trim().parse().ok();
+        }
+    }
+
+    // Open descriptors are the entries of /proc/<pid>/fd.
+    let open_files = std::fs::read_dir(format!("/proc/{pid}/fd"))
+        .ok()
+        .map(|entries| entries.count() as u32);
+
+    let command = std::fs::read(format!("/proc/{pid}/cmdline"))
+        .ok()
+        .map(|raw| {
+            raw.split(|&b| b == 0)
+                .filter(|part| !part.is_empty())
+                .map(|part| String::from_utf8_lossy(part).into_owned())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default();
+
+    Some(ProcessInfo {
+        pid,
+        name,
+        command,
+        memory_bytes,
+        cpu_percent: None,
+        threads,
+        open_files,
+    })
+}
+
+/// The process tree rooted at `root`, from one `/proc` scan of pid →
+/// parent links; each process comes after its parent.
+pub(crate) fn process_tree_impl(root: u32) -> Vec<bunctl_core::ProcessNode> {
+    let mut links: Vec<(u32, u32)> = Vec::new();
+    if let Ok(entries) = std::fs::read_dir("/proc") {
+        for entry in entries.flatten() {
+            let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse::<u32>().ok()) else {
+                continue;
+            };
+            if let Some(ppid) = read_ppid(pid) {
+                links.push((pid, ppid));
+            }
+        }
+    }
+    let mut tree = Vec::new();
+    let mut queue = std::collections::VecDeque::from([root]);
+    while let Some(pid) = queue.pop_front() {
+        let ppid = links.iter().find(|(p, _)| *p == pid).map_or(0, |(_, pp)| *pp);
+        if let Some(info) = get_process_info_impl(pid) {
+            tree.push(bunctl_core::ProcessNode {
+                pid,
+                ppid,
+                name: info.name,
+                command: info.command,
+                memory_bytes: info.memory_bytes,
+                cpu_ticks: cpu_ticks_impl(pid),
+                threads: info.threads,
+            });
+        }
+        queue.extend(links.iter().filter(|(_, pp)| *pp == pid).map(|(p, _)| *p));
+    }
+    tree
+}
+
+fn read_ppid(pid: u32) -> Option<u32> {
+    let status = std::fs::read_to_string(format!("/proc/{pid}/status")).ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("PPid:"))
+        .and_then(|rest| rest.trim().parse().ok())
+}
+
+pub(crate) fn cpu_ticks_impl(pid: u32) -> Option<u64> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // The comm field can contain spaces; fields are counted from after the
+    // closing paren. utime and stime are fields 14 and 15 (1-based).
+    let rest = stat.rsplit_once(')')?.1;
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(utime + stime)
+}
+
+/// Move `pid` into a per-app child of our own cgroup and set `cpu.max` to
+/// `percent` of one core (cgroup v2 only).
+pub(crate) fn apply_cpu_limit_impl(pid: u32, name: &str, percent: f64) -> std::io::Result<()> {
+    const PERIOD_USECS: u64 = 100_000;
+    let dir = app_cgroup_dir(name)?;
+    std::fs::create_dir_all(&dir)?;
+    let quota = ((percent / 100.0) * PERIOD_USECS as f64) as u64;
+    std::fs::write(dir.join("cpu.max"), format!("{} {PERIOD_USECS}\n", quota.max(1000)))?;
+    std::fs::write(dir.join("cgroup.procs"), pid.to_string())?;
+    Ok(())
+}
+
+/// The per-app child of our own cgroup, as created by
+/// [`apply_cpu_limit_impl`] (cgroup v2 only).
+fn app_cgroup_dir(name: &str) -> std::io::Result<std::path::PathBuf> {
+    let own = std::fs::read_to_string("/proc/self/cgroup")?;
+    // On the v2 unified hierarchy there is a single "0::/path" entry.
+    let path = own
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .ok_or_else(|| std::io::Error::other("not on the cgroup v2 unified hierarchy"))?;
+    Ok(std::path::Path::new("/sys/fs/cgroup")
+        .join(path.trim().trim_start_matches('/'))
+        .join(format!("bunctl-{name}")))
+}
+
+/// Move the calling thread into a fresh network namespace with loopback
+/// up (`private_network`); runs in the pre-exec child, before exec.
+pub(crate) fn isolate_network_impl() -> std::io::Result<()> {
+    if unsafe { libc::unshare(libc::CLONE_NEWNET) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    // A new namespace starts with lo down; flip it up so the app can bind
+    // 127.0.0.1.
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM, 0) };
+    if fd < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let mut req: libc::ifreq = unsafe { std::mem::zeroed() };
+    for (dst, src) in req.ifr_name.iter_mut().zip(b"lo") {
+        *dst = *src as libc::c_char;
+    }
+    let result = unsafe {
+        req.ifr_ifru.ifru_flags = (libc::IFF_UP | libc::IFF_RUNNING) as libc::c_short;
+        if libc::ioctl(fd, libc::SIOCSIFFLAGS, &req) != 0 {
+            Err(std::io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    };
+    unsafe { libc::close(fd) };
+    result
+}
+
+/// Open a TCP connection to `127.0.0.1:port` inside `pid`'s network
+/// namespace. The thread joins the namespace for the duration — a socket
+/// keeps the namespace it was created in — so this must run on a
+/// dedicated blocking thread, never an async worker.
+pub(crate) fn connect_in_namespace_impl(
+    pid: u32,
+    port: u16,
+) -> std::io::Result<std::net::TcpStream> {
+    use std::os::fd::AsRawFd;
+    let own = std::fs::File::open("/proc/self/ns/net")?;
+    let target = std::fs::File::open(format!("/proc/{pid}/ns/net"))?;
+    if unsafe { libc::setns(target.as_raw_fd(), libc::CLONE_NEWNET) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let stream = std::net::TcpStream::connect_timeout(
+        &std::net::SocketAddr::from(([127, 0, 0, 1], port)),
+        std::time::Duration::from_secs(5),
+    );
+    // Restore the thread's own namespace whether or not the connect
+    // succeeded; failing that is not survivable for the thread pool.
+    if unsafe { libc::setns(own.as_raw_fd(), libc::CLONE_NEWNET) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    stream
+}
+
+/// Pin `pid` to the given CPU cores via `sched_setaffinity`.
+pub(crate) fn apply_cpu_affinity_impl(pid: u32, cpus: &[u32]) -> std::io::Result<()> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    for &cpu in cpus {
+        unsafe { libc::CPU_SET(cpu as usize, &mut set) };
+    }
+    if unsafe { libc::sched_setaffinity(pid as i32, std::mem::size_of::<libc::cpu_set_t>(), &set) }
+        != 0
+    {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Cumulative `oom_kill` counter of the app's cgroup `memory.events`;
+/// `None` when the app has no cgroup of its own.
+pub(crate) fn oom_kill_count_impl(name: &str) -> Option<u64> {
+    let events = std::fs::read_to_string(app_cgroup_dir(name).ok()?.join("memory.events")).ok()?;
+    events
+        .lines()
+        .find_map(|line| line.strip_prefix("oom_kill "))
+        .and_then(|n| n.trim().parse().ok())
+}
+
+/// Find zombie children of this process (state `Z`, PPid == us) and reap
+/// them with a targeted `waitpid`. PIDs in `known` belong to tokio `Child`
+/// handles and are left for their owners.
+pub(crate) fn reap_zombies_impl(known: &[u32]) -> Vec<u32> {
+    let own_pid = std::process::id();
+    let mut reaped = Vec::new();
+    let Ok(entries) = std::fs::read_dir("/proc") else { return reaped };
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse::<u32>().ok()) else {
+            continue;
+        };
+        if known.contains(&pid) {
+            continue;
+        }
+        let Ok(status) = std::fs::read_to_string(format!("/proc/{pid}/status")) else { continue };
+        let mut is_zombie = false;
+        let mut parent = 0u32;
+        for line in status.lines() {
+            if let Some(rest) = line.strip_prefix("State:") {
+                is_zombie = rest.trim_start().starts_with('Z');
+            } else if let Some(rest) = line.strip_prefix("PPid:") {
+                parent = rest.trim().parse().unwrap_or(0);
+            }
+        }
+        if is_zombie && parent == own_pid {
+            let mut wstatus = 0;
+            if unsafe { libc::waitpid(pid as i32, &mut wstatus, libc::WNOHANG) } == pid as i32 {
+                reaped.push(pid);
+            }
+        }
+    }
+    reaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_cpu_ticks() {
+        assert!(cpu_ticks_impl(std::process::id()).is_some());
+    }
+
+    #[test]
+    fn inspects_own_process() {
+        let info = get_process_info_impl(std::process::id()).unwrap();
+        assert!(info.memory_bytes.unwrap_or(0) > 0);
+        assert!(!info.name.is_empty());
+        assert!(info.threads.unwrap_or(0) >= 1);
+        assert!(info.open_files.unwrap_or(0) >= 3);
+    }
+}
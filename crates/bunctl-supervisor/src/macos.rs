@@ -0,0 +1,110 @@
+//! macOS process inspection via libproc.
+//!
+//! Memory is the physical footprint (what Activity Monitor shows), CPU
+//! comes from `proc_pid_rusage` deltas against the wall clock, threads
+//! and fd counts from `proc_pidinfo` — parity with the Linux numbers.
+
+use crate::ProcessInfo;
+
+pub(crate) fn get_process_info_impl(pid: u32) -> Option<ProcessInfo> {
+    if !crate::is_alive(pid) {
+        return None;
+    }
+    let rusage = rusage(pid);
+    Some(ProcessInfo {
+        pid,
+        name: proc_name(pid).unwrap_or_else(|| format!("pid-{pid}")),
+        command: proc_path(pid).unwrap_or_default(),
+        memory_bytes: rusage.as_ref().map(|ru| ru.ri_phys_footprint),
+        cpu_percent: rusage.as_ref().and_then(|ru| cpu_percent(pid, ru)),
+        threads: task_info(pid).map(|task| task.pti_threadnum as u32),
+        open_files: fd_count(pid),
+    })
+}
+
+fn rusage(pid: u32) -> Option<libc::rusage_info_v2> {
+    let mut info: libc::rusage_info_v2 = unsafe { std::mem::zeroed() };
+    let ret = unsafe {
+        libc::proc_pid_rusage(
+            pid as i32,
+            libc::RUSAGE_INFO_V2,
+            std::ptr::addr_of_mut!(info).cast(),
+        )
+    };
+    (ret == 0).then_some(info)
+}
+
+/// CPU usage in percent of one core, from the delta of two rusage samples
+/// against the wall clock. The first call for a PID only primes the
+/// cache; status polling provides the second sample.
+fn cpu_percent(pid: u32, ru: &libc::rusage_info_v2) -> Option<f64> {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use std::time::Instant;
+
+    static SAMPLES: Mutex<Option<HashMap<u32, (u64, Instant)>>> = Mutex::new(None);
+
+    let total_ns = mach_to_ns(ru.ri_user_time + ru.ri_system_time);
+    let now = Instant::now();
+    let mut cache = SAMPLES.lock().expect("not poisoned");
+    let cache = cache.get_or_insert_with(HashMap::new);
+    // Age out PIDs that are no longer polled so churn cannot grow the map.
+    if cache.len() > 512 {
+        cache.retain(|_, (_, at)| now.duration_since(*at).as_secs() < 300);
+    }
+    let (prev_total, prev_at) = cache.insert(pid, (total_ns, now))?;
+    let elapsed = now.duration_since(prev_at).as_secs_f64();
+    if elapsed <= 0.0 || total_ns < prev_total {
+        // A reused PID: the fresh sample primes the new process.
+        return None;
+    }
+    Some((total_ns - prev_total) as f64 / 1e9 / elapsed * 100.0)
+}
+
+/// Mach absolute time units (what rusage times are reported in) to
+/// nanoseconds.
+fn mach_to_ns(t: u64) -> u64 {
+    use std::sync::OnceLock;
+    static TIMEBASE: OnceLock<(u32, u32)> = OnceLock::new();
+    let &(numer, denom) = TIMEBASE.get_or_init(|| unsafe {
+        let mut tb: libc::mach_timebase_info = std::mem::zeroed();
+        libc::mach_timebase_info(&mut tb);
+        (tb.numer, tb.denom)
+    });
+    t * u64::from(numer) / u64::from(denom.max(1))
+}
+
+fn task_info(pid: u32) -> Option<libc::proc_taskinfo> {
+    let mut info: libc::proc_taskinfo = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<libc::proc_taskinfo>() as i32;
+    let ret = unsafe {
+        libc::proc_pidinfo(
+            pid as i32,
+            libc::PROC_PIDTASKINFO,
+            0,
+            std::ptr::addr_of_mut!(info).cast(),
+            size,
+        )
+    };
+    (ret == size).then_some(info)
+}
+
+fn fd_count(pid: u32) -> Option<u32> {
+    // A null buffer asks for the required size; one proc_fdinfo per fd.
+    let bytes =
+        unsafe { libc::proc_pidinfo(pid as i32, libc::PROC_PIDLISTFDS, 0, std::ptr::null_mut(), 0) };
+    (bytes >= 0).then(|| bytes as u32 / std::mem::size_of::<libc::proc_fdinfo>() as u32)
+}
+
+fn proc_name(pid: u32) -> Option<String> {
+    let mut buf = [0u8; 64];
+    let ret = unsafe { libc::proc_name(pid as i32, buf.as_mut_ptr().cast(), buf.len() as u32) };
+    (ret > 0).then(|| String::from_utf8_lossy(&buf[..ret as usize]).into_owned())
+}
+
+fn proc_path(pid: u32) -> Option<String> {
+    // PROC_PIDPATHINFO_MAXSIZE
+    let mut buf = [0u8; 4096];
+    let ret = unsafe { libc::proc_pidpath(pid as i32, buf.as_mut_ptr().cast(), buf.len() as u32) };
+    (ret > 0).then(|| String::from_utf8_lossy(&buf[..ret as usize]).into_owned())
+}
@@ -0,0 +1,104 @@
+//! Spawn-time resolution of `env_secrets` values.
+
+use bunctl_core::config::SecretSource;
+use bunctl_core::AppConfig;
+
+use crate::SupervisorError;
+
+/// Resolve every `env_secrets` entry into `config.env`, in place.
+///
+/// Call this on a throwaway copy of the config just before spawning: the
+/// resolved values must never reach the persisted config snapshot, status
+/// output or logs.
+pub async fn resolve(config: &mut AppConfig) -> Result<(), SupervisorError> {
+    let sources = std::mem::take(&mut config.env_secrets);
+    for (key, source) in sources {
+        let value = resolve_source(&key, source).await?;
+        config.env.insert(key, value);
+    }
+    // The run_as password is referenced the same way; the resolved value
+    // lives on the throwaway spawn config only.
+    if let Some(run_as) = &mut config.run_as {
+        if let Some(source) = run_as.password.take() {
+            run_as.resolved_password = Some(resolve_source("run_as password", source).await?);
+        }
+    }
+    Ok(())
+}
+
+/// One source to its trimmed value; `key` only labels error messages.
+async fn resolve_source(key: &str, source: SecretSource) -> Result<String, SupervisorError> {
+    let value = match source {
+        SecretSource::File { path } => {
+            std::fs::read_to_string(&path).map_err(|e| SupervisorError::Secret {
+                key: key.to_owned(),
+                message: format!("cannot read {}: {e}", path.display()),
+            })?
+        }
+        SecretSource::Command { argv } => {
+            let Some((program, args)) = argv.split_first() else {
+                return Err(SupervisorError::Secret {
+                    key: key.to_owned(),
+                    message: "empty argv".into(),
+                });
+            };
+            let output = tokio::process::Command::new(program)
+                .args(args)
+                .output()
+                .await
+                .map_err(|e| SupervisorError::Secret {
+                    key: key.to_owned(),
+                    message: format!("cannot run {program}: {e}"),
+                })?;
+            if !output.status.success() {
+                return Err(SupervisorError::Secret {
+                    key: key.to_owned(),
+                    message: format!("{program} exited with {}", output.status),
+                });
+            }
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        }
+    };
+    Ok(value.trim_end_matches(['\r', '\n']).to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn file_secret_lands_in_env_only() {
+        let path = std::env::temp_dir().join(format!("bunctl-secret-{}", std::process::id()));
+        std::fs::write(&path, "s3cret\n").unwrap();
+        let mut config = AppConfig { name: "api".into(), ..Default::default() };
+        config.env_secrets.insert(
+            "DB_PASSWORD".into(),
+            SecretSource::File { path: path.clone() },
+        );
+        resolve(&mut config).await.unwrap();
+        assert_eq!(config.env["DB_PASSWORD"], "s3cret");
+        assert!(config.env_secrets.is_empty());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn run_as_password_resolves_onto_spawn_config() {
+        let path = std::env::temp_dir().join(format!("bunctl-runas-{}", std::process::id()));
+        std::fs::write(&path, "hunter2\n").unwrap();
+        let mut config = AppConfig {
+            name: "svc".into(),
+            run_as: Some(bunctl_core::config::RunAs {
+                user: r"CORP\svcacct".into(),
+                password: Some(SecretSource::File { path: path.clone() }),
+                resolved_password: None,
+            }),
+            ..Default::default()
+        };
+        resolve(&mut config).await.unwrap();
+        let run_as = config.run_as.unwrap();
+        // The reference is consumed; only the spawn copy holds the value.
+        assert!(run_as.password.is_none());
+        assert_eq!(run_as.resolved_password.as_deref(), Some("hunter2"));
+        let _ = std::fs::remove_file(&path);
+    }
+}
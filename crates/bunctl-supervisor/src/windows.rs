@@ -0,0 +1,352 @@
+//! Windows process control and inspection.
+//!
+//! The name and image come from `QueryFullProcessImageNameW`; command
+//! line, memory and thread count from one CIM (WMI) query — no PEB
+//! reading.
+
+use crate::ProcessInfo;
+
+pub(crate) fn get_process_info_impl(pid: u32) -> Option<ProcessInfo> {
+    let image = query_image_name(pid);
+    let cim = cim_process(pid);
+    if image.is_none() && cim.is_none() {
+        return None;
+    }
+    let (memory_bytes, threads, command) = cim.unwrap_or((None, None, String::new()));
+    let name = image
+        .as_deref()
+        .and_then(|path| std::path::Path::new(path).file_name())
+        .and_then(|name| name.to_str())
+        .map_or_else(|| format!("pid-{pid}"), str::to_owned);
+    Some(ProcessInfo {
+        pid,
+        name,
+        command,
+        memory_bytes,
+        cpu_percent: cpu_percent(pid),
+        threads,
+        open_files: None,
+    })
+}
+
+/// CPU usage of `pid` in percent of one core, from the delta of two
+/// `GetProcessTimes` samples against the wall clock. The first call for a
+/// PID only primes the cache and reports `None`; status polling provides
+/// the second sample.
+fn cpu_percent(pid: u32) -> Option<f64> {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use std::time::Instant;
+
+    static SAMPLES: Mutex<Option<HashMap<u32, (u64, Instant)>>> = Mutex::new(None);
+
+    let total = process_cpu_100ns(pid)?;
+    let now = Instant::now();
+    let mut cache = SAMPLES.lock().expect("not poisoned");
+    let cache = cache.get_or_insert_with(HashMap::new);
+    // PIDs of exited processes are never sampled again; age them out so
+    // the cache does not grow with PID churn.
+    if cache.len() > 512 {
+        cache.retain(|_, (_, at)| now.duration_since(*at).as_secs() < 300);
+    }
+    let (prev_total, prev_at) = cache.insert(pid, (total, now))?;
+    let elapsed = now.duration_since(prev_at).as_secs_f64();
+    if elapsed <= 0.0 || total < prev_total {
+        // A reused PID: the fresh sample primes the new process.
+        return None;
+    }
+    Some((total - prev_total) as f64 / 10_000_000.0 / elapsed * 100.0)
+}
+
+/// Cumulative kernel + user CPU time of `pid` in 100ns units.
+fn process_cpu_100ns(pid: u32) -> Option<u64> {
+    use ffi::*;
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return None;
+        }
+        let mut creation = FILETIME::default();
+        let mut exit = FILETIME::default();
+        let mut kernel = FILETIME::default();
+        let mut user = FILETIME::default();
+        let ok = GetProcessTimes(handle, &mut creation, &mut exit, &mut kernel, &mut user);
+        CloseHandle(handle);
+        (ok != 0).then(|| kernel.ticks() + user.ticks())
+    }
+}
+
+/// The full image path of `pid` via `QueryFullProcessImageNameW`.
+fn query_image_name(pid: u32) -> Option<String> {
+    use ffi::*;
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return None;
+        }
+        let mut buf = [0u16; 1024];
+        let mut len = buf.len() as u32;
+        let ok = QueryFullProcessImageNameW(handle, 0, buf.as_mut_ptr(), &mut len);
+        CloseHandle(handle);
+        (ok != 0).then(|| String::from_utf16_lossy(&buf[..len as usize]))
+    }
+}
+
+/// Memory, thread count and command line of `pid` from `Win32_Process`.
+fn cim_process(pid: u32) -> Option<(Option<u64>, Option<u32>, String)> {
+    let script = format!(
+        "$p = Get-CimInstance Win32_Process -Filter 'ProcessId = {pid}'; \
+         if ($p) {{ $p.WorkingSetSize; $p.ThreadCount; $p.CommandLine }}"
+    );
+    let out = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&out.stdout);
+    let mut lines = text.lines();
+    let memory = lines.next().and_then(|line| line.trim().parse().ok());
+    let threads = lines.next().and_then(|line| line.trim().parse().ok());
+    // A process may legitimately have no command line (e.g. System).
+    let command = lines.next().unwrap_or("").trim().to_owned();
+    (memory.is_some() || threads.is_some() || !command.is_empty())
+        .then_some((memory, threads, command))
+}
+
+pub(crate) fn kill_impl(pid: u32) {
+    // taskkill avoids holding process handles open; fine for the rare
+    // escalation path.
+    let _ = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .output();
+}
+
+/// Wrap `argv` so it runs under the `run_as` account: a PowerShell relay
+/// starts the target via `System.Diagnostics.Process` with credentials
+/// (CreateProcessWithLogonW underneath), streams its stdout/stderr through
+/// to our pipes and exits with the target's code. The password travels in
+/// the relay's environment (`BUNCTL_RUNAS_PW`, set by [`crate::spawn`]),
+/// never on a command line.
+pub(crate) fn run_as_argv(argv: &[String], run_as: &bunctl_core::config::RunAs) -> Vec<String> {
+    let args = argv[1..].iter().map(|a| arg_quote(a)).collect::<Vec<_>>().join(" ");
+    let script = format!(
+        r#"$ErrorActionPreference = 'Stop'
+$pw = if ($env:BUNCTL_RUNAS_PW) {{ ConvertTo-SecureString $env:BUNCTL_RUNAS_PW -AsPlainText -Force }} else {{ New-Object System.Security.SecureString }}
+Remove-Item Env:\BUNCTL_RUNAS_PW -ErrorAction SilentlyContinue
+$psi = New-Object System.Diagnostics.ProcessStartInfo
+$psi.FileName = {file}
+$psi.Arguments = {args}
+$psi.WorkingDirectory = (Get-Location).Path
+$user = {user}
+if ($user -match '^(.+)\\(.+)$') {{ $psi.Domain = $Matches[1]; $psi.UserName = $Matches[2] }} else {{ $psi.UserName = $user }}
+$psi.Password = $pw
+$psi.UseShellExecute = $false
+$psi.RedirectStandardOutput = $true
+$psi.RedirectStandardError = $true
+$p = New-Object System.Diagnostics.Process
+$p.StartInfo = $psi
+$null = Register-ObjectEvent -InputObject $p -EventName OutputDataReceived -Action {{ if ($null -ne $EventArgs.Data) {{ [Console]::Out.WriteLine($EventArgs.Data) }} }}
+$null = Register-ObjectEvent -InputObject $p -EventName ErrorDataReceived -Action {{ if ($null -ne $EventArgs.Data) {{ [Console]::Error.WriteLine($EventArgs.Data) }} }}
+$null = $p.Start()
+$p.BeginOutputReadLine()
+$p.BeginErrorReadLine()
+$p.WaitForExit()
+exit $p.ExitCode"#,
+        file = ps_quote(&argv[0]),
+        args = ps_quote(&args),
+        user = ps_quote(&run_as.user),
+    );
+    vec![
+        "powershell".to_owned(),
+        "-NoProfile".to_owned(),
+        "-NonInteractive".to_owned(),
+        "-Command".to_owned(),
+        script,
+    ]
+}
+
+/// A PowerShell single-quoted string literal.
+fn ps_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// One element of a Windows command line, quoted when needed.
+fn arg_quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        return arg.to_owned();
+    }
+    format!("\"{}\"", arg.replace('"', "\\\""))
+}
+
+pub(crate) fn apply_cpu_affinity_impl(pid: u32, cpus: &[u32]) -> std::io::Result<()> {
+    // PowerShell sets the affinity mask without us holding a process
+    // handle, matching the taskkill/tasklist approach above. Masks are
+    // limited to 64 cores, like SetProcessAffinityMask itself.
+    let mask = cpus.iter().fold(0u64, |mask, &cpu| mask | 1u64 << cpu.min(63));
+    let out = std::process::Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-Command",
+            &format!("(Get-Process -Id {pid}).ProcessorAffinity = {mask}"),
+        ])
+        .output()?;
+    if !out.status.success() {
+        return Err(std::io::Error::other(
+            String::from_utf8_lossy(&out.stderr).trim().to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+// Minimal hand-written Win32 declarations; the crate takes no windows
+// API dependency for these few calls.
+#[allow(non_snake_case, non_camel_case_types)]
+mod ffi {
+    use std::ffi::c_void;
+
+    pub const JOB_OBJECT_LIMIT_BREAKAWAY_OK: u32 = 0x0800;
+    pub const JOB_OBJECT_LIMIT_SILENT_BREAKAWAY_OK: u32 = 0x1000;
+    pub const JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE: u32 = 0x2000;
+    pub const JOB_OBJECT_EXTENDED_LIMIT_INFORMATION_CLASS: u32 = 9;
+    pub const PROCESS_SET_QUOTA: u32 = 0x0100;
+    pub const PROCESS_TERMINATE: u32 = 0x0001;
+    pub const PROCESS_QUERY_LIMITED_INFORMATION: u32 = 0x1000;
+
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct JOBOBJECT_BASIC_LIMIT_INFORMATION {
+        pub PerProcessUserTimeLimit: i64,
+        pub PerJobUserTimeLimit: i64,
+        pub LimitFlags: u32,
+        pub MinimumWorkingSetSize: usize,
+        pub MaximumWorkingSetSize: usize,
+        pub ActiveProcessLimit: u32,
+        pub Affinity: usize,
+        pub PriorityClass: u32,
+        pub SchedulingClass: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct IO_COUNTERS {
+        pub ReadOperationCount: u64,
+        pub WriteOperationCount: u64,
+        pub OtherOperationCount: u64,
+        pub ReadTransferCount: u64,
+        pub WriteTransferCount: u64,
+        pub OtherTransferCount: u64,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct JOBOBJECT_EXTENDED_LIMIT_INFORMATION {
+        pub BasicLimitInformation: JOBOBJECT_BASIC_LIMIT_INFORMATION,
+        pub IoInfo: IO_COUNTERS,
+        pub ProcessMemoryLimit: usize,
+        pub JobMemoryLimit: usize,
+        pub PeakProcessMemoryUsed: usize,
+        pub PeakJobMemoryUsed: usize,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        pub fn CreateJobObjectW(attrs: *mut c_void, name: *const u16) -> *mut c_void;
+        pub fn SetInformationJobObject(
+            job: *mut c_void,
+            class: u32,
+            info: *const c_void,
+            len: u32,
+        ) -> i32;
+        pub fn AssignProcessToJobObject(job: *mut c_void, process: *mut c_void) -> i32;
+        pub fn OpenProcess(access: u32, inherit: i32, pid: u32) -> *mut c_void;
+        pub fn CloseHandle(handle: *mut c_void) -> i32;
+        pub fn QueryFullProcessImageNameW(
+            handle: *mut c_void,
+            flags: u32,
+            buf: *mut u16,
+            len: *mut u32,
+        ) -> i32;
+        pub fn GetProcessTimes(
+            handle: *mut c_void,
+            creation: *mut FILETIME,
+            exit: *mut FILETIME,
+            kernel: *mut FILETIME,
+            user: *mut FILETIME,
+        ) -> i32;
+    }
+
+    #[repr(C)]
+    #[derive(Default, Clone, Copy)]
+    pub struct FILETIME {
+        pub dwLowDateTime: u32,
+        pub dwHighDateTime: u32,
+    }
+
+    impl FILETIME {
+        /// The raw 100-nanosecond tick count.
+        pub fn ticks(self) -> u64 {
+            (u64::from(self.dwHighDateTime) << 32) | u64::from(self.dwLowDateTime)
+        }
+    }
+}
+
+/// Create a job object with the configured flags and put `pid` in it.
+/// On success the job handle is deliberately kept open for the daemon's
+/// lifetime: with `kill_on_close`, closing it would kill the app.
+pub(crate) fn apply_job_object_impl(
+    pid: u32,
+    config: &bunctl_core::config::JobObjectConfig,
+) -> std::io::Result<()> {
+    use ffi::*;
+    unsafe {
+        let handle = CreateJobObjectW(std::ptr::null_mut(), std::ptr::null());
+        if handle.is_null() {
+            return Err(std::io::Error::last_os_error());
+        }
+        let mut info = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+        if config.breakaway_ok {
+            info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_BREAKAWAY_OK;
+        }
+        if config.silent_breakaway {
+            info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_SILENT_BREAKAWAY_OK;
+        }
+        if config.kill_on_close {
+            info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+        }
+        if SetInformationJobObject(
+            handle,
+            JOB_OBJECT_EXTENDED_LIMIT_INFORMATION_CLASS,
+            std::ptr::addr_of!(info).cast(),
+            std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+        ) == 0
+        {
+            let err = std::io::Error::last_os_error();
+            CloseHandle(handle);
+            return Err(err);
+        }
+        let process = OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, 0, pid);
+        if process.is_null() {
+            let err = std::io::Error::last_os_error();
+            CloseHandle(handle);
+            return Err(err);
+        }
+        let assigned = AssignProcessToJobObject(handle, process);
+        CloseHandle(process);
+        if assigned == 0 {
+            let err = std::io::Error::last_os_error();
+            CloseHandle(handle);
+            return Err(err);
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn is_alive_impl(pid: u32) -> bool {
+    std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {pid}"), "/NH"])
+        .output()
+        .map(|out| String::from_utf8_lossy(&out.stdout).contains(&pid.to_string()))
+        .unwrap_or(false)
+}
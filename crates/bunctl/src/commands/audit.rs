@@ -0,0 +1,43 @@
+use bunctl_core::audit::AuditEntry;
+use bunctl_core::time::rfc3339;
+
+/// Render audit entries as a TIME / PEER / ACTION / APP / RESULT table.
+pub fn render(entries: &[AuditEntry]) {
+    if entries.is_empty() {
+        println!("no audit entries in the requested window");
+        return;
+    }
+    println!("{:<22} {:<16} {:<8} {:<16} RESULT", "TIME", "PEER", "ACTION", "APP");
+    for entry in entries {
+        let result = if entry.success {
+            "ok".to_owned()
+        } else {
+            format!("error: {}", entry.detail.as_deref().unwrap_or("unknown"))
+        };
+        println!(
+            "{:<22} {:<16} {:<8} {:<16} {result}",
+            rfc3339(entry.ts),
+            entry.peer,
+            entry.action,
+            entry.app.as_deref().unwrap_or("-"),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_without_panicking() {
+        render(&[]);
+        render(&[AuditEntry {
+            ts: 0,
+            peer: "uid:0".into(),
+            action: "stop".into(),
+            app: Some("api".into()),
+            success: false,
+            detail: Some("app not found: api".into()),
+        }]);
+    }
+}
@@ -0,0 +1,213 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use bunctl_client::BunctlClient;
+use bunctl_core::config::{DeployConfig, CONFIG_FILE};
+use bunctl_core::BunctlConfig;
+
+use super::restart;
+
+/// Run the deploy workflow for one app (or every app with a `deploy`
+/// section): git checkout/pull, pre-deploy commands, rolling reload,
+/// post-deploy commands. Aborts on the first failing step.
+pub async fn run(
+    client: &mut BunctlClient,
+    name: Option<&str>,
+    config_path: Option<&Path>,
+) -> Result<i32> {
+    let path = config_path.unwrap_or(Path::new(CONFIG_FILE));
+    let config = BunctlConfig::load(path)
+        .with_context(|| format!("cannot load config from {}", path.display()))?;
+
+    let targets: Vec<_> = match name {
+        Some(name) => {
+            let Some(app) = config.app(name) else {
+                bail!("app '{name}' not found in {}", path.display());
+            };
+            if app.deploy.is_none() {
+                bail!("app '{name}' has no deploy section");
+            }
+            vec![app.clone()]
+        }
+        None => config.apps.iter().filter(|app| app.deploy.is_some()).cloned().collect(),
+    };
+    if targets.is_empty() {
+        bail!("no apps with a deploy section in {}", path.display());
+    }
+
+    for app in &targets {
+        let deploy = app.deploy.as_ref().expect("targets are filtered on deploy");
+        println!("deploying {} from {} ({})", app.name, deploy.repo, deploy.branch);
+        let workdir = if deploy.releases {
+            let release = new_release(deploy).await?;
+            for argv in &deploy.pre_deploy {
+                run_step(&release, argv).await?;
+            }
+            flip_current(&deploy.path, &release)?;
+            prune_releases(deploy);
+            release
+        } else {
+            checkout(deploy).await?;
+            for argv in &deploy.pre_deploy {
+                run_step(&deploy.path, argv).await?;
+            }
+            deploy.path.clone()
+        };
+        // Reload: rolling restart when managed, plain start on first deploy.
+        if client.status(&app.name).await.is_ok() {
+            restart::rolling(client, &app.name, 1, "0s").await?;
+        } else {
+            client.start(app.clone()).await?;
+            println!("started {}", app.name);
+        }
+        for argv in &deploy.post_deploy {
+            run_step(&workdir, argv).await?;
+        }
+        println!("deployed {}", app.name);
+    }
+    Ok(0)
+}
+
+/// Flip the `current` symlink back to the release before the one it points
+/// at and restart the app.
+pub async fn rollback(
+    client: &mut BunctlClient,
+    name: &str,
+    config_path: Option<&Path>,
+) -> Result<i32> {
+    let path = config_path.unwrap_or(Path::new(CONFIG_FILE));
+    let config = BunctlConfig::load(path)
+        .with_context(|| format!("cannot load config from {}", path.display()))?;
+    let Some(app) = config.app(name) else {
+        bail!("app '{name}' not found in {}", path.display());
+    };
+    let Some(deploy) = &app.deploy else {
+        bail!("app '{name}' has no deploy section");
+    };
+    if !deploy.releases {
+        bail!("app '{name}' does not use release directories (set \"releases\": true)");
+    }
+
+    let current = std::fs::read_link(deploy.path.join("current"))
+        .with_context(|| format!("no current release for '{name}'"))?;
+    let releases = list_releases(&deploy.path);
+    let current_ts = release_timestamp(&current);
+    let Some(previous) = releases
+        .iter()
+        .rev()
+        .find(|(ts, _)| current_ts.is_some_and(|cur| *ts < cur))
+    else {
+        bail!("no release older than the current one to roll back to");
+    };
+    println!(
+        "rolling back {name}: {} -> {}",
+        current.display(),
+        previous.1.display()
+    );
+    flip_current(&deploy.path, &previous.1)?;
+    restart::rolling(client, name, 1, "0s").await
+}
+
+/// Clone the branch into a fresh `releases/<timestamp>` directory.
+async fn new_release(deploy: &DeployConfig) -> Result<std::path::PathBuf> {
+    let release = deploy
+        .path
+        .join("releases")
+        .join(bunctl_core::time::unix_now().to_string());
+    std::fs::create_dir_all(release.parent().expect("release dir has a parent"))?;
+    let dir = release.to_string_lossy().into_owned();
+    git(&["clone", "--branch", &deploy.branch, &deploy.repo, &dir]).await?;
+    Ok(release)
+}
+
+/// Atomically point `<base>/current` at `release` (symlink + rename).
+fn flip_current(base: &Path, release: &Path) -> Result<()> {
+    let tmp = base.join("current.tmp");
+    let _ = std::fs::remove_file(&tmp);
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(release, &tmp)?;
+    #[cfg(windows)]
+    std::os::windows::fs::symlink_dir(release, &tmp)?;
+    std::fs::rename(&tmp, base.join("current"))?;
+    Ok(())
+}
+
+/// Timestamp-named release directories under `<base>/releases`, oldest
+/// first.
+fn list_releases(base: &Path) -> Vec<(u64, std::path::PathBuf)> {
+    let mut releases: Vec<(u64, std::path::PathBuf)> = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(base.join("releases")) {
+        for entry in entries.flatten() {
+            if let Some(ts) = entry.file_name().to_str().and_then(|n| n.parse().ok()) {
+                releases.push((ts, entry.path()));
+            }
+        }
+    }
+    releases.sort_by_key(|(ts, _)| *ts);
+    releases
+}
+
+fn release_timestamp(release: &Path) -> Option<u64> {
+    release.file_name()?.to_str()?.parse().ok()
+}
+
+/// Drop the oldest releases beyond `keep_releases`, never touching the one
+/// `current` points at.
+fn prune_releases(deploy: &DeployConfig) {
+    let current = std::fs::read_link(deploy.path.join("current")).ok();
+    let releases = list_releases(&deploy.path);
+    let excess = releases.len().saturating_sub(deploy.keep_releases.max(1) as usize);
+    for (_, release) in releases.into_iter().take(excess) {
+        if current.as_deref() == Some(release.as_path()) {
+            continue;
+        }
+        if let Err(err) = std::fs::remove_dir_all(&release) {
+            eprintln!("warning: cannot prune release {}: {err}", release.display());
+        }
+    }
+}
+
+/// Clone the repo on first deploy, otherwise fetch and fast-forward the
+/// configured branch.
+async fn checkout(deploy: &DeployConfig) -> Result<()> {
+    if deploy.path.join(".git").exists() {
+        let dir = deploy.path.to_string_lossy().into_owned();
+        git(&["-C", &dir, "fetch", "origin", &deploy.branch]).await?;
+        git(&["-C", &dir, "checkout", &deploy.branch]).await?;
+        git(&["-C", &dir, "pull", "--ff-only", "origin", &deploy.branch]).await
+    } else {
+        let dir = deploy.path.to_string_lossy().into_owned();
+        git(&["clone", "--branch", &deploy.branch, &deploy.repo, &dir]).await
+    }
+}
+
+async fn git(args: &[&str]) -> Result<()> {
+    let status = tokio::process::Command::new("git")
+        .args(args)
+        .status()
+        .await
+        .context("cannot run git")?;
+    if !status.success() {
+        bail!("git {} failed ({status})", args.join(" "));
+    }
+    Ok(())
+}
+
+/// Run one configured step as an explicit argv array in the deploy path —
+/// never through a shell.
+async fn run_step(cwd: &Path, argv: &[String]) -> Result<()> {
+    let Some((program, args)) = argv.split_first() else {
+        bail!("empty deploy command");
+    };
+    println!("running {}", argv.join(" "));
+    let status = tokio::process::Command::new(program)
+        .args(args)
+        .current_dir(cwd)
+        .status()
+        .await
+        .with_context(|| format!("cannot run {program}"))?;
+    if !status.success() {
+        bail!("deploy step '{}' failed ({status})", argv.join(" "));
+    }
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use std::path::Path;
+
+use anyhow::Result;
+use bunctl_client::BunctlClient;
+use bunctl_core::AppConfig;
+
+/// Compare the daemon's cached config of `name` with what the config file
+/// currently says; returns exit code 1 when they have drifted (so scripts
+/// can gate restarts on it).
+pub async fn run(client: &mut BunctlClient, name: &str, config: Option<&Path>) -> Result<i32> {
+    let daemon = client.config(name).await?;
+    let file = super::start::load_app(name, config)?;
+    let drift = render(&daemon, &file);
+    Ok(i32::from(drift))
+}
+
+/// Print a field-level diff of the two configs; `true` when they differ.
+pub fn render(daemon: &AppConfig, file: &AppConfig) -> bool {
+    let daemon = fields(daemon);
+    let file = fields(file);
+    let mut keys: Vec<&String> = daemon.keys().chain(file.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    let mut drift = false;
+    for key in keys {
+        let old = daemon.get(key);
+        let new = file.get(key);
+        if old == new {
+            continue;
+        }
+        drift = true;
+        println!("{key}:");
+        println!("  daemon: {}", old.map_or("(unset)".into(), ToString::to_string));
+        println!("  file:   {}", new.map_or("(unset)".into(), ToString::to_string));
+    }
+    if !drift {
+        println!("in sync: a restart will not change behavior");
+    }
+    drift
+}
+
+/// Flatten a config into its top-level JSON fields.
+fn fields(config: &AppConfig) -> serde_json::Map<String, serde_json::Value> {
+    match serde_json::to_value(config) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_field_level_drift() {
+        let daemon = AppConfig {
+            name: "api".into(),
+            command: "bun".into(),
+            ..AppConfig::default()
+        };
+        let mut file = daemon.clone();
+        assert!(!render(&daemon, &file));
+        file.max_memory = Some(512 << 20);
+        assert!(render(&daemon, &file));
+    }
+}
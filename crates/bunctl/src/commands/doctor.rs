@@ -0,0 +1,288 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::fleet::Target;
+
+/// One diagnostic verdict: what was checked, whether it passed, and — on
+/// failure — what to do about it.
+struct Check {
+    name: &'static str,
+    passed: bool,
+    detail: String,
+    hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, passed: true, detail: detail.into(), hint: None }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { name, passed: false, detail: detail.into(), hint: Some(hint.into()) }
+    }
+}
+
+/// Run the environment self-diagnostics and print one pass/fail line per
+/// check, with a remediation hint under each failure. Returns exit code 1
+/// when anything failed.
+pub async fn run(target: &Target, token: Option<&str>) -> Result<i32> {
+    let mut checks = Vec::new();
+
+    if let Some(mode) = check_mode() {
+        checks.push(mode);
+    }
+    checks.push(check_daemon(target, token).await);
+    checks.push(check_dir("log directory", &bunctl_logging::default_log_dir()));
+    if let Target::Local(socket) = target {
+        if let Some(run_dir) = socket.parent() {
+            checks.push(check_dir("run directory", run_dir));
+        }
+    }
+    checks.push(check_bun().await);
+    if let Some(check) = check_open_files() {
+        checks.push(check);
+    }
+    checks.push(check_clock());
+    checks.push(check_pid_records());
+
+    let mut failed = 0;
+    for check in &checks {
+        let verdict = if check.passed {
+            crate::output::paint("  ok", "32")
+        } else {
+            failed += 1;
+            crate::output::paint("FAIL", "31")
+        };
+        println!("{verdict}  {}: {}", check.name, check.detail);
+        if let Some(hint) = &check.hint {
+            println!("      hint: {hint}");
+        }
+    }
+    if failed > 0 {
+        println!("{failed} of {} checks failed", checks.len());
+    }
+    Ok(i32::from(failed > 0))
+}
+
+/// Which mode the default paths select: rootless (per-user, the supported
+/// default for euid != 0 — no operation needs root) or the system instance
+/// under /run and /var when running as root.
+fn check_mode() -> Option<Check> {
+    #[cfg(unix)]
+    {
+        extern "C" {
+            fn geteuid() -> u32;
+        }
+        let euid = unsafe { geteuid() };
+        Some(if euid == 0 {
+            Check::pass("mode", "system instance (root; state under /run and /var)")
+        } else {
+            Check::pass("mode", format!("rootless (uid {euid}; per-user paths, no root needed)"))
+        })
+    }
+    #[cfg(not(unix))]
+    None
+}
+
+/// Is the daemon answering on its socket? A socket file with nobody
+/// listening behind it is reported as stale.
+async fn check_daemon(target: &Target, token: Option<&str>) -> Check {
+    let location = match target {
+        Target::Local(path) => path.display().to_string(),
+        Target::Remote(host) => host.clone(),
+        Target::Fleet(_) => unreachable!("doctor runs against a single daemon"),
+    };
+    match super::connect(target, token, None).await {
+        Ok(mut client) => match client.ping().await {
+            Ok(()) => Check::pass("daemon", format!("reachable at {location}")),
+            Err(err) => Check::fail(
+                "daemon",
+                format!("connected to {location} but ping failed: {err}"),
+                "the daemon may be shutting down or wedged; try `bunctl shutdown` and restart it",
+            ),
+        },
+        Err(_) => {
+            if let Target::Local(path) = target {
+                if path.exists() {
+                    return Check::fail(
+                        "daemon",
+                        format!("stale socket at {location} (nobody listening)"),
+                        format!("remove {location} and start the daemon with `bunctl-daemon`"),
+                    );
+                }
+            }
+            Check::fail(
+                "daemon",
+                format!("not reachable at {location}"),
+                "start it with `bunctl-daemon` (apps auto-start it on `bunctl start`)",
+            )
+        }
+    }
+}
+
+/// The directory must exist and be writable by us; probed by creating and
+/// removing a scratch file rather than interpreting permission bits.
+fn check_dir(name: &'static str, dir: &Path) -> Check {
+    if !dir.is_dir() {
+        return Check::fail(
+            name,
+            format!("{} does not exist", dir.display()),
+            format!("create it with `mkdir -p {}`", dir.display()),
+        );
+    }
+    let probe = dir.join(format!(".bunctl-doctor-{}", std::process::id()));
+    match std::fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            Check::pass(name, format!("{} is writable", dir.display()))
+        }
+        Err(err) => Check::fail(
+            name,
+            format!("{} is not writable: {err}", dir.display()),
+            format!("fix ownership or mode of {}", dir.display()),
+        ),
+    }
+}
+
+/// Is a bun binary on PATH, and which version?
+async fn check_bun() -> Check {
+    match tokio::process::Command::new("bun").arg("--version").output().await {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+            Check::pass("bun", format!("version {version}"))
+        }
+        Ok(output) => Check::fail(
+            "bun",
+            format!("`bun --version` exited with {}", output.status),
+            "reinstall bun from https://bun.sh",
+        ),
+        Err(_) => Check::fail(
+            "bun",
+            "not found in PATH".to_owned(),
+            "install bun from https://bun.sh, or set an absolute `command` per app",
+        ),
+    }
+}
+
+/// Soft open-file limit, read from /proc on Linux. Each supervised app
+/// costs several descriptors (pipes, sockets, log files), so a tight limit
+/// bites well before it looks exhausted.
+fn check_open_files() -> Option<Check> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let limits = std::fs::read_to_string("/proc/self/limits").ok()?;
+    let line = limits.lines().find(|l| l.starts_with("Max open files"))?;
+    let soft = line.split_whitespace().nth(3)?;
+    if soft == "unlimited" {
+        return Some(Check::pass("open files", "soft limit unlimited"));
+    }
+    let soft: u64 = soft.parse().ok()?;
+    Some(if soft < 1024 {
+        Check::fail(
+            "open files",
+            format!("soft limit {soft} is low"),
+            "raise it with `ulimit -n 4096` or a limits.d entry",
+        )
+    } else {
+        Check::pass("open files", format!("soft limit {soft}"))
+    })
+}
+
+/// A clock running behind the binary's own modification time means the
+/// system time went backwards since installation — timestamps in logs,
+/// metrics and backoff windows would all be wrong.
+fn check_clock() -> Check {
+    let now = std::time::SystemTime::now();
+    let binary_mtime = std::env::current_exe()
+        .and_then(std::fs::metadata)
+        .and_then(|meta| meta.modified());
+    match binary_mtime {
+        Ok(mtime) if now < mtime => Check::fail(
+            "clock",
+            "system time is before this binary was installed".to_owned(),
+            "check NTP sync; timers and log timestamps are unreliable until fixed",
+        ),
+        _ => Check::pass("clock", bunctl_core::time::rfc3339(bunctl_core::time::unix_now())),
+    }
+}
+
+/// Pid records left by a dead daemon whose processes are gone too. The
+/// daemon cleans these up itself at startup; a pile of them with no daemon
+/// running is what this flags.
+fn check_pid_records() -> Check {
+    let dir = default_pid_dir();
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Check::pass("pid records", "none");
+    };
+    let mut total = 0usize;
+    let mut stale = 0usize;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|e| e != "json") {
+            continue;
+        }
+        total += 1;
+        if let Some(pid) = recorded_pid(&path) {
+            if cfg!(target_os = "linux") && !Path::new(&format!("/proc/{pid}")).exists() {
+                stale += 1;
+            }
+        }
+    }
+    if stale > 0 {
+        Check::fail(
+            "pid records",
+            format!("{stale} of {total} in {} point at dead processes", dir.display()),
+            "the daemon discards them at startup; delete them if no daemon will run again",
+        )
+    } else if total > 0 {
+        Check::pass("pid records", format!("{total} in {}", dir.display()))
+    } else {
+        Check::pass("pid records", "none")
+    }
+}
+
+fn recorded_pid(path: &Path) -> Option<u64> {
+    let text = std::fs::read_to_string(path).ok()?;
+    let record: serde_json::Value = serde_json::from_str(&text).ok()?;
+    record["pid"].as_u64()
+}
+
+/// Same per-user location the daemon's pid registry uses; duplicated here
+/// because the CLI does not link the daemon crate.
+fn default_pid_dir() -> PathBuf {
+    #[cfg(unix)]
+    {
+        extern "C" {
+            fn geteuid() -> u32;
+        }
+        if unsafe { geteuid() } == 0 {
+            return PathBuf::from("/var/lib/bunctl/pids");
+        }
+    }
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(dir) = std::env::var_os("XDG_STATE_HOME").filter(|d| !d.is_empty()) {
+            return PathBuf::from(dir).join("bunctl").join("pids");
+        }
+        std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("/tmp"))
+            .join(".local/state/bunctl/pids")
+    }
+    #[cfg(target_os = "macos")]
+    {
+        std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("/tmp"))
+            .join("Library/Application Support/bunctl/pids")
+    }
+    #[cfg(windows)]
+    {
+        match std::env::var_os("LOCALAPPDATA") {
+            Some(dir) => PathBuf::from(dir).join("bunctl").join("pids"),
+            None => PathBuf::from(r"C:\bunctl\pids"),
+        }
+    }
+}
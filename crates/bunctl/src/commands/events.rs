@@ -0,0 +1,73 @@
+use bunctl_core::time::rfc3339;
+use bunctl_core::{DaemonEvent, EventRecord};
+
+/// Render persisted events as a TIME / APP / EVENT / DETAIL table.
+pub fn render(events: &[EventRecord]) {
+    if events.is_empty() {
+        println!("no events in the requested window");
+        return;
+    }
+    println!("{:<22} {:<16} {:<26} DETAIL", "TIME", "APP", "EVENT");
+    for record in events {
+        println!(
+            "{:<22} {:<16} {:<26} {}",
+            rfc3339(record.ts),
+            record.app.as_deref().unwrap_or("-"),
+            record.event.event_type(),
+            detail(&record.event),
+        );
+    }
+}
+
+/// One-line human summary of an event's payload.
+fn detail(event: &DaemonEvent) -> String {
+    match event {
+        DaemonEvent::ProcessStarted { pid } => format!("pid {pid}"),
+        DaemonEvent::ProcessExited { code, reason, .. } => match code {
+            Some(code) => format!("code {code}"),
+            None => reason.to_string(),
+        },
+        DaemonEvent::StatusChange { state } => state.to_string(),
+        DaemonEvent::StartTimeout { elapsed_secs } => format!("after {elapsed_secs}s"),
+        DaemonEvent::RestartBudgetExhausted { resume_secs } => {
+            format!("resumes in {resume_secs}s")
+        }
+        DaemonEvent::Recycled { trigger, uptime_secs } => {
+            format!("{trigger} after {uptime_secs}s up")
+        }
+        DaemonEvent::LogLine { line, .. } => line.clone(),
+        DaemonEvent::HealthChanged { healthy: true, .. } => "healthy".into(),
+        DaemonEvent::HealthChanged { healthy: false, failed } => {
+            format!("unhealthy: {}", failed.join(", "))
+        }
+        DaemonEvent::ReadinessChanged { ready: true, .. } => "ready".into(),
+        DaemonEvent::ReadinessChanged { ready: false, failed } => {
+            format!("not ready: {}", failed.join(", "))
+        }
+        DaemonEvent::ResourceLimitExceeded { resource, current, limit, over_secs } => {
+            format!("{resource} {current:.1} > {limit:.1} for {over_secs}s")
+        }
+        DaemonEvent::ConnectionLimitReached { active } => format!("{active} active"),
+        DaemonEvent::DaemonShutdown => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_without_panicking() {
+        render(&[]);
+        render(&[EventRecord {
+            ts: 0,
+            seq: 3,
+            app: Some("api".into()),
+            event: DaemonEvent::ProcessExited {
+                code: Some(1),
+                reason: bunctl_core::ExitReason::Code { code: 1 },
+                core: None,
+            },
+        }]);
+    }
+}
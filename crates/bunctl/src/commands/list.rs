@@ -0,0 +1,157 @@
+use anyhow::Result;
+use bunctl_core::AppStatus;
+use clap::ValueEnum;
+
+use crate::output::{format_memory, format_uptime, truncate};
+
+/// Sort key for the list table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    Name,
+    State,
+    Pid,
+    Cpu,
+    Mem,
+    Uptime,
+    Restarts,
+}
+
+/// Render the list of statuses as JSON or an aligned table.
+pub fn render(mut statuses: Vec<AppStatus>, json: bool, wide: bool, sort: SortKey) -> Result<()> {
+    sort_statuses(&mut statuses, sort);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&statuses)?);
+        return Ok(());
+    }
+    print!("{}", table(&statuses, wide));
+    Ok(())
+}
+
+fn sort_statuses(statuses: &mut [AppStatus], sort: SortKey) {
+    match sort {
+        SortKey::Name => statuses.sort_by(|a, b| a.name.cmp(&b.name)),
+        SortKey::State => statuses.sort_by_key(|s| s.state as u8),
+        SortKey::Pid => statuses.sort_by_key(|s| s.pid),
+        SortKey::Cpu => statuses.sort_by(|a, b| {
+            b.cpu_percent
+                .unwrap_or(0.0)
+                .total_cmp(&a.cpu_percent.unwrap_or(0.0))
+        }),
+        SortKey::Mem => statuses.sort_by_key(|s| std::cmp::Reverse(s.memory_bytes)),
+        SortKey::Uptime => statuses.sort_by_key(|s| std::cmp::Reverse(s.uptime_secs)),
+        SortKey::Restarts => statuses.sort_by_key(|s| std::cmp::Reverse(s.restarts)),
+    }
+}
+
+/// Build the aligned table; separate from printing so it can be tested.
+pub(crate) fn table(statuses: &[AppStatus], wide: bool) -> String {
+    let mut rows: Vec<Vec<String>> = Vec::with_capacity(statuses.len() + 1);
+    let mut header = vec!["NAME".into(), "STATE".into(), "PID".into(), "MEM".into()];
+    if wide {
+        header.extend([
+            "INST".into(),
+            "CPU".into(),
+            "UPTIME".into(),
+            "RESTARTS".into(),
+            "TAGS".into(),
+        ]);
+    }
+    rows.push(header);
+    for s in statuses {
+        let state = if s.orphan { "orphan".into() } else { s.state.to_string() };
+        let mut row = vec![
+            s.name.to_string(),
+            state,
+            s.pid.map_or_else(|| "-".into(), |p| p.to_string()),
+            s.memory_bytes.map_or_else(|| "-".into(), format_memory),
+        ];
+        if wide {
+            row.extend([
+                match (s.instance_id, s.instances_total) {
+                    (Some(i), Some(n)) => format!("{i}/{n}"),
+                    _ => "-".into(),
+                },
+                s.cpu_percent.map_or_else(|| "-".into(), |c| format!("{c:.1}%")),
+                s.uptime_secs.map_or_else(|| "-".into(), format_uptime),
+                s.restarts.to_string(),
+                if s.tags.is_empty() { "-".into() } else { truncate(&s.tags.join(","), 24) },
+            ]);
+        }
+        rows.push(row);
+    }
+
+    let cols = rows[0].len();
+    let widths: Vec<usize> = (0..cols)
+        .map(|c| rows.iter().map(|r| r[c].len()).max().unwrap_or(0))
+        .collect();
+    let mut out = String::new();
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            if i > 0 {
+                out.push_str("  ");
+            }
+            out.push_str(cell);
+            if i < cols - 1 {
+                for _ in cell.len()..widths[i] {
+                    out.push(' ');
+                }
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bunctl_core::{AppId, AppState};
+
+    fn status(name: &str, mem: Option<u64>) -> AppStatus {
+        AppStatus {
+            name: AppId::new(name),
+            original_name: None,
+            instance_id: None,
+            instances_total: None,
+            instances: Vec::new(),
+            description: None,
+            tags: Vec::new(),
+            state: AppState::Running,
+            pid: Some(42),
+            cpu_percent: None,
+            memory_bytes: mem,
+            uptime_secs: Some(61),
+            ready: None,
+            on_demand: None,
+            threads: None,
+            open_files: None,
+            heap_bytes: None,
+            event_loop_lag_ms: None,
+            cwd: None,
+            env: Default::default(),
+            restarts: 0,
+            last_exit_reason: None,
+            log_metrics: Default::default(),
+            orphan: false,
+            cpu_history: Vec::new(),
+            memory_history: Vec::new(),
+            spawn_info: None,
+        }
+    }
+
+    #[test]
+    fn table_aligns_columns() {
+        let out = table(&[status("api", Some(2 * 1024 * 1024)), status("worker-long", None)], false);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("NAME         STATE"));
+        assert!(lines[1].contains("2.0MiB"));
+    }
+
+    #[test]
+    fn sorts_by_memory_descending() {
+        let mut list = vec![status("small", Some(10)), status("big", Some(1000))];
+        sort_statuses(&mut list, SortKey::Mem);
+        assert_eq!(list[0].name.as_str(), "big");
+    }
+}
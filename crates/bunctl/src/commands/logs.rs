@@ -0,0 +1,83 @@
+use anyhow::Result;
+use bunctl_client::BunctlClient;
+use bunctl_core::DaemonEvent;
+use bunctl_ipc::message::{IpcRequest, IpcResponse, SubscriptionType};
+use futures::StreamExt;
+
+use crate::output::paint;
+
+/// ANSI color codes cycled through for `[app]` prefixes in merged views.
+const PREFIX_COLORS: [&str; 6] = ["36", "35", "33", "32", "34", "91"];
+
+fn prefix(app: &str) -> String {
+    let hash: usize = app.bytes().fold(0usize, |acc, b| acc.wrapping_mul(31).wrapping_add(b as usize));
+    paint(&format!("[{app}]"), PREFIX_COLORS[hash % PREFIX_COLORS.len()])
+}
+
+/// Show recent lines of every app: per-app blocks by default, or one
+/// chronologically interleaved stream with `[app]` prefixes (`--merge`).
+pub async fn all(client: &mut BunctlClient, lines: usize, merge: bool) -> Result<i32> {
+    let mut per_app: Vec<(String, Vec<String>)> = Vec::new();
+    for status in client.list(false).await? {
+        let name = status.name.to_string();
+        let req = IpcRequest::Logs {
+            name: name.clone(),
+            lines,
+            include_stopped: false,
+            grep: None,
+        };
+        match client.request(&req).await? {
+            IpcResponse::Logs { lines } => per_app.push((name, lines)),
+            IpcResponse::Error { .. } => continue, // e.g. nothing captured yet
+            resp => return super::render(&resp),
+        }
+    }
+
+    if !merge {
+        for (idx, (app, lines)) in per_app.iter().enumerate() {
+            if idx > 0 {
+                println!();
+            }
+            println!("{}", prefix(app));
+            for line in lines {
+                println!("{line}");
+            }
+        }
+        return Ok(0);
+    }
+
+    // Entry timestamps are RFC 3339, so their lexical order is their
+    // chronological order; continuation lines keep their entry's key.
+    let mut merged: Vec<(String, &str, &str)> = Vec::new();
+    for (app, lines) in &per_app {
+        let mut key = String::new();
+        for line in lines {
+            if let Some(ts) = line.strip_prefix('[').and_then(|l| l.split(']').next()) {
+                key = ts.to_owned();
+            }
+            merged.push((key.clone(), app, line));
+        }
+    }
+    merged.sort_by(|a, b| a.0.cmp(&b.0));
+    for (_, app, line) in merged {
+        println!("{} {line}", prefix(app));
+    }
+    Ok(0)
+}
+
+/// Stream new log lines as they are captured, for one app or all of them,
+/// until interrupted.
+pub async fn follow(client: BunctlClient, app: Option<String>) -> Result<i32> {
+    let stream = client.subscribe(SubscriptionType::Logs, app).await?;
+    futures::pin_mut!(stream);
+    while let Some(event) = stream.next().await {
+        let event = event?;
+        if let DaemonEvent::LogLine { line, .. } = event.event {
+            match event.app {
+                Some(app) => println!("{} {line}", prefix(&app)),
+                None => println!("{line}"),
+            }
+        }
+    }
+    Ok(0)
+}
@@ -0,0 +1,33 @@
+use bunctl_core::metrics::MetricSample;
+use bunctl_core::time::rfc3339;
+
+use crate::output::format_memory;
+
+/// Render persisted samples as a TIME / CPU / MEM / RESTARTS table.
+pub fn render(samples: &[MetricSample]) {
+    if samples.is_empty() {
+        println!("no samples in the requested window");
+        return;
+    }
+    println!("{:<22} {:>7} {:>10} {:>9}", "TIME", "CPU", "MEM", "RESTARTS");
+    for sample in samples {
+        println!(
+            "{:<22} {:>6.1}% {:>10} {:>9}",
+            rfc3339(sample.ts),
+            sample.cpu_percent,
+            format_memory(sample.memory_bytes),
+            sample.restarts
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_without_panicking() {
+        render(&[]);
+        render(&[MetricSample { ts: 0, cpu_percent: 12.5, memory_bytes: 2048, restarts: 1 }]);
+    }
+}
@@ -0,0 +1,449 @@
+mod audit;
+mod events;
+mod deploy;
+mod diff;
+mod doctor;
+pub mod list;
+mod logs;
+mod metrics;
+mod report;
+mod restart;
+mod start;
+mod status;
+mod top;
+
+use anyhow::{bail, Context, Result};
+use bunctl_client::BunctlClient;
+use bunctl_ipc::message::{EventFilter, IpcRequest, IpcResponse};
+
+use crate::fleet::Target;
+use crate::{Cli, Command};
+
+/// Execute the parsed CLI invocation; returns the process exit code.
+pub async fn run(cli: Cli) -> Result<i32> {
+    let target = Target::from_cli(&cli);
+    let timeout = cli.timeout.map(std::time::Duration::from_secs);
+
+    // List has local rendering options, so it bypasses the generic
+    // request/render path (except in fleet mode, which keeps summaries).
+    if let (Command::List { all, json, wide, sort, tag }, false) =
+        (&cli.command, matches!(target, Target::Fleet(_)))
+    {
+        let mut client = connect(&target, cli.token.as_deref(), timeout).await?;
+        let mut statuses = match request(&mut client, &IpcRequest::List { all: *all }).await? {
+            IpcResponse::StatusList { statuses } => statuses,
+            resp => return render(&resp),
+        };
+        if let Some(tag) = tag {
+            statuses.retain(|s| s.tags.iter().any(|t| t == tag));
+        }
+        list::render(statuses, *json, *wide, *sort)?;
+        return Ok(0);
+    }
+
+    // Verification reads the local log directory; no daemon involved.
+    if let Command::Logs { name, verify: true, .. } = &cli.command {
+        let Some(name) = name else { bail!("logs --verify needs an app name") };
+        let logs = bunctl_logging::LogManager::new(bunctl_logging::default_log_dir())?;
+        let problems = logs.verify(&bunctl_core::AppId::new(name))?;
+        if problems.is_empty() {
+            crate::output::note(&format!("manifest of {name} verifies"));
+            return Ok(0);
+        }
+        for problem in &problems {
+            eprintln!("{problem}");
+        }
+        return Ok(1);
+    }
+
+    // Merged/multi-app views and --follow orchestrate their own requests
+    // (or take over the connection), so they bypass the generic path.
+    if let Command::Logs { name, lines, merge, follow, verify: false, daemon: false, .. } =
+        &cli.command
+    {
+        if *follow || *merge || name.is_none() {
+            if matches!(target, Target::Fleet(_)) {
+                bail!("logs --merge/--follow cannot fan out to --hosts");
+            }
+            let mut client = connect(&target, cli.token.as_deref(), timeout).await?;
+            return if *follow {
+                logs::follow(client, name.clone()).await
+            } else {
+                logs::all(&mut client, *lines, *merge).await
+            };
+        }
+    }
+
+    // Deploy runs local commands between daemon requests; single daemon only.
+    if let (Command::Deploy { name, config }, false) =
+        (&cli.command, matches!(target, Target::Fleet(_)))
+    {
+        let mut client = connect(&target, cli.token.as_deref(), timeout).await?;
+        return deploy::run(&mut client, name.as_deref(), config.as_deref()).await;
+    }
+
+    if let (Command::Rollback { name, config }, false) =
+        (&cli.command, matches!(target, Target::Fleet(_)))
+    {
+        let mut client = connect(&target, cli.token.as_deref(), timeout).await?;
+        return deploy::rollback(&mut client, name, config.as_deref()).await;
+    }
+
+    // Diff compares against the local config file, so it cannot fan out.
+    if let (Command::Diff { name, config }, false) =
+        (&cli.command, matches!(target, Target::Fleet(_)))
+    {
+        let mut client = connect(&target, cli.token.as_deref(), timeout).await?;
+        return diff::run(&mut client, name, config.as_deref()).await;
+    }
+
+    // Doctor inspects the local environment and makes its own connection
+    // attempt, so it bypasses the generic path entirely.
+    if let (Command::Doctor, false) = (&cli.command, matches!(target, Target::Fleet(_))) {
+        return doctor::run(&target, cli.token.as_deref()).await;
+    }
+
+    // A report issues several requests and writes a local archive, so it is
+    // single-daemon only.
+    if let (Command::Report { name, output }, false) =
+        (&cli.command, matches!(target, Target::Fleet(_)))
+    {
+        let mut client = connect(&target, cli.token.as_deref(), timeout).await?;
+        return report::run(&mut client, name, output.as_deref()).await;
+    }
+
+    // Rolling restarts orchestrate many requests with waits in between, so
+    // they bypass the generic path too (single daemon only).
+    if let (Command::Restart { name, rolling: true, batch, delay, .. }, false) =
+        (&cli.command, matches!(target, Target::Fleet(_)))
+    {
+        let mut client = connect(&target, cli.token.as_deref(), timeout).await?;
+        return restart::rolling(&mut client, name, *batch, delay).await;
+    }
+
+    // Top repaints the terminal in a refresh loop, so it owns the
+    // connection (single daemon only).
+    if let (Command::Top { name, interval, once }, false) =
+        (&cli.command, matches!(target, Target::Fleet(_)))
+    {
+        let mut client = connect(&target, cli.token.as_deref(), timeout).await?;
+        return top::run(&mut client, name, interval, *once).await;
+    }
+
+    // Status --summary needs two requests (the app list and the daemon's own
+    // usage), so it also bypasses the generic path outside fleet mode.
+    if let (Command::Status { summary: true, .. }, false) =
+        (&cli.command, matches!(target, Target::Fleet(_)))
+    {
+        let mut client = connect(&target, cli.token.as_deref(), timeout).await?;
+        let statuses = match request(&mut client, &IpcRequest::Status { name: None }).await? {
+            IpcResponse::StatusList { statuses } => statuses,
+            resp => return render(&resp),
+        };
+        let daemon = match request(&mut client, &IpcRequest::Status { name: Some("daemon".into()) })
+            .await?
+        {
+            IpcResponse::Status(status) => Some(*status),
+            _ => None,
+        };
+        status::render_list(&statuses);
+        status::render_summary(&statuses, daemon.as_ref());
+        return Ok(0);
+    }
+
+    // A dry run is fully local: nothing is spawned and no daemon is needed.
+    if let Command::Start { name, config, rename, cwd, env, max_memory, dry_run: true, .. } =
+        &cli.command
+    {
+        let overrides = start::Overrides {
+            rename: rename.clone(),
+            cwd: cwd.clone(),
+            env: env.clone(),
+            max_memory: max_memory.clone(),
+        };
+        return start::dry_run(name.as_deref(), config.as_deref(), &overrides);
+    }
+
+    let requests: Vec<IpcRequest> = match &cli.command {
+        Command::Start { name, config, rename, cwd, env, max_memory, force, .. } => {
+            let overrides = start::Overrides {
+                rename: rename.clone(),
+                cwd: cwd.clone(),
+                env: env.clone(),
+                max_memory: max_memory.clone(),
+            };
+            start::build_requests(name.as_deref(), config.as_deref(), &overrides, *force)?
+        }
+        Command::Adopt { name, pid } => vec![IpcRequest::Adopt { name: name.clone(), pid: *pid }],
+        Command::Stop { name } => vec![IpcRequest::Stop { name: name.clone() }],
+        Command::Restart { name, update_env, config, .. } => {
+            let config = if *update_env {
+                Some(Box::new(start::load_app(name, config.as_deref())?))
+            } else {
+                None
+            };
+            vec![IpcRequest::Restart { name: name.clone(), config }]
+        }
+        Command::Delete { name, purge_logs, keep_logs } => {
+            let purge_logs = match (purge_logs, keep_logs) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            };
+            vec![IpcRequest::Delete { name: name.clone(), purge_logs }]
+        }
+        Command::Rename { old, new } => {
+            vec![IpcRequest::Rename { old: old.clone(), new: new.clone() }]
+        }
+        Command::Swap { name, config } => start::build_swap_request(name, config.as_deref())?,
+        Command::Deploy { .. } => bail!("deploy runs local commands and cannot fan out to --hosts"),
+        Command::Diff { .. } => bail!("diff reads the local config file and cannot fan out to --hosts"),
+        Command::Rollback { .. } => {
+            bail!("rollback flips local symlinks and cannot fan out to --hosts")
+        }
+        Command::Report { .. } => {
+            bail!("report writes a local archive and cannot fan out to --hosts")
+        }
+        Command::Doctor => {
+            bail!("doctor inspects the local environment and cannot fan out to --hosts")
+        }
+        Command::Status { clients: true, .. } => vec![IpcRequest::Clients],
+        Command::Status { name, .. } => vec![IpcRequest::Status { name: name.clone() }],
+        Command::Describe { name } => vec![IpcRequest::Describe { name: name.clone() }],
+        Command::List { all, .. } => vec![IpcRequest::List { all: *all }],
+        Command::Logs { daemon: true, lines, grep, .. } => {
+            // The daemon writes its own log under the reserved name.
+            vec![IpcRequest::Logs {
+                name: "daemon".into(),
+                lines: *lines,
+                include_stopped: true,
+                grep: grep.clone(),
+            }]
+        }
+        Command::Logs { name, lines, include_stopped, grep, .. } => {
+            // A missing name only reaches here in fleet mode.
+            let Some(name) = name else { bail!("logs across --hosts needs an app name") };
+            vec![IpcRequest::Logs {
+                name: name.clone(),
+                lines: *lines,
+                include_stopped: *include_stopped,
+                grep: grep.clone(),
+            }]
+        }
+        Command::Health { name } => {
+            vec![IpcRequest::Health { name: name.clone() }]
+        }
+        Command::Top { .. } => bail!("top renders a live view and cannot fan out to --hosts"),
+        Command::Metrics { name, since } => {
+            let window = bunctl_core::time::parse_duration(since)
+                .with_context(|| format!("invalid duration: {since}"))?;
+            vec![IpcRequest::Metrics { name: name.clone(), since_secs: window.as_secs() }]
+        }
+        Command::Audit { since } => {
+            let window = bunctl_core::time::parse_duration(since)
+                .with_context(|| format!("invalid duration: {since}"))?;
+            vec![IpcRequest::Audit { since_secs: window.as_secs() }]
+        }
+        Command::Events { since, app, event_type, severity } => {
+            let window = bunctl_core::time::parse_duration(since)
+                .with_context(|| format!("invalid duration: {since}"))?;
+            let min_severity = severity
+                .as_deref()
+                .map(|s| match s {
+                    "info" => Ok(bunctl_core::EventSeverity::Info),
+                    "warning" => Ok(bunctl_core::EventSeverity::Warning),
+                    "error" => Ok(bunctl_core::EventSeverity::Error),
+                    other => bail!("invalid severity: {other} (info, warning or error)"),
+                })
+                .transpose()?;
+            let filter = EventFilter {
+                event_types: event_type.clone(),
+                app_glob: app.clone(),
+                min_severity,
+            };
+            let filter = (filter != EventFilter::default()).then_some(filter);
+            vec![IpcRequest::Events { since_secs: window.as_secs(), filter }]
+        }
+        Command::Export => vec![IpcRequest::Export],
+        Command::Import { file } => {
+            let text = std::fs::read_to_string(file)
+                .with_context(|| format!("cannot read snapshot {}", file.display()))?;
+            let snapshot = serde_json::from_str(&text)
+                .with_context(|| format!("invalid snapshot {}", file.display()))?;
+            vec![IpcRequest::Import { snapshot: Box::new(snapshot) }]
+        }
+        Command::Ping => vec![IpcRequest::Ping],
+        Command::Shutdown => vec![IpcRequest::Shutdown],
+    };
+
+    match target {
+        Target::Fleet(hosts) => crate::fleet::run(&hosts, cli.token.as_deref(), timeout, &requests).await,
+        single => {
+            let mut client = connect(&single, cli.token.as_deref(), timeout).await?;
+            let mut code = 0;
+            for req in &requests {
+                let resp = request(&mut client, req).await?;
+                if render(&resp)? != 0 {
+                    code = 1;
+                }
+            }
+            Ok(code)
+        }
+    }
+}
+
+/// Send one request, reporting timing and the raw response on stderr when
+/// `--verbose` is active.
+async fn request(client: &mut BunctlClient, req: &IpcRequest) -> Result<IpcResponse> {
+    let started = std::time::Instant::now();
+    let resp = client.request(req).await?;
+    if let Ok(raw) = serde_json::to_string(&resp) {
+        crate::output::detail(&format!("ipc: answered in {:.1?}: {raw}", started.elapsed()));
+    }
+    Ok(resp)
+}
+
+/// Open a connection to a single daemon. `timeout`, when set, replaces the
+/// per-command timeout defaults.
+pub async fn connect(
+    target: &Target,
+    token: Option<&str>,
+    timeout: Option<std::time::Duration>,
+) -> Result<BunctlClient> {
+    let mut client = match target {
+        Target::Local(path) => BunctlClient::connect(path)
+            .await
+            .with_context(|| format!("cannot reach daemon at {} (is it running?)", path.display()))?,
+        Target::Remote(host) => BunctlClient::connect_tcp(host, token)
+            .await
+            .with_context(|| format!("cannot reach daemon at {host}"))?,
+        Target::Fleet(_) => bail!("fleet targets must go through fleet::run"),
+    };
+    if let Some(timeout) = timeout {
+        client.set_timeout(timeout);
+    }
+    Ok(client)
+}
+
+/// Print a response for humans; returns the exit code it implies.
+pub fn render(resp: &IpcResponse) -> Result<i32> {
+    match resp {
+        IpcResponse::Success { message } => {
+            if let Some(msg) = message {
+                crate::output::note(msg);
+            }
+            Ok(0)
+        }
+        IpcResponse::Error { code, message } => {
+            eprintln!("error ({code:?}): {message}");
+            Ok(1)
+        }
+        IpcResponse::Status(status) => {
+            status::render_one(status);
+            Ok(0)
+        }
+        IpcResponse::Describe { status, banner } => {
+            status::render_one(status);
+            if !banner.is_empty() {
+                println!("banner:");
+                for line in banner {
+                    println!("  {line}");
+                }
+            }
+            Ok(0)
+        }
+        IpcResponse::StatusList { statuses: list } => {
+            status::render_list(list);
+            Ok(0)
+        }
+        IpcResponse::Logs { lines } => {
+            // Files store stripped text; give stderr entries their color
+            // back here instead.
+            for line in lines {
+                if line.contains("][stderr]") {
+                    println!("{}", crate::output::paint(line, "31"));
+                } else {
+                    println!("{line}");
+                }
+            }
+            Ok(0)
+        }
+        IpcResponse::Metrics { samples } => {
+            metrics::render(samples);
+            Ok(0)
+        }
+        IpcResponse::Health { records } => {
+            status::render_health(records);
+            Ok(0)
+        }
+        IpcResponse::ProcessTree { procs, .. } => {
+            // `bunctl top` renders this itself; a raw dump for batches.
+            println!("{}", serde_json::to_string_pretty(procs)?);
+            Ok(0)
+        }
+        IpcResponse::Config { config } => {
+            println!("{}", serde_json::to_string_pretty(config)?);
+            Ok(0)
+        }
+        IpcResponse::Audit { entries } => {
+            audit::render(entries);
+            Ok(0)
+        }
+        IpcResponse::Events { events } => {
+            events::render(events);
+            Ok(0)
+        }
+        IpcResponse::Clients { clients } => {
+            status::render_clients(clients);
+            Ok(0)
+        }
+        IpcResponse::Snapshot { snapshot } => {
+            println!("{}", serde_json::to_string_pretty(snapshot)?);
+            Ok(0)
+        }
+        IpcResponse::Batch { responses } => {
+            let mut code = 0;
+            for resp in responses {
+                if render(resp)? != 0 {
+                    code = 1;
+                }
+            }
+            Ok(code)
+        }
+        IpcResponse::Event { .. } => Ok(0),
+    }
+}
+
+/// One-line summary of a response, used by the fleet result table.
+pub fn summarize(resp: &IpcResponse) -> (bool, String) {
+    match resp {
+        IpcResponse::Success { message } => {
+            (true, message.clone().unwrap_or_else(|| "ok".into()))
+        }
+        IpcResponse::Error { code, message } => (false, format!("{code:?}: {message}")),
+        IpcResponse::Status(status) => (true, format!("{} {}", status.name, status.state)),
+        IpcResponse::Describe { status, banner } => {
+            (true, format!("{} {}, {} banner lines", status.name, status.state, banner.len()))
+        }
+        IpcResponse::StatusList { statuses } => (true, format!("{} apps", statuses.len())),
+        IpcResponse::Logs { lines } => (true, format!("{} log lines", lines.len())),
+        IpcResponse::Metrics { samples } => (true, format!("{} samples", samples.len())),
+        IpcResponse::Health { records } => (true, format!("{} health checks", records.len())),
+        IpcResponse::ProcessTree { procs, .. } => (true, format!("{} processes", procs.len())),
+        IpcResponse::Config { config } => (true, format!("config of {}", config.name)),
+        IpcResponse::Audit { entries } => (true, format!("{} audit entries", entries.len())),
+        IpcResponse::Events { events } => (true, format!("{} events", events.len())),
+        IpcResponse::Clients { clients } => (true, format!("{} clients", clients.len())),
+        IpcResponse::Snapshot { snapshot } => {
+            (true, format!("{} apps exported", snapshot.apps.len()))
+        }
+        IpcResponse::Batch { responses } => {
+            let failed = responses
+                .iter()
+                .filter(|r| matches!(r, IpcResponse::Error { .. }))
+                .count();
+            (failed == 0, format!("{} of {} ok", responses.len() - failed, responses.len()))
+        }
+        IpcResponse::Event { .. } => (true, "event".into()),
+    }
+}
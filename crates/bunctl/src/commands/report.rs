@@ -0,0 +1,151 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use bunctl_client::BunctlClient;
+
+/// How many recent log lines a report includes.
+const REPORT_LOG_LINES: usize = 500;
+
+/// How far back the metrics history in a report reaches.
+const REPORT_METRICS_SECS: u64 = 3600;
+
+/// Gather one app's diagnostics — status, config, recent logs, metrics,
+/// health history, startup banner and system info — into a single tarball
+/// for attaching to a bug report. Env vars come from the status view, so
+/// the daemon's redaction has already been applied.
+pub async fn run(
+    client: &mut BunctlClient,
+    name: &str,
+    output: Option<&std::path::Path>,
+) -> Result<i32> {
+    let mut tar = TarBuilder::default();
+
+    let (status, banner) = client.describe(name).await?;
+    tar.append("status.json", serde_json::to_vec_pretty(&status)?);
+    if !banner.is_empty() {
+        tar.append("banner.txt", banner.join("\n").into_bytes());
+    }
+
+    match client.config(name).await {
+        Ok(config) => tar.append("config.json", serde_json::to_vec_pretty(&config)?),
+        Err(err) => tar.append("config.json.error", err.to_string().into_bytes()),
+    }
+
+    match client.logs(name, REPORT_LOG_LINES, true).await {
+        Ok(lines) => tar.append("logs.txt", lines.join("\n").into_bytes()),
+        Err(err) => tar.append("logs.txt.error", err.to_string().into_bytes()),
+    }
+
+    match client.metrics(name, REPORT_METRICS_SECS).await {
+        Ok(samples) => tar.append("metrics.json", serde_json::to_vec_pretty(&samples)?),
+        Err(err) => tar.append("metrics.json.error", err.to_string().into_bytes()),
+    }
+
+    if let Ok(records) = client.health(name).await {
+        if !records.is_empty() {
+            tar.append("health.json", serde_json::to_vec_pretty(&records)?);
+        }
+    }
+
+    tar.append("system.txt", system_info(&status).into_bytes());
+
+    let path = output.map(PathBuf::from).unwrap_or_else(|| {
+        PathBuf::from(format!("bunctl-report-{name}-{}.tar", bunctl_core::time::unix_now()))
+    });
+    let files = tar.files();
+    let data = tar.finish();
+    let size = data.len();
+    std::fs::write(&path, data).with_context(|| format!("cannot write {}", path.display()))?;
+    crate::output::note(&format!(
+        "wrote {} ({files} files, {})",
+        path.display(),
+        crate::output::format_memory(size as u64)
+    ));
+    Ok(0)
+}
+
+/// Host and crash context worth having next to the logs.
+fn system_info(status: &bunctl_core::AppStatus) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("bunctl:    {}\n", env!("CARGO_PKG_VERSION")));
+    out.push_str(&format!("os:        {} {}\n", std::env::consts::OS, std::env::consts::ARCH));
+    out.push_str(&format!("generated: {}\n", bunctl_core::time::rfc3339(bunctl_core::time::unix_now())));
+    out.push_str(&format!("app state: {}\n", status.state));
+    if let Some(reason) = &status.last_exit_reason {
+        out.push_str(&format!("last exit: {reason}\n"));
+    }
+    out.push_str(&format!("restarts:  {}\n", status.restarts));
+    out
+}
+
+/// Minimal ustar archive writer: enough for a flat list of small regular
+/// files, readable by any `tar` implementation. Kept here rather than
+/// pulling in an archive crate for one command.
+#[derive(Default)]
+struct TarBuilder {
+    data: Vec<u8>,
+    files: usize,
+}
+
+impl TarBuilder {
+    fn append(&mut self, name: &str, content: Vec<u8>) {
+        let mut header = [0u8; 512];
+        header[..name.len().min(100)].copy_from_slice(&name.as_bytes()[..name.len().min(100)]);
+        header[100..107].copy_from_slice(b"0000644"); // mode
+        header[108..115].copy_from_slice(b"0000000"); // uid
+        header[116..123].copy_from_slice(b"0000000"); // gid
+        let size = format!("{:011o}", content.len());
+        header[124..135].copy_from_slice(size.as_bytes());
+        let mtime = format!("{:011o}", bunctl_core::time::unix_now());
+        header[136..147].copy_from_slice(mtime.as_bytes());
+        header[148..156].copy_from_slice(b"        "); // checksum placeholder
+        header[156] = b'0'; // regular file
+        header[257..262].copy_from_slice(b"ustar");
+        header[263..265].copy_from_slice(b"00");
+        let checksum: u32 = header.iter().map(|&b| u32::from(b)).sum();
+        header[148..154].copy_from_slice(format!("{checksum:06o}").as_bytes());
+        header[154] = 0;
+        header[155] = b' ';
+
+        self.data.extend_from_slice(&header);
+        self.data.extend_from_slice(&content);
+        // Content is padded to the 512-byte block size.
+        let padding = (512 - content.len() % 512) % 512;
+        self.data.extend(std::iter::repeat_n(0u8, padding));
+        self.files += 1;
+    }
+
+    fn files(&self) -> usize {
+        self.files
+    }
+
+    /// Close the archive with the two zero blocks tar expects.
+    fn finish(mut self) -> Vec<u8> {
+        self.data.extend(std::iter::repeat_n(0u8, 1024));
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tar_blocks_are_aligned_and_checksummed() {
+        let mut tar = TarBuilder::default();
+        tar.append("hello.txt", b"hello world\n".to_vec());
+        let data = tar.finish();
+        // One header block, one (padded) content block, two closing blocks.
+        assert_eq!(data.len(), 4 * 512);
+        assert_eq!(&data[257..262], b"ustar");
+        // The stored checksum matches a recount with the checksum field
+        // treated as spaces.
+        let stored = std::str::from_utf8(&data[148..154]).unwrap();
+        let stored = u32::from_str_radix(stored, 8).unwrap();
+        let mut sum: u32 = data[..512].iter().map(|&b| u32::from(b)).sum();
+        for &b in &data[148..156] {
+            sum = sum - u32::from(b) + u32::from(b' ');
+        }
+        assert_eq!(stored, sum);
+    }
+}
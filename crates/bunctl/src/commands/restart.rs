@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use bunctl_client::BunctlClient;
+use bunctl_core::AppState;
+use bunctl_ipc::message::{IpcRequest, IpcResponse};
+
+/// How long to wait for a restarted app to come back up before declaring
+/// the wave failed.
+const HEALTHY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Restart apps in waves of `batch`, waiting for each wave to come back up
+/// and pausing `delay` between waves. A failed wave aborts the roll, leaving
+/// the remaining apps untouched.
+pub async fn rolling(
+    client: &mut BunctlClient,
+    name: &str,
+    batch: usize,
+    delay: &str,
+) -> Result<i32> {
+    let delay = bunctl_core::time::parse_duration(delay)
+        .with_context(|| format!("invalid duration: {delay}"))?;
+    let batch = batch.max(1);
+
+    let names: Vec<String> = if name == "all" {
+        let statuses = client.list(false).await?;
+        statuses.iter().map(|s| s.name.to_string()).collect()
+    } else {
+        vec![name.to_owned()]
+    };
+    if names.is_empty() {
+        println!("nothing to restart");
+        return Ok(0);
+    }
+
+    for (i, wave) in names.chunks(batch).enumerate() {
+        if i > 0 && !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        println!("wave {}: restarting {}", i + 1, wave.join(", "));
+        for app in wave {
+            match client.request(&IpcRequest::Restart { name: app.clone(), config: None }).await? {
+                IpcResponse::Success { .. } => {}
+                IpcResponse::Error { code, message } => {
+                    bail!("restart of {app} failed ({code:?}): {message}; aborting roll");
+                }
+                _ => bail!("unexpected response restarting {app}; aborting roll"),
+            }
+        }
+        for app in wave {
+            wait_running(client, app).await?;
+        }
+    }
+    println!("rolling restart complete ({} apps)", names.len());
+    Ok(0)
+}
+
+/// Poll until the app reports Running, failing fast on Errored/Stopped.
+async fn wait_running(client: &mut BunctlClient, name: &str) -> Result<()> {
+    let deadline = tokio::time::Instant::now() + HEALTHY_TIMEOUT;
+    loop {
+        let status = client.status(name).await?;
+        match status.state {
+            AppState::Running => return Ok(()),
+            AppState::Errored | AppState::Stopped | AppState::Paused => {
+                bail!("{name} is {} after restart; aborting roll", status.state);
+            }
+            AppState::Starting | AppState::Stopping => {}
+        }
+        if tokio::time::Instant::now() >= deadline {
+            bail!("{name} did not come back up within {HEALTHY_TIMEOUT:?}; aborting roll");
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
@@ -0,0 +1,272 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use bunctl_core::{config::CONFIG_FILE, AppConfig, BunctlConfig};
+use bunctl_ipc::message::IpcRequest;
+
+/// Inline config overrides from `bunctl start` flags, merged over whatever
+/// the config file (or script-path synthesis) produced. The merged config is
+/// what the daemon receives and persists, so overrides survive restarts.
+#[derive(Debug, Default)]
+pub struct Overrides {
+    pub rename: Option<String>,
+    pub cwd: Option<PathBuf>,
+    pub env: Vec<String>,
+    pub max_memory: Option<String>,
+}
+
+impl Overrides {
+    pub fn is_empty(&self) -> bool {
+        self.rename.is_none()
+            && self.cwd.is_none()
+            && self.env.is_empty()
+            && self.max_memory.is_none()
+    }
+
+    fn apply(&self, app: &mut AppConfig) -> Result<()> {
+        if let Some(name) = &self.rename {
+            app.name = name.clone();
+        }
+        if let Some(cwd) = &self.cwd {
+            app.cwd = Some(cwd.clone());
+        }
+        for pair in &self.env {
+            let Some((key, value)) = pair.split_once('=') else {
+                bail!("invalid --env '{pair}' (expected KEY=VALUE)");
+            };
+            app.env.insert(key.to_owned(), value.to_owned());
+        }
+        if let Some(mem) = &self.max_memory {
+            app.max_memory = Some(
+                bunctl_core::units::parse_memory(mem)
+                    .with_context(|| format!("invalid --max-memory '{mem}'"))?,
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Script file extensions `bunctl start <path>` recognizes.
+const SCRIPT_EXTENSIONS: [&str; 6] = ["ts", "tsx", "js", "jsx", "mjs", "cjs"];
+
+/// Build a BlueGreen swap request for one app from the config file.
+pub fn build_swap_request(name: &str, config: Option<&Path>) -> Result<Vec<IpcRequest>> {
+    let app = load_app(name, config)?;
+    Ok(vec![IpcRequest::BlueGreen { config: Box::new(app) }])
+}
+
+/// Load one app's config from the config file.
+pub fn load_app(name: &str, config: Option<&Path>) -> Result<AppConfig> {
+    let path = config.unwrap_or(Path::new(CONFIG_FILE));
+    let config = BunctlConfig::load(path)
+        .with_context(|| format!("cannot load config from {}", path.display()))?;
+    let Some(app) = config.app(name) else {
+        bail!("app '{name}' not found in {}", path.display());
+    };
+    Ok(app.clone())
+}
+
+/// Build the Start request(s) from the config file: one per app, or a single
+/// one when a name is given.
+pub fn build_requests(
+    name: Option<&str>,
+    config: Option<&Path>,
+    overrides: &Overrides,
+    force: bool,
+) -> Result<Vec<IpcRequest>> {
+    let mut apps = resolve_apps(name, config)?;
+    if !overrides.is_empty() {
+        if overrides.rename.is_some() && apps.len() > 1 {
+            bail!("--name cannot rename multiple apps at once");
+        }
+        for app in &mut apps {
+            overrides.apply(app)?;
+            // Echo the merged result so it is obvious what the daemon will
+            // run and persist.
+            println!("effective config: {}", serde_json::to_string(app)?);
+        }
+    }
+    Ok(apps
+        .into_iter()
+        .map(|app| IpcRequest::Start { config: Box::new(app), force })
+        .collect())
+}
+
+/// Show what `start` would do — the effective config after overrides, the
+/// exact argv, cwd, log destination and limits — without contacting the
+/// daemon or spawning anything.
+pub fn dry_run(name: Option<&str>, config: Option<&Path>, overrides: &Overrides) -> Result<i32> {
+    let mut apps = resolve_apps(name, config)?;
+    if overrides.rename.is_some() && apps.len() > 1 {
+        bail!("--name cannot rename multiple apps at once");
+    }
+    let log_dir = bunctl_logging::default_log_dir();
+    for (idx, app) in apps.iter_mut().enumerate() {
+        overrides.apply(app)?;
+        if idx > 0 {
+            println!();
+        }
+        render_plan(app, &log_dir)?;
+    }
+    Ok(0)
+}
+
+/// One app's dry-run plan, in the `key: value` layout status uses.
+fn render_plan(app: &AppConfig, log_dir: &Path) -> Result<()> {
+    let id = bunctl_core::AppId::new(&app.name);
+    println!("app:      {id}");
+    println!("argv:     {}", argv(app).join(" "));
+    match &app.cwd {
+        Some(cwd) => println!("cwd:      {}", cwd.display()),
+        None => println!("cwd:      (daemon's working directory)"),
+    }
+    // Dry run never contacts the daemon, so the compiled-in default is the
+    // best guess; say so instead of asserting a path a --log-dir daemon
+    // would not use.
+    println!(
+        "log:      {} (daemon default; actual path depends on --log-dir)",
+        log_dir.join(format!("{id}.log")).display()
+    );
+    if !app.env.is_empty() {
+        println!("env:");
+        for (key, value) in &app.env {
+            println!("  {key}={value}");
+        }
+    }
+    for key in app.env_secrets.keys() {
+        println!("secret:   {key} (resolved at spawn time)");
+    }
+    if let Some(mem) = app.max_memory {
+        println!("limit:    memory {}", crate::output::format_memory(mem));
+    }
+    if let Some(cpu) = app.max_cpu_percent {
+        println!("limit:    cpu {cpu}% ({:?} mode)", app.cpu_limit_mode);
+    }
+    println!("config:   {}", serde_json::to_string(app)?);
+    Ok(())
+}
+
+/// The argv the supervisor would spawn, mirroring its interpreter and
+/// container handling (without the resolved pinned-bun path, which may not
+/// be installed yet).
+fn argv(app: &AppConfig) -> Vec<String> {
+    let mut argv = Vec::new();
+    match app.exec_kind {
+        bunctl_core::config::ExecKind::Process => {
+            match &app.interpreter {
+                Some(interpreter) => {
+                    argv.push(interpreter.clone());
+                    argv.extend(app.interpreter_args.iter().cloned());
+                    argv.push(app.command.clone());
+                }
+                None => argv.push(app.command.clone()),
+            }
+            argv.extend(app.args.iter().cloned());
+        }
+        bunctl_core::config::ExecKind::Container => {
+            argv.push(app.runtime.clone().unwrap_or_else(|| "docker".to_owned()));
+            argv.extend(["run", "--rm", "--sig-proxy=true", "--name"].map(str::to_owned));
+            argv.push(format!("bunctl-{}", app.name));
+            argv.push(app.command.clone());
+            argv.extend(app.args.iter().cloned());
+        }
+    }
+    argv
+}
+
+/// The app configs a `start` invocation refers to, before overrides.
+fn resolve_apps(name: Option<&str>, config: Option<&Path>) -> Result<Vec<AppConfig>> {
+    // pm2-style ergonomics: a bare script path (or package.json script name)
+    // needs no config file at all.
+    if config.is_none() {
+        if let Some(app) = name.and_then(script_config) {
+            return Ok(vec![app]);
+        }
+    }
+    let path = config.unwrap_or(Path::new(CONFIG_FILE));
+    let config = BunctlConfig::load(path)
+        .with_context(|| format!("cannot load config from {}", path.display()))?;
+
+    match name {
+        Some(name) => {
+            let Some(app) = config.app(name) else {
+                bail!("app '{name}' not found in {}", path.display());
+            };
+            Ok(vec![app.clone()])
+        }
+        None => {
+            if config.apps.is_empty() {
+                bail!("no apps defined in {}", path.display());
+            }
+            Ok(config.apps)
+        }
+    }
+}
+
+/// Synthesize a config for `bunctl start ./server.ts` or
+/// `bunctl start <package.json script>`; `None` when `name` is neither.
+fn script_config(name: &str) -> Option<AppConfig> {
+    let path = Path::new(name);
+    let is_script = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| SCRIPT_EXTENSIONS.contains(&ext));
+    if is_script && path.is_file() {
+        let abs = path.canonicalize().ok()?;
+        let file = abs.file_name()?.to_str()?.to_owned();
+        let stem = abs.file_stem()?.to_str()?.to_owned();
+        return Some(AppConfig {
+            name: stem,
+            command: "bun".to_owned(),
+            args: vec![file],
+            cwd: abs.parent().map(Path::to_path_buf),
+            ..AppConfig::default()
+        });
+    }
+    if package_json_has_script(name) {
+        return Some(AppConfig {
+            name: name.to_owned(),
+            command: "bun".to_owned(),
+            args: vec!["run".to_owned(), name.to_owned()],
+            cwd: std::env::current_dir().ok(),
+            ..AppConfig::default()
+        });
+    }
+    None
+}
+
+/// Whether `./package.json` declares `name` under `"scripts"`.
+fn package_json_has_script(name: &str) -> bool {
+    let Ok(data) = std::fs::read_to_string("package.json") else {
+        return false;
+    };
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(&data) else {
+        return false;
+    };
+    json.get("scripts").and_then(|s| s.get(name)).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn synthesizes_config_from_script_path() {
+        let dir = std::env::temp_dir().join(format!("bunctl-script-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("server.ts");
+        std::fs::write(&script, "export {};\n").unwrap();
+        let app = script_config(script.to_str().unwrap()).unwrap();
+        assert_eq!(app.name, "server");
+        assert_eq!(app.command, "bun");
+        assert_eq!(app.args, vec!["server.ts"]);
+        assert!(app.cwd.is_some());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn plain_names_fall_through_to_the_config_file() {
+        assert!(script_config("my-api").is_none());
+        assert!(script_config("does-not-exist.ts").is_none());
+    }
+}
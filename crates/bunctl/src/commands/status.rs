@@ -0,0 +1,188 @@
+use bunctl_core::AppStatus;
+
+use crate::output::{format_memory, format_uptime, sparkline_f32, state_label};
+
+/// Render one app's status as `key: value` lines.
+pub fn render_one(status: &AppStatus) {
+    println!("name:     {}", status.name);
+    if let Some(original) = &status.original_name {
+        println!("given as: {original} (sanitized)");
+    }
+    if let (Some(i), Some(n)) = (status.instance_id, status.instances_total) {
+        println!("instance: {i} of {n}");
+    }
+    if let Some(description) = &status.description {
+        println!("desc:     {description}");
+    }
+    if !status.tags.is_empty() {
+        println!("tags:     {}", status.tags.join(", "));
+    }
+    println!("state:    {}", state_label(status.state));
+    if let Some(ready) = status.ready {
+        println!("ready:    {}", if ready { "yes" } else { "no" });
+    }
+    if let Some(mode) = &status.on_demand {
+        println!("demand:   {mode}");
+    }
+    if let Some(pid) = status.pid {
+        println!("pid:      {pid}");
+    }
+    if let Some(cpu) = status.cpu_percent {
+        println!("cpu:      {cpu:.1}% {}", sparkline_f32(&status.cpu_history));
+    }
+    if let Some(mem) = status.memory_bytes {
+        let history: Vec<f32> = status.memory_history.iter().map(|&b| b as f32).collect();
+        println!("memory:   {} {}", format_memory(mem), sparkline_f32(&history));
+    }
+    if let Some(uptime) = status.uptime_secs {
+        println!("uptime:   {}", format_uptime(uptime));
+    }
+    if let Some(cwd) = &status.cwd {
+        println!("cwd:      {}", cwd.display());
+    }
+    if let Some(threads) = status.threads {
+        println!("threads:  {threads}");
+    }
+    if let Some(fds) = status.open_files {
+        println!("fds:      {fds}");
+    }
+    if let Some(heap) = status.heap_bytes {
+        println!("heap:     {}", format_memory(heap));
+    }
+    if let Some(lag) = status.event_loop_lag_ms {
+        println!("loop lag: {lag:.1}ms");
+    }
+    if !status.env.is_empty() {
+        println!("env:");
+        for (key, value) in &status.env {
+            println!("  {key}={value}");
+        }
+    }
+    if !status.log_metrics.is_empty() {
+        println!(
+            "filtered: {} dropped, {} sampled",
+            status.log_metrics.dropped, status.log_metrics.sampled
+        );
+    }
+    if let Some(reason) = &status.last_exit_reason {
+        println!("exit:     {reason}");
+    }
+    println!("restarts: {}", status.restarts);
+    if let Some(spawn) = &status.spawn_info {
+        println!(
+            "spawned:  {} (generation {})",
+            bunctl_core::time::rfc3339(spawn.spawned_at),
+            spawn.generation
+        );
+        println!("argv:     {}", spawn.argv.join(" "));
+        if let Some(version) = &spawn.interpreter_version {
+            println!("runtime:  {version}");
+        }
+        if !spawn.env.is_empty() {
+            println!("spawn env:");
+            for (key, value) in &spawn.env {
+                println!("  {key}={value}");
+            }
+        }
+    }
+    if !status.instances.is_empty() {
+        println!("members:");
+        for member in &status.instances {
+            println!(
+                "  {}-{}  {}  pid {}  {}",
+                status.name,
+                member.instance,
+                state_label(member.state),
+                member.pid.map_or_else(|| "-".into(), |p| p.to_string()),
+                member.memory_bytes.map_or_else(|| "-".into(), format_memory),
+            );
+        }
+    }
+}
+
+/// Render health check history: a ●-per-attempt timeline (oldest first),
+/// the latest result and the current pass/fail streak.
+pub fn render_health(records: &[bunctl_core::HealthRecord]) {
+    if records.is_empty() {
+        println!("no health checks recorded yet");
+        return;
+    }
+    let timeline: String = records
+        .iter()
+        .map(|r| crate::output::paint("\u{25cf}", if r.ok { "32" } else { "31" }))
+        .collect();
+    println!("history:  {timeline}");
+    let last = records.last().expect("non-empty");
+    match &last.error {
+        None => println!("latest:   ok ({}ms)", last.latency_ms),
+        Some(error) => println!("latest:   failed ({}ms): {error}", last.latency_ms),
+    }
+    let streak: Vec<_> = records.iter().rev().take_while(|r| r.ok == last.ok).collect();
+    println!(
+        "streak:   {} {} since {}",
+        streak.len(),
+        if last.ok { "passing" } else { "failing" },
+        bunctl_core::time::rfc3339(streak.last().expect("non-empty").ts)
+    );
+}
+
+/// Render a compact one-line-per-app view (shared with `list`).
+pub fn render_list(list: &[AppStatus]) {
+    print!("{}", super::list::table(list, false));
+}
+
+/// Render the aggregated footer for `status --summary`: totals across all
+/// managed apps, counts by state and the daemon's own usage.
+pub fn render_summary(list: &[AppStatus], daemon: Option<&AppStatus>) {
+    let total_mem: u64 = list.iter().filter_map(|s| s.memory_bytes).sum();
+    let total_cpu: f64 = list.iter().filter_map(|s| s.cpu_percent).sum();
+
+    let mut by_state: std::collections::BTreeMap<String, usize> = Default::default();
+    for status in list {
+        *by_state.entry(status.state.to_string()).or_default() += 1;
+    }
+    let states = by_state
+        .iter()
+        .map(|(state, n)| format!("{n} {state}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    println!();
+    println!(
+        "total:    {} apps ({}), {:.1}% cpu, {} memory",
+        list.len(),
+        if states.is_empty() { "none".into() } else { states },
+        total_cpu,
+        format_memory(total_mem)
+    );
+    if let Some(daemon) = daemon {
+        println!(
+            "daemon:   pid {}, {:.1}% cpu, {} memory, up {}",
+            daemon.pid.unwrap_or(0),
+            daemon.cpu_percent.unwrap_or(0.0),
+            format_memory(daemon.memory_bytes.unwrap_or(0)),
+            format_uptime(daemon.uptime_secs.unwrap_or(0))
+        );
+    }
+}
+
+/// Render connected clients as a PEER / PID / USER / VERSION / CONNECTED
+/// table (`status --clients`).
+pub fn render_clients(clients: &[bunctl_ipc::message::ClientInfo]) {
+    if clients.is_empty() {
+        println!("no clients connected");
+        return;
+    }
+    println!("{:<18} {:>7} {:<12} {:<10} CONNECTED", "PEER", "PID", "USER", "VERSION");
+    for client in clients {
+        println!(
+            "{:<18} {:>7} {:<12} {:<10} {}",
+            client.peer,
+            client.pid,
+            client.user,
+            client.version,
+            format_uptime(client.connected_secs)
+        );
+    }
+}
+
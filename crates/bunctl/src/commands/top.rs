@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use bunctl_client::BunctlClient;
+use bunctl_core::ProcessNode;
+
+use crate::output::{format_memory, truncate};
+
+/// Live process tree view of one app, refreshing like `top` until
+/// interrupted. CPU per process is computed here from the tick deltas
+/// between two refreshes, so the first frame shows none.
+pub async fn run(client: &mut BunctlClient, name: &str, interval: &str, once: bool) -> Result<i32> {
+    let interval = bunctl_core::time::parse_duration(interval)
+        .with_context(|| format!("invalid duration: {interval}"))?;
+    let mut prev: HashMap<u32, u64> = HashMap::new();
+    let mut prev_at = Instant::now();
+    let mut first = true;
+    loop {
+        let (procs, clock_hz) = client.process_tree(name).await?;
+        let elapsed = prev_at.elapsed().as_secs_f64();
+        prev_at = Instant::now();
+        let mut cpu: HashMap<u32, f64> = HashMap::new();
+        for proc in &procs {
+            if let (Some(ticks), Some(&before)) = (proc.cpu_ticks, prev.get(&proc.pid)) {
+                let secs = ticks.saturating_sub(before) as f64 / clock_hz.max(1) as f64;
+                cpu.insert(proc.pid, secs / elapsed.max(0.001) * 100.0);
+            }
+        }
+        prev = procs.iter().filter_map(|p| p.cpu_ticks.map(|t| (p.pid, t))).collect();
+
+        if !once {
+            if !first {
+                // Clear and rehome instead of scrolling, like top does.
+                print!("\x1b[2J\x1b[H");
+            }
+            first = false;
+        }
+        println!("{name}: {} processes, {} total", procs.len(), format_memory(total_memory(&procs)));
+        for line in render_lines(&procs, &cpu) {
+            println!("{line}");
+        }
+        std::io::stdout().flush().ok();
+        if once {
+            return Ok(0);
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+fn total_memory(procs: &[ProcessNode]) -> u64 {
+    procs.iter().filter_map(|p| p.memory_bytes).sum()
+}
+
+/// The table body: one row per process, indented under its parent.
+fn render_lines(procs: &[ProcessNode], cpu: &HashMap<u32, f64>) -> Vec<String> {
+    let mut lines = vec![format!("{:>7} {:>6} {:>9} {:>4}  {}", "PID", "CPU", "MEM", "THR", "COMMAND")];
+    // The root is the one process whose parent is outside the tree.
+    let roots: Vec<&ProcessNode> =
+        procs.iter().filter(|p| !procs.iter().any(|q| q.pid == p.ppid)).collect();
+    for root in roots {
+        push_subtree(procs, root, 0, cpu, &mut lines);
+    }
+    lines
+}
+
+fn push_subtree(
+    procs: &[ProcessNode],
+    node: &ProcessNode,
+    depth: usize,
+    cpu: &HashMap<u32, f64>,
+    lines: &mut Vec<String>,
+) {
+    let pct = cpu.get(&node.pid).map_or_else(|| "-".into(), |pct| format!("{pct:.1}%"));
+    let mem = node.memory_bytes.map_or_else(|| "-".into(), format_memory);
+    let threads = node.threads.map_or_else(|| "-".into(), |n| n.to_string());
+    let command = if node.command.is_empty() { &node.name } else { &node.command };
+    lines.push(format!(
+        "{:>7} {pct:>6} {mem:>9} {threads:>4}  {:indent$}{}",
+        node.pid,
+        "",
+        truncate(command, 80 - 2 * depth.min(20)),
+        indent = 2 * depth,
+    ));
+    for child in procs.iter().filter(|p| p.ppid == node.pid && p.pid != node.pid) {
+        push_subtree(procs, child, depth + 1, cpu, lines);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(pid: u32, ppid: u32, name: &str) -> ProcessNode {
+        ProcessNode {
+            pid,
+            ppid,
+            name: name.into(),
+            command: String::new(),
+            memory_bytes: Some(1024),
+            cpu_ticks: Some(0),
+            threads: Some(1),
+        }
+    }
+
+    #[test]
+    fn indents_children_under_their_parent() {
+        let procs =
+            vec![node(10, 1, "bun"), node(20, 10, "worker"), node(30, 20, "grandchild")];
+        let lines = render_lines(&procs, &HashMap::new());
+        assert_eq!(lines.len(), 4); // header + three processes
+        assert!(lines[1].ends_with("bun"));
+        assert!(lines[2].ends_with("  worker"));
+        assert!(lines[3].ends_with("    grandchild"));
+    }
+}
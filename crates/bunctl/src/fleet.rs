@@ -0,0 +1,102 @@
+//! Fan a command out to several daemons and merge the results.
+
+use anyhow::Result;
+use bunctl_ipc::message::IpcRequest;
+use tokio::task::JoinSet;
+
+use crate::commands;
+use crate::Cli;
+
+/// Where a CLI invocation is directed.
+pub enum Target {
+    /// The local daemon via its Unix socket.
+    Local(std::path::PathBuf),
+    /// A single remote daemon (`--host`).
+    Remote(String),
+    /// Several remote daemons (`--hosts`).
+    Fleet(Vec<String>),
+}
+
+impl Target {
+    pub fn from_cli(cli: &Cli) -> Self {
+        if !cli.hosts.is_empty() {
+            Target::Fleet(cli.hosts.clone())
+        } else if let Some(host) = &cli.host {
+            Target::Remote(host.clone())
+        } else {
+            let path = cli
+                .socket
+                .clone()
+                .unwrap_or_else(bunctl_ipc::socket_path::default_socket_path);
+            Target::Local(path)
+        }
+    }
+}
+
+/// Send `requests` to every host concurrently and render a merged result
+/// table. Returns exit code 1 when any host failed.
+pub async fn run(
+    hosts: &[String],
+    token: Option<&str>,
+    timeout: Option<std::time::Duration>,
+    requests: &[IpcRequest],
+) -> Result<i32> {
+    let mut set = JoinSet::new();
+    for host in hosts {
+        let host = host.clone();
+        let token = token.map(str::to_owned);
+        let requests = requests.to_vec();
+        set.spawn(async move {
+            let outcome = run_one(&host, token.as_deref(), timeout, &requests).await;
+            (host, outcome)
+        });
+    }
+
+    let mut results: Vec<(String, Result<String, String>)> = Vec::with_capacity(hosts.len());
+    while let Some(joined) = set.join_next().await {
+        let (host, outcome) = joined.expect("fleet task panicked");
+        results.push((host, outcome));
+    }
+    // JoinSet completion order is arbitrary; keep the table stable.
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let width = results.iter().map(|(h, _)| h.len()).max().unwrap_or(4).max(4);
+    println!("{:<width$}  {:<6}  DETAIL", "HOST", "RESULT");
+    let mut failed = false;
+    for (host, outcome) in &results {
+        match outcome {
+            Ok(detail) => println!("{host:<width$}  {:<6}  {detail}", "ok"),
+            Err(detail) => {
+                failed = true;
+                println!("{host:<width$}  {:<6}  {detail}", "FAIL");
+            }
+        }
+    }
+    if failed {
+        eprintln!("one or more hosts failed");
+    }
+    Ok(if failed { 1 } else { 0 })
+}
+
+/// Run all requests against one host, collapsing the outcome to a single
+/// summary line for the table.
+async fn run_one(
+    host: &str,
+    token: Option<&str>,
+    timeout: Option<std::time::Duration>,
+    requests: &[IpcRequest],
+) -> Result<String, String> {
+    let mut client = commands::connect(&Target::Remote(host.to_owned()), token, timeout)
+        .await
+        .map_err(|e| format!("{e:#}"))?;
+    let mut summaries = Vec::with_capacity(requests.len());
+    for req in requests {
+        let resp = client.request(req).await.map_err(|e| e.to_string())?;
+        let (ok, summary) = commands::summarize(&resp);
+        if !ok {
+            return Err(summary);
+        }
+        summaries.push(summary);
+    }
+    Ok(summaries.join("; "))
+}
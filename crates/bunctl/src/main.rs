@@ -0,0 +1,312 @@
+mod commands;
+mod fleet;
+mod output;
+mod profile;
+
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+/// PM2-style process manager for Bun applications.
+#[derive(Debug, Parser)]
+#[command(name = "bunctl", version, about)]
+pub struct Cli {
+    /// Connection profile from ~/.config/bunctl/profiles.json (also read
+    /// from BUNCTL_PROFILE); explicit --socket/--host/--token flags win.
+    #[arg(long, global = true, env = "BUNCTL_PROFILE")]
+    pub profile: Option<String>,
+
+    /// Path of the daemon control socket (default: per-user runtime dir).
+    #[arg(long, global = true)]
+    pub socket: Option<PathBuf>,
+
+    /// Manage a remote daemon over TCP (`host:port`).
+    #[arg(long, global = true, conflicts_with = "hosts")]
+    pub host: Option<String>,
+
+    /// Fan the command out to several remote daemons (`h1:port,h2:port`).
+    #[arg(long, global = true, value_delimiter = ',')]
+    pub hosts: Vec<String>,
+
+    /// Auth token for remote daemons (also read from BUNCTL_TOKEN).
+    #[arg(long, global = true, env = "BUNCTL_TOKEN")]
+    pub token: Option<String>,
+
+    /// Fixed request timeout in seconds, overriding the per-command
+    /// defaults (fast for queries, generous for start/stop).
+    #[arg(long, global = true, value_name = "SECS")]
+    pub timeout: Option<u64>,
+
+    /// Disable colored output (the NO_COLOR env var does the same).
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Use SI units (kB/MB) instead of binary (KiB/MiB) for sizes.
+    #[arg(long, global = true)]
+    pub si: bool,
+
+    /// Only print essential lines and errors; drop confirmations.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Also print IPC timing and the daemon's raw responses (to stderr).
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Start an app from bunctl.json (all apps when no name is given), or a
+    /// bare script path / package.json script without any config file.
+    Start {
+        name: Option<String>,
+        /// Config file to read (default: ./bunctl.json).
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        /// Register the app under this name instead of the configured one.
+        #[arg(long = "name")]
+        rename: Option<String>,
+        /// Working directory override.
+        #[arg(long)]
+        cwd: Option<PathBuf>,
+        /// Environment overrides (`KEY=VALUE`, repeatable), merged over the
+        /// config file's env.
+        #[arg(long = "env", value_name = "KEY=VALUE")]
+        env: Vec<String>,
+        /// Memory limit override, e.g. `512M` or `2G`.
+        #[arg(long)]
+        max_memory: Option<String>,
+        /// Show what would happen — effective config, argv, cwd, log
+        /// destination, limits — without contacting the daemon.
+        #[arg(long)]
+        dry_run: bool,
+        /// Restart the app if it is already running instead of failing.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Bring an externally started process under management.
+    Adopt {
+        name: String,
+        /// PID of the running process to adopt.
+        #[arg(long)]
+        pid: u32,
+    },
+    /// Stop a running app.
+    Stop { name: String },
+    /// Restart an app (or restart in waves with --rolling).
+    Restart {
+        /// App name, or `all` with --rolling.
+        name: String,
+        /// Restart in batches, waiting for each batch to come back up and
+        /// aborting if one fails.
+        #[arg(long)]
+        rolling: bool,
+        /// Apps per wave.
+        #[arg(long, default_value_t = 1, requires = "rolling")]
+        batch: usize,
+        /// Pause between waves, e.g. `10s`.
+        #[arg(long, default_value = "0s", requires = "rolling")]
+        delay: String,
+        /// Re-read the config file and apply its env/args on this restart
+        /// instead of reusing the daemon's cached snapshot.
+        #[arg(long, conflicts_with = "rolling")]
+        update_env: bool,
+        /// Config file for --update-env (default: ./bunctl.json).
+        #[arg(short, long, requires = "update_env")]
+        config: Option<PathBuf>,
+    },
+    /// Remove an app from the daemon, stopping it first.
+    Delete {
+        name: String,
+        /// Also remove the app's log files and recorded metrics.
+        #[arg(long)]
+        purge_logs: bool,
+        /// Keep log files even when the app's config says to purge them.
+        #[arg(long, conflicts_with = "purge_logs")]
+        keep_logs: bool,
+    },
+    /// Rename a stopped app, moving its logs and metrics with it.
+    Rename { old: String, new: String },
+    /// Run an app's deploy workflow: git pull, install steps, reload.
+    Deploy {
+        /// App to deploy (default: every app with a deploy section).
+        name: Option<String>,
+        /// Config file to read (default: ./bunctl.json).
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+    },
+    /// Blue/green swap: bring up a candidate instance from the config file
+    /// and promote it once healthy.
+    Swap {
+        name: String,
+        /// Config file to read (default: ./bunctl.json).
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+    },
+    /// Flip an app back to its previous release and restart it.
+    Rollback {
+        name: String,
+        /// Config file to read (default: ./bunctl.json).
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+    },
+    /// Show how the daemon's cached config differs from the config file.
+    Diff {
+        name: String,
+        /// Config file to compare against (default: ./bunctl.json).
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+    },
+    /// Show status of one app or all apps.
+    Status {
+        name: Option<String>,
+        /// Append an aggregated footer: totals, counts by state and the
+        /// daemon's own usage.
+        #[arg(long)]
+        summary: bool,
+        /// List clients currently connected to the daemon instead.
+        #[arg(long, conflicts_with_all = ["name", "summary"])]
+        clients: bool,
+    },
+    /// Detailed view of one app: status plus the startup banner (the first
+    /// lines of output captured at its most recent start attempt).
+    Describe { name: String },
+    /// Bundle one app's diagnostics (status, config, recent logs, metrics,
+    /// health history, system info) into a tarball for a bug report.
+    Report {
+        name: String,
+        /// Where to write the archive; defaults to
+        /// `bunctl-report-<app>-<timestamp>.tar` in the current directory.
+        #[arg(long, short)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Check the environment — daemon socket, directory permissions, bun
+    /// binary, ulimits, clock — and print pass/fail with remediation hints.
+    Doctor,
+    /// List apps as an aligned table.
+    List {
+        /// Also list orphan log files from apps no longer managed.
+        #[arg(long)]
+        all: bool,
+        /// Output the raw status list as JSON.
+        #[arg(long)]
+        json: bool,
+        /// Include CPU, uptime and restart columns.
+        #[arg(long)]
+        wide: bool,
+        /// Sort order of the table.
+        #[arg(long, value_enum, default_value = "name")]
+        sort: commands::list::SortKey,
+        /// Only show apps carrying this tag.
+        #[arg(long)]
+        tag: Option<String>,
+    },
+    /// Show recent log lines for an app (all apps when no name is given).
+    Logs {
+        name: Option<String>,
+        /// Number of lines from the end of the log.
+        #[arg(short = 'n', long, default_value_t = 50)]
+        lines: usize,
+        /// Read the log file even if the app is not currently managed.
+        #[arg(long)]
+        include_stopped: bool,
+        /// Only show entries containing this substring; multiline entries
+        /// (grouped stack traces) are returned whole.
+        #[arg(long)]
+        grep: Option<String>,
+        /// Validate the app's rotation manifest (sizes, SHA-256 chain)
+        /// against the local log directory instead of showing lines.
+        #[arg(long, conflicts_with_all = ["grep", "include_stopped"])]
+        verify: bool,
+        /// Interleave all apps chronologically with colored `[app]`
+        /// prefixes instead of per-app blocks.
+        #[arg(long, conflicts_with = "name")]
+        merge: bool,
+        /// Keep streaming new lines as they are captured.
+        #[arg(short, long)]
+        follow: bool,
+        /// Show the daemon's own log instead of an app's.
+        #[arg(long, conflicts_with_all = ["name", "merge", "follow", "verify"])]
+        daemon: bool,
+    },
+    /// Show recent health check results for an app.
+    Health { name: String },
+    /// Live process tree of an app — children, per-process CPU and memory —
+    /// refreshing like `top` until interrupted.
+    Top {
+        name: String,
+        /// Refresh interval, e.g. `2s` or `10s`.
+        #[arg(long, default_value = "2s")]
+        interval: String,
+        /// Print one snapshot and exit instead of refreshing.
+        #[arg(long)]
+        once: bool,
+    },
+    /// Show persisted resource samples for an app.
+    Metrics {
+        name: String,
+        /// Time window to show, e.g. `90s`, `15m`, `1h`, `2d`.
+        #[arg(long, default_value = "1h")]
+        since: String,
+    },
+    /// Show recorded management actions (who did what, and when).
+    Audit {
+        /// Time window to show, e.g. `90s`, `15m`, `1h`, `2d`.
+        #[arg(long, default_value = "1d")]
+        since: String,
+    },
+    /// Show persisted daemon events (what the daemon did, and when).
+    Events {
+        /// Time window to show, e.g. `90s`, `15m`, `1h`, `2d`.
+        #[arg(long, default_value = "1h")]
+        since: String,
+        /// Only events for apps matching this glob, e.g. `worker-*`.
+        #[arg(long)]
+        app: Option<String>,
+        /// Only events of these types, e.g. `process_exited`; repeatable.
+        #[arg(long = "type")]
+        event_type: Vec<String>,
+        /// Drop events below this severity: `info`, `warning` or `error`.
+        #[arg(long)]
+        severity: Option<String>,
+    },
+    /// Dump the daemon's full app registry as JSON on stdout.
+    Export,
+    /// Replay an exported snapshot into the daemon.
+    Import {
+        /// Snapshot file produced by `bunctl export`.
+        file: PathBuf,
+    },
+    /// Check whether the daemon is reachable.
+    Ping,
+    /// Ask the daemon to shut down.
+    Shutdown,
+}
+
+#[tokio::main]
+async fn main() {
+    // Rust starts with SIGPIPE ignored, which turns `bunctl export | head`
+    // into a broken-pipe panic; die quietly like other Unix CLIs instead.
+    #[cfg(unix)]
+    unsafe {
+        libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+    }
+    let mut cli = Cli::parse();
+    output::init(cli.no_color, cli.si, cli.quiet, cli.verbose);
+    if let Some(name) = cli.profile.clone() {
+        if let Err(err) = profile::apply(&mut cli, &name) {
+            eprintln!("error: {err:#}");
+            std::process::exit(1);
+        }
+    }
+    match commands::run(cli).await {
+        Ok(code) => std::process::exit(code),
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Shared human-output helpers: color, units, truncation.
+//!
+//! Everything that prints for humans (status, list, the fleet table) goes
+//! through here so flags like `--no-color` and `--si` apply everywhere.
+
+use std::sync::OnceLock;
+
+use bunctl_core::AppState;
+
+struct OutputPrefs {
+    color: bool,
+    si_units: bool,
+    quiet: bool,
+    verbose: bool,
+}
+
+static PREFS: OnceLock<OutputPrefs> = OnceLock::new();
+
+/// Install the output preferences from CLI flags and environment, once at
+/// startup. Color is disabled by `--no-color` or a set `NO_COLOR` env var
+/// (<https://no-color.org>).
+pub fn init(no_color: bool, si_units: bool, quiet: bool, verbose: bool) {
+    let color = !no_color && std::env::var_os("NO_COLOR").is_none();
+    let _ = PREFS.set(OutputPrefs { color, si_units, quiet, verbose });
+}
+
+fn prefs() -> &'static OutputPrefs {
+    PREFS.get_or_init(|| OutputPrefs {
+        color: std::env::var_os("NO_COLOR").is_none(),
+        si_units: false,
+        quiet: false,
+        verbose: false,
+    })
+}
+
+/// Print a confirmation line, unless `--quiet` is active. Essential output
+/// (tables, logs, errors) prints unconditionally and does not go through
+/// here.
+pub fn note(message: &str) {
+    if !prefs().quiet {
+        println!("{message}");
+    }
+}
+
+/// Print a diagnostic line to stderr, only when `--verbose` is active.
+/// Dimmed so it stays visually apart from real output.
+pub fn detail(message: &str) {
+    if prefs().verbose {
+        eprintln!("{}", paint(message, "90"));
+    }
+}
+
+/// Wrap `text` in an ANSI color code, unless color is disabled.
+pub fn paint(text: &str, code: &str) -> String {
+    if prefs().color {
+        format!("\x1b[{code}m{text}\x1b[0m")
+    } else {
+        text.to_owned()
+    }
+}
+
+/// A state name colored by severity: green when running, red when errored,
+/// yellow in transition or paused, dim when stopped.
+pub fn state_label(state: AppState) -> String {
+    let code = match state {
+        AppState::Running => "32",
+        AppState::Errored => "31",
+        AppState::Starting | AppState::Stopping | AppState::Paused => "33",
+        AppState::Stopped => "90",
+    };
+    paint(&state.to_string(), code)
+}
+
+/// Truncate to at most `width` characters, ending in `…` when cut. Counts
+/// characters, not bytes, so multi-byte names stay intact.
+pub fn truncate(s: &str, width: usize) -> String {
+    if s.chars().count() <= width {
+        return s.to_owned();
+    }
+    let mut out: String = s.chars().take(width.saturating_sub(1)).collect();
+    out.push('…');
+    out
+}
+
+/// Format bytes in the configured unit system (binary by default, SI with
+/// `--si`).
+pub fn format_memory(bytes: u64) -> String {
+    format_memory_with(bytes, prefs().si_units)
+}
+
+fn format_memory_with(bytes: u64, si: bool) -> String {
+    let (step, units): (f64, [&str; 4]) = if si {
+        (1000.0, ["B", "kB", "MB", "GB"])
+    } else {
+        (1024.0, ["B", "KiB", "MiB", "GiB"])
+    };
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= step && unit < units.len() - 1 {
+        value /= step;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes}B")
+    } else {
+        format!("{value:.1}{}", units[unit])
+    }
+}
+
+pub fn format_uptime(secs: u64) -> String {
+    if secs >= 86_400 {
+        format!("{}d{}h", secs / 86_400, (secs % 86_400) / 3600)
+    } else if secs >= 3600 {
+        format!("{}h{}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{}m{}s", secs / 60, secs % 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// Render a series as a unicode sparkline, scaled to its own maximum.
+pub fn sparkline_f32(values: &[f32]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = values.iter().copied().fold(0.0f32, f32::max);
+    if max <= 0.0 {
+        return values.iter().map(|_| BARS[0]).collect();
+    }
+    values
+        .iter()
+        .map(|&v| {
+            let idx = ((v / max) * (BARS.len() - 1) as f32).round() as usize;
+            BARS[idx.min(BARS.len() - 1)]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_by_characters() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("a-much-longer-name", 8), "a-much-…");
+        assert_eq!(truncate("ünïcödé-name", 8), "ünïcödé…");
+    }
+
+    #[test]
+    fn memory_units_binary_and_si() {
+        assert_eq!(format_memory_with(2 * 1024 * 1024, false), "2.0MiB");
+        assert_eq!(format_memory_with(2_000_000, true), "2.0MB");
+        assert_eq!(format_memory_with(512, false), "512B");
+    }
+}
@@ -0,0 +1,72 @@
+//! Named connection profiles, so `bunctl --profile staging` can target a
+//! different daemon (another socket on this machine, or a remote one) with
+//! its token, without spelling the flags out every time.
+//!
+//! Profiles live in a client-side JSON file:
+//!
+//! ```json
+//! {
+//!     "staging": { "host": "10.0.0.2:9631", "token": "..." },
+//!     "system": { "socket": "/run/bunctl/bunctl.sock" }
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::Cli;
+
+/// One profile: where the daemon is and how to authenticate. At most one of
+/// `socket` and `host` makes sense; explicit CLI flags still win.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Profile {
+    socket: Option<PathBuf>,
+    host: Option<String>,
+    token: Option<String>,
+}
+
+/// Fill connection fields of `cli` from the named profile, keeping any the
+/// user set explicitly on the command line.
+pub fn apply(cli: &mut Cli, name: &str) -> Result<()> {
+    let path = profiles_path();
+    let text = std::fs::read_to_string(&path)
+        .with_context(|| format!("cannot read profiles file {}", path.display()))?;
+    let mut profiles: HashMap<String, Profile> = serde_json::from_str(&text)
+        .with_context(|| format!("invalid profiles file {}", path.display()))?;
+    let Some(profile) = profiles.remove(name) else {
+        let mut known: Vec<&String> = profiles.keys().collect();
+        known.sort();
+        bail!(
+            "no profile named {name} in {} (defined: {})",
+            path.display(),
+            known.iter().map(|k| k.as_str()).collect::<Vec<_>>().join(", ")
+        );
+    };
+    if profile.socket.is_some() && profile.host.is_some() {
+        bail!("profile {name} sets both socket and host");
+    }
+    if cli.socket.is_none() && cli.host.is_none() && cli.hosts.is_empty() {
+        cli.socket = profile.socket;
+        cli.host = profile.host;
+    }
+    if cli.token.is_none() {
+        cli.token = profile.token;
+    }
+    Ok(())
+}
+
+/// `$XDG_CONFIG_HOME/bunctl/profiles.json`, falling back to
+/// `~/.config/bunctl/profiles.json`.
+fn profiles_path() -> PathBuf {
+    if let Some(dir) = std::env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        return PathBuf::from(dir).join("bunctl").join("profiles.json");
+    }
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .join(".config/bunctl/profiles.json")
+}